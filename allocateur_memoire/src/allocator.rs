@@ -1,35 +1,65 @@
 // Import des traits et types nécessaires à la gestion de la mémoire bas-niveau
 use core::alloc::{GlobalAlloc, Layout};          // Trait GlobalAlloc + Layout de blocs mémoire
+use core::cell::UnsafeCell;                      // Le heap est modifié à travers &self (alloc ne reçoit pas &mut self)
 use core::ptr::null_mut;                         // Pour retourner un pointeur nul si échec d'allocation
-use core::sync::atomic::{AtomicUsize, Ordering}; // Permet une allocation thread-safe via des opérations atomiques
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering}; // Permet une allocation thread-safe via des opérations atomiques
 
-// Taille totale du heap en octets : ici, 64 Ko
-const HEAP_SIZE: usize = 64 * 1024;
+// Taille du heap de l'allocateur global en octets : ici, 64 Ko
+// La feature "large-heap" le porte à 512 Ko (activée par "regex" : chaque motif compilé y garde 15 à 20 Ko)
+#[cfg(not(feature = "large-heap"))]
+pub const HEAP_SIZE: usize = 64 * 1024;
+#[cfg(feature = "large-heap")]
+pub const HEAP_SIZE: usize = 512 * 1024;
 
-// Structure représentant notre heap statique, aligné sur 8 octets
-// Le #[repr(align(N))] garantit un alignement mémoire pour les architectures modernes
-#[repr(align(8))]
-struct AlignedHeap([u8; HEAP_SIZE]);
+// Alignement minimal imposé à chaque allocation (en plus de celui demandé par le Layout)
+// 1 = pas de contrainte ; 64 = une ligne de cache avec la feature "cache-aligned"
+#[cfg(not(feature = "cache-aligned"))]
+const MIN_ALIGN: usize = 1;
+#[cfg(feature = "cache-aligned")]
+const MIN_ALIGN: usize = 64;
 
-// Allocation statique de notre buffer mémoire (le "heap" custom)
-// L'utilisation de "unsafe" ici est nécessaire car les accès à une zone statique mutable peuvent entraîner des conditions de course si mal utilisés.
-// → https://doc.rust-lang.org/reference/items/static-items.html#mutable-statics
-static mut HEAP: AlignedHeap = AlignedHeap([0; HEAP_SIZE]);
+// Structure représentant notre heap, aligné sur 8 octets
+// Le #[repr(align(N))] garantit un alignement mémoire pour les architectures modernes
+// Avec la feature "page-aligned-heap", il démarre sur une frontière de page (4096), comme le heap d'un OS
+#[cfg_attr(not(feature = "page-aligned-heap"), repr(align(8)))]
+#[cfg_attr(feature = "page-aligned-heap", repr(align(4096)))]
+struct AlignedHeap<const N: usize>([u8; N]);
 
 // Allocateur bump : alloue de la mémoire de manière séquentielle. Il gère un seul pointeur (next) qui avance dans le heap au fur et à mesure des allocations
 // → https://www.youtube.com/watch?v=TZ5a3gCCZYo
 
 // Avantage : très rapide et simple
 // Inconvénient : pas de libération de mémoire (pas de free)
-pub struct BumpAllocator {
+
+// La taille du heap est un paramètre const : chaque allocateur embarque son propre buffer de N octets.
+// Placé dans un static, ce buffer remplace l'ancien "static mut HEAP" (un BumpAllocator<4096> pour des essais,
+// un BumpAllocator<HEAP_SIZE> pour l'allocateur global...)
+pub struct BumpAllocator<const N: usize = HEAP_SIZE> {
+    heap: UnsafeCell<AlignedHeap<N>>,
     next: AtomicUsize,
+    // Plus haut niveau atteint par next depuis le dernier reset_peak()
+    peak: AtomicUsize,
+    // Alerte "mémoire basse" : seuil en octets restants (0 = désactivé), état déclenché et alerte à signaler
+    // alloc() ne fait que lever un drapeau : afficher depuis l'allocateur réentrerait dans alloc (println! alloue)
+    low_memory_threshold: AtomicUsize,
+    low_memory_fired: AtomicBool,
+    low_memory_pending: AtomicBool,
 }
 
-impl BumpAllocator {
+// SAFETY : le heap n'est jamais lu ni écrit par l'allocateur lui-même, il ne fait que distribuer des zones
+// disjointes grâce au compare_exchange sur next. Partager l'allocateur entre threads est donc sûr
+unsafe impl<const N: usize> Sync for BumpAllocator<N> {}
+
+impl<const N: usize> BumpAllocator<N> {
     // Constructeur de l'allocateur, initialise 'next' à 0 (début du heap)
     pub const fn new() -> Self {
         BumpAllocator {
+            heap: UnsafeCell::new(AlignedHeap([0; N])),
             next: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            low_memory_threshold: AtomicUsize::new(0),
+            low_memory_fired: AtomicBool::new(false),
+            low_memory_pending: AtomicBool::new(false),
         }
     }
 
@@ -38,31 +68,118 @@ impl BumpAllocator {
         (addr + align - 1) & !(align - 1)
     }
 
+    // Plus petite granularité effective : toute allocation démarre sur un multiple de cette valeur
+    // Une allocation de 1 octet peut donc en coûter jusqu'à ce nombre (alignement de la suivante)
+    pub fn min_alloc_granularity(&self) -> usize {
+        MIN_ALIGN
+    }
+
     // Permet de connaître la quantité de mémoire déjà allouée
     pub fn allocated_bytes(&self) -> usize {
         self.next.load(Ordering::Relaxed)
     }
 
+    // Adresses de début (incluse) et de fin (exclue) du heap
+    // Le début respecte l'alignement de AlignedHeap (8, ou 4096 avec "page-aligned-heap")
+    pub fn heap_range(&self) -> (usize, usize) {
+        let start = self.heap.get() as usize;
+        (start, start + N)
+    }
+
     // Retourne la taille totale disponible sur le heap
     pub fn heap_size(&self) -> usize {
-        HEAP_SIZE
+        N
+    }
+
+    // Pic d'utilisation du heap (ne redescend jamais, même quand dealloc libérera vraiment)
+    pub fn peak_bytes(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    // Repart de l'utilisation actuelle pour mesurer le pic d'une nouvelle phase
+    pub fn reset_peak(&self) {
+        self.peak.store(self.allocated_bytes(), Ordering::Relaxed);
+    }
+
+    // Mémoire encore disponible avant que les allocations n'échouent
+    pub fn remaining(&self) -> usize {
+        N - self.allocated_bytes()
+    }
+
+    // Lève une alerte quand remaining() passe sous `threshold` octets (0 = désactivé)
+    // L'alerte n'est levée qu'une seule fois jusqu'au prochain reset
+    pub fn set_low_memory_threshold(&self, threshold: usize) {
+        self.low_memory_threshold.store(threshold, Ordering::SeqCst);
+        self.reset_low_memory_alert();
+    }
+
+    // Réarme l'alerte : le prochain passage sous le seuil la lèvera de nouveau
+    pub fn reset_low_memory_alert(&self) {
+        self.low_memory_fired.store(false, Ordering::SeqCst);
+        self.low_memory_pending.store(false, Ordering::SeqCst);
+    }
+
+    // Renvoie true (une seule fois) si l'alerte a été levée depuis le dernier appel
+    // À appeler hors de l'allocateur, par exemple entre deux commandes, pour afficher l'avertissement
+    pub fn take_low_memory_alert(&self) -> bool {
+        self.low_memory_pending.swap(false, Ordering::SeqCst)
+    }
+
+    // Appelé après chaque allocation réussie : lève l'alerte si on vient de passer sous le seuil
+    fn check_low_memory(&self) {
+        let threshold = self.low_memory_threshold.load(Ordering::Relaxed);
+        if threshold == 0 || self.remaining() >= threshold {
+            return;
+        }
+
+        // swap garantit qu'un seul appel gagne, même si plusieurs threads franchissent le seuil ensemble
+        if !self.low_memory_fired.swap(true, Ordering::SeqCst) {
+            self.low_memory_pending.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+// Résultat d'une simulation d'allocations (voir BumpAllocator::simulate)
+pub struct AllocationProfile {
+    pub peak: usize,                  // Octets consommés à la fin (un bump ne redescend jamais)
+    pub allocations: usize,           // Nombre d'allocations qui auraient réussi
+    pub first_failure: Option<usize>, // Index de la première allocation qui aurait échoué (OOM)
+}
+
+impl BumpAllocator {
+    // Rejoue une suite d'allocations (taille, alignement) avec la même arithmétique que alloc(),
+    // sur un heap imaginaire de `heap_size` octets : le vrai heap n'est pas touché
+    // On suppose le début du heap aligné, comme AlignedHeap (au moins sur 8)
+    pub fn simulate(requests: &[(usize, usize)], heap_size: usize) -> AllocationProfile {
+        let mut next = 0;
+        let mut allocations = 0;
+
+        for (index, &(size, align)) in requests.iter().enumerate() {
+            let end = Self::align_up(next, align.max(MIN_ALIGN)) + size;
+            if end > heap_size {
+                return AllocationProfile { peak: next, allocations, first_failure: Some(index) };
+            }
+            next = end;
+            allocations += 1;
+        }
+
+        AllocationProfile { peak: next, allocations, first_failure: None }
     }
 }
 
 // Implémentation du trait GlobalAlloc de Rust. Ce trait permet me d'utiliser notre allocateur comme allocateur GLOBAL
 // Rust appellera automatiquement `alloc()` et `dealloc()` via `Box`, `Vec`, etc.
-unsafe impl GlobalAlloc for BumpAllocator {
+unsafe impl<const N: usize> GlobalAlloc for BumpAllocator<N> {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
         // Définition des bornes de la zone mémoire
-        let heap_start = HEAP.0.as_ptr() as usize;
-        let heap_end = heap_start + HEAP_SIZE;
+        let (heap_start, heap_end) = self.heap_range();
 
         // Récupère la position actuelle dans le heap
         let mut current = self.next.load(Ordering::Relaxed);
 
         loop {
             // Calcul de la position alignée pour cette allocation
-            let alloc_start = Self::align_up(heap_start + current, layout.align());
+            let alloc_start = Self::align_up(heap_start + current, layout.align().max(MIN_ALIGN));
             let alloc_end = alloc_start + layout.size();
 
             // Vérifie qu'on ne dépasse pas la taille du heap
@@ -80,7 +197,13 @@ unsafe impl GlobalAlloc for BumpAllocator {
                 Ordering::SeqCst,
                 Ordering::Relaxed,
             ){
-                Ok(_) => return alloc_start as *mut u8, // Validé : retourne le pointeur vers la zone allouée
+                Ok(_) => {
+                    // Validé : le pic n'est mis à jour qu'après un CAS réussi (fetch_max ne peut que monter)
+                    self.peak.fetch_max(next_offset, Ordering::Relaxed);
+                    // On vérifie le seuil d'alerte puis on retourne le pointeur vers la zone allouée
+                    self.check_low_memory();
+                    return alloc_start as *mut u8;
+                },
                 Err(old) => current = old,              // Échec : quelqu'un d'autre a alloué entre temps, on recommence
             }
         }
@@ -93,5 +216,94 @@ unsafe impl GlobalAlloc for BumpAllocator {
 
 // Ce bloc indique que notre allocateur personnalisé devient L'ALLOCATEUR GLOBAL. Toutes les allocations effectuées dans le programme passeront par ce bump allocator.
 // → https://doc.rust-lang.org/std/alloc/index.html
-#[global_allocator]
-pub static ALLOCATOR: BumpAllocator = BumpAllocator::new();
\ No newline at end of file
+// Sauf pour les tests unitaires : chacun formate un disque de 1MB, que le heap de 64KB (qui ne libère jamais)
+// ne peut pas contenir. Ils tournent sur l'allocateur du système, et le bump est testé sur des BumpAllocator<N> locaux
+#[cfg_attr(not(test), global_allocator)]
+pub static ALLOCATOR: BumpAllocator<HEAP_SIZE> = BumpAllocator::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulate_reports_known_peak() {
+        // 10 octets, puis 4 alignés sur 4, puis 100 alignés sur 8
+        let requests = [(10, 1), (4, 4), (100, 8)];
+        let profile = BumpAllocator::simulate(&requests, 1024);
+        // Sans "cache-aligned" : 0..10, 12..16, 16..116 ; avec : 0..10, 64..68, 128..228
+        let expected = if cfg!(feature = "cache-aligned") { 228 } else { 116 };
+        assert_eq!((profile.peak, profile.allocations, profile.first_failure), (expected, 3, None));
+
+        let profile = BumpAllocator::simulate(&requests, 100);
+        assert_eq!((profile.allocations, profile.first_failure), (2, Some(2)));
+    }
+
+    #[test]
+    fn low_memory_alert_fires_once_until_reset() {
+        let heap = BumpAllocator::<4096>::new();
+        heap.set_low_memory_threshold(1024);
+        let alloc = |size| unsafe { heap.alloc(Layout::from_size_align(size, 1).unwrap()) };
+
+        assert!(!alloc(2048).is_null());
+        assert!(!heap.take_low_memory_alert());
+
+        // 548 octets restants : sous le seuil, l'alerte est levée une seule fois
+        assert!(!alloc(1500).is_null());
+        assert!(heap.take_low_memory_alert());
+        assert!(!heap.take_low_memory_alert());
+        assert!(!alloc(100).is_null());
+        assert!(!heap.take_low_memory_alert());
+
+        heap.reset_low_memory_alert();
+        assert!(!alloc(100).is_null());
+        assert!(heap.take_low_memory_alert());
+    }
+
+
+    #[test]
+    fn small_heap_allocates_in_order_and_fails_when_full() {
+        let heap = BumpAllocator::<256>::new();
+        let (start, end) = heap.heap_range();
+        let alloc = |size, align| unsafe { heap.alloc(Layout::from_size_align(size, align).unwrap()) as usize };
+
+        let a = alloc(10, 1);
+        let b = alloc(16, 8);
+        assert_eq!(a, BumpAllocator::<256>::align_up(start, heap.min_alloc_granularity()));
+        assert!(b >= a + 10 && b % 8 == 0 && b + 16 <= end);
+        assert_eq!(heap.allocated_bytes(), b + 16 - start);
+
+        // Trop grand pour ce qui reste : null, et rien n'est consommé
+        let used = heap.allocated_bytes();
+        assert_eq!(alloc(heap.remaining() + 1, 1), 0);
+        assert_eq!(heap.allocated_bytes(), used);
+
+        // Des petites allocations passent jusqu'à épuisement, puis plus rien
+        while alloc(1, 1) != 0 {}
+        assert_eq!(alloc(1, 1), 0);
+        assert!(heap.remaining() < heap.min_alloc_granularity());
+    }
+
+
+    #[test]
+    fn peak_follows_allocations_and_restarts_after_reset() {
+        let heap = BumpAllocator::<1024>::new();
+        let alloc = |size| unsafe { heap.alloc(Layout::from_size_align(size, 1).unwrap()) };
+        assert_eq!(heap.peak_bytes(), 0);
+
+        assert!(!alloc(100).is_null());
+        assert!(!alloc(200).is_null());
+        let used = heap.allocated_bytes();
+        assert!(used >= 300);
+        assert_eq!(heap.peak_bytes(), used);
+
+        // Un échec ne compte pas dans le pic
+        assert!(alloc(2048).is_null());
+        assert_eq!(heap.peak_bytes(), used);
+
+        // Après reset, le pic repart de l'utilisation actuelle et ne mesure que la nouvelle phase
+        heap.reset_peak();
+        assert_eq!(heap.peak_bytes(), used);
+        assert!(!alloc(50).is_null());
+        assert_eq!(heap.peak_bytes(), heap.allocated_bytes());
+        assert!(heap.peak_bytes() >= used + 50);
+    }
+}
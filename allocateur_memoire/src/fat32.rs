@@ -8,6 +8,11 @@
 // Note perso : FAT32 = File Allocation Table 32-bit, remplace FAT16
 // Le "32" vient du fait qu'on utilise 32 bits pour adresser les clusters (en fait 28 bits utilisés)
 
+use crate::ui::{glyph, Glyph};
+use core::cell::Cell;
+use std::io;
+use std::path::Path;
+
 // Structure du Boot Sector FAT32 (exactement 512 octets)
 // Sources : Microsoft FAT32 specification + osdev wiki
 // Le #[repr(C, packed)] force Rust à respecter l'ordre exact des champs sans padding
@@ -77,6 +82,63 @@ pub struct Fat32BootSector {
     pub signature: u16,               
 }
 
+// Relit le boot sector (les 512 premiers octets) d'un disque déjà formaté et vérifie qu'il est exploitable
+// Ne vérifie que la géométrie : le reste (label, type...) est informatif
+fn read_boot_sector(storage: &[u8]) -> Result<Fat32BootSector, &'static str> {
+    if storage.len() < 512 {
+        return Err("Disque trop petit pour contenir un boot sector");
+    }
+    // SAFETY : au moins 512 octets, et n'importe quelle suite d'octets est un Fat32BootSector valide (que des entiers)
+    let boot_sector = unsafe { core::ptr::read_unaligned(storage.as_ptr() as *const Fat32BootSector) };
+
+    let signature = boot_sector.signature;
+    if signature != 0xAA55 {
+        return Err("Signature du boot sector invalide (0xAA55 attendu)");
+    }
+    let bytes_per_sector = boot_sector.bytes_per_sector;
+    if !matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096) {
+        return Err("Octets par secteur invalides (512, 1024, 2048 ou 4096)");
+    }
+    if !boot_sector.sectors_per_cluster.is_power_of_two() {
+        return Err("Secteurs par cluster invalides (puissance de 2 attendue)");
+    }
+    if boot_sector.num_fats == 0 {
+        return Err("Le boot sector n'annonce aucune FAT");
+    }
+    Ok(boot_sector)
+}
+
+// Secteur FSInfo (exactement 512 octets), placé au secteur `fs_info` du boot sector (1 en général)
+// Garde en cache le nombre de clusters libres pour éviter de rescanner toute la FAT
+// Source : https://wiki.osdev.org/FAT32#FSInfo_Structure
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct Fat32FsInfo {
+    // Octets 0-3 : Signature de début (0x41615252 = "RRaA")
+    pub lead_signature: u32,
+    // Octets 4-483 : Réservé
+    pub reserved1: [u8; 480],
+    // Octets 484-487 : Seconde signature (0x61417272 = "rrAa")
+    pub struct_signature: u32,
+    // Octets 488-491 : Nombre de clusters libres (0xFFFFFFFF = inconnu)
+    pub free_count: u32,
+    // Octets 492-495 : Indice du prochain cluster libre probable (0xFFFFFFFF = inconnu)
+    pub next_free: u32,
+    // Octets 496-507 : Réservé
+    pub reserved2: [u8; 12],
+    // Octets 508-511 : Signature de fin (0xAA550000)
+    pub trail_signature: u32,
+}
+
+pub const FSINFO_LEAD_SIGNATURE: u32 = 0x41615252;
+pub const FSINFO_STRUCT_SIGNATURE: u32 = 0x61417272;
+pub const FSINFO_TRAIL_SIGNATURE: u32 = 0xAA550000;
+pub const FSINFO_UNKNOWN: u32 = 0xFFFFFFFF;     // Valeur "inconnue" pour free_count et next_free
+const SPARSE_UNSUPPORTED: &str = "Operation impossible sur un fichier creux (voir create_sparse)";
+const BUNDLE_MAGIC: &[u8; 8] = b"FAT32BDL";     // En-tête des fichiers écrits par export_bundle
+const FREE_COUNT_RECHECK_INTERVAL: u32 = 256; // En debug, get_free_space recompte la FAT tous les N appels
+const FRAGMENT_CLUSTERS_PER_FILE: usize = 4;    // Taille des fichiers de create_fragmented_state, en clusters
+
 // Entrée de répertoire FAT32 (exactement 32 octets)
 // Chaque fichier/dossier a une entrée de cette taille dans son répertoire parent
 // Source : https://wiki.osdev.org/FAT32#Directory_Structure
@@ -109,18 +171,756 @@ pub struct DirectoryEntry {
     pub file_size: u32,               
 }
 
+impl DirectoryEntry {
+    // Nouvelle entrée de fichier (horodatages à zéro, voir stamped pour les renseigner)
+    fn new_file(name: [u8; 11], first_cluster: u32, file_size: u32) -> Self {
+        DirectoryEntry {
+            name,
+            attributes: FILE_ATTRIBUTE_ARCHIVE,
+            reserved: 0,
+            creation_time_tenth: 0,
+            creation_time: 0,
+            creation_date: 0,
+            last_access_date: 0,
+            first_cluster_high: (first_cluster >> 16) as u16,
+            write_time: 0,
+            write_date: 0,
+            first_cluster_low: first_cluster as u16,
+            file_size,
+        }
+    }
+
+    // Nouvelle entrée de répertoire (taille toujours 0, le contenu est une suite d'entrées)
+    fn new_directory(name: [u8; 11], first_cluster: u32) -> Self {
+        DirectoryEntry {
+            attributes: FILE_ATTRIBUTE_DIRECTORY,
+            ..Self::new_file(name, first_cluster, 0)
+        }
+    }
+
+    // Renseigne les dates de création, de modification et de dernier accès
+    fn stamped(mut self, at: DosDateTime) -> Self {
+        let (date, time) = at.encode();
+        self.creation_date = date;
+        self.creation_time = time;
+        // Le format DOS compte par 2 secondes : la seconde impaire va dans les centièmes (0-199)
+        self.creation_time_tenth = (at.second % 2) * 100;
+        self.write_date = date;
+        self.write_time = time;
+        self.last_access_date = date;
+        self
+    }
+
+    // Date de dernière modification, None si elle n'a jamais été renseignée
+    fn modified(&self) -> Option<DosDateTime> {
+        let (date, time) = (self.write_date, self.write_time);
+        (date != 0).then(|| DosDateTime::decode(date, time))
+    }
+
+    // Décode les 32 octets d'une entrée (little-endian, comme tout FAT32)
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+        let mut name = [0u8; 11];
+        name.copy_from_slice(&bytes[0..11]);
+        DirectoryEntry {
+            name,
+            attributes: bytes[11],
+            reserved: bytes[12],
+            creation_time_tenth: bytes[13],
+            creation_time: u16_at(14),
+            creation_date: u16_at(16),
+            last_access_date: u16_at(18),
+            first_cluster_high: u16_at(20),
+            write_time: u16_at(22),
+            write_date: u16_at(24),
+            first_cluster_low: u16_at(26),
+            file_size: u32::from_le_bytes([bytes[28], bytes[29], bytes[30], bytes[31]]),
+        }
+    }
+
+    // Encode l'entrée dans son format disque de 32 octets
+    fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[0..11].copy_from_slice(&self.name);
+        bytes[11] = self.attributes;
+        bytes[12] = self.reserved;
+        bytes[13] = self.creation_time_tenth;
+        bytes[14..16].copy_from_slice(&self.creation_time.to_le_bytes());
+        bytes[16..18].copy_from_slice(&self.creation_date.to_le_bytes());
+        bytes[18..20].copy_from_slice(&self.last_access_date.to_le_bytes());
+        bytes[20..22].copy_from_slice(&self.first_cluster_high.to_le_bytes());
+        bytes[22..24].copy_from_slice(&self.write_time.to_le_bytes());
+        bytes[24..26].copy_from_slice(&self.write_date.to_le_bytes());
+        bytes[26..28].copy_from_slice(&self.first_cluster_low.to_le_bytes());
+        bytes[28..32].copy_from_slice(&self.file_size.to_le_bytes());
+        bytes
+    }
+
+    // Fichier creux (voir create_sparse) : marqué dans l'octet réservé 12
+    fn is_sparse(&self) -> bool {
+        self.reserved & ENTRY_FLAG_SPARSE != 0
+    }
+
+    // Premier cluster reconstruit à partir des deux moitiés de 16 bits
+    fn first_cluster(&self) -> u32 {
+        ((self.first_cluster_high as u32) << 16) | self.first_cluster_low as u32
+    }
+
+    // Nom lisible : "HELLO   TXT" -> "HELLO.TXT"
+    fn display_name(&self) -> String {
+        let name = self.name;
+        let base = String::from_utf8_lossy(&name[..8]);
+        let ext = String::from_utf8_lossy(&name[8..]);
+        let (base, ext) = (base.trim_end(), ext.trim_end());
+        if ext.is_empty() {
+            base.to_string()
+        } else {
+            format!("{}.{}", base, ext)
+        }
+    }
+}
+
+// Entrée de nom long (LFN) : 13 caractères UTF-16 du nom, placée juste avant l'entrée 8.3 du fichier
+// Un nom long occupe plusieurs de ces entrées, rangées de la dernière partie à la première
+// Source : https://wiki.osdev.org/FAT#Long_File_Names
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct LfnEntry {
+    // Octet 0 : Numéro de la partie (1, 2...), 0x40 ajouté sur la dernière
+    pub order: u8,
+    // Octets 1-10 : Caractères 1 à 5
+    pub name1: [u16; 5],
+    // Octet 11 : Toujours 0x0F (lecture seule + caché + système + label)
+    pub attributes: u8,
+    // Octet 12 : Toujours 0
+    pub entry_type: u8,
+    // Octet 13 : Checksum du nom 8.3 associé
+    pub checksum: u8,
+    // Octets 14-25 : Caractères 6 à 11
+    pub name2: [u16; 6],
+    // Octets 26-27 : Toujours 0
+    pub first_cluster_low: u16,
+    // Octets 28-31 : Caractères 12 et 13
+    pub name3: [u16; 2],
+}
+
+impl LfnEntry {
+    // Partie numéro `order` d'un nom long (0x40 déjà ajouté par l'appelant si c'est la dernière)
+    fn new(order: u8, checksum: u8, units: [u16; LFN_CHARS_PER_ENTRY]) -> Self {
+        let (mut name1, mut name2, mut name3) = ([0u16; 5], [0u16; 6], [0u16; 2]);
+        name1.copy_from_slice(&units[..5]);
+        name2.copy_from_slice(&units[5..11]);
+        name3.copy_from_slice(&units[11..]);
+        LfnEntry {
+            order,
+            name1,
+            attributes: FILE_ATTRIBUTE_LFN,
+            entry_type: 0,
+            checksum,
+            name2,
+            first_cluster_low: 0,
+            name3,
+        }
+    }
+
+    // Décode les 32 octets d'une entrée LFN
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+        let mut units = [0u16; LFN_CHARS_PER_ENTRY];
+        let positions = (1..11).step_by(2).chain((14..26).step_by(2)).chain((28..32).step_by(2));
+        for (unit, i) in units.iter_mut().zip(positions) {
+            *unit = u16_at(i);
+        }
+        LfnEntry::new(bytes[0], bytes[13], units)
+    }
+
+    // Encode l'entrée dans son format disque de 32 octets
+    fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[0] = self.order;
+        bytes[11] = self.attributes;
+        bytes[12] = self.entry_type;
+        bytes[13] = self.checksum;
+        bytes[26..28].copy_from_slice(&self.first_cluster_low.to_le_bytes());
+        let positions = (1..11).step_by(2).chain((14..26).step_by(2)).chain((28..32).step_by(2));
+        for (unit, i) in self.units().into_iter().zip(positions) {
+            bytes[i..i + 2].copy_from_slice(&unit.to_le_bytes());
+        }
+        bytes
+    }
+
+    // Les 13 caractères UTF-16 de cette partie, dans l'ordre
+    fn units(&self) -> [u16; LFN_CHARS_PER_ENTRY] {
+        let (name1, name2, name3) = (self.name1, self.name2, self.name3);
+        let mut units = [0u16; LFN_CHARS_PER_ENTRY];
+        units[..5].copy_from_slice(&name1);
+        units[5..11].copy_from_slice(&name2);
+        units[11..].copy_from_slice(&name3);
+        units
+    }
+}
+
+// Checksum du nom 8.3 recopié dans chaque entrée LFN (rotation à droite puis addition, octet par octet)
+fn lfn_checksum(short_name: &[u8; 11]) -> u8 {
+    short_name.iter().fold(0u8, |sum, &byte| ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(byte))
+}
+
+// Entrées LFN d'un nom long, dans l'ordre où elles sont écrites sur disque (dernière partie en premier)
+// Le nom se termine par un 0x0000 puis est complété par des 0xFFFF
+fn lfn_entries(long_name: &str, short_name: &[u8; 11]) -> Vec<LfnEntry> {
+    let units: Vec<u16> = long_name.encode_utf16().collect();
+    let count = units.len().div_ceil(LFN_CHARS_PER_ENTRY);
+    let checksum = lfn_checksum(short_name);
+
+    (1..=count)
+        .rev()
+        .map(|order| {
+            let mut part = [0xFFFF; LFN_CHARS_PER_ENTRY];
+            let start = (order - 1) * LFN_CHARS_PER_ENTRY;
+            let chunk = &units[start..units.len().min(start + LFN_CHARS_PER_ENTRY)];
+            part[..chunk.len()].copy_from_slice(chunk);
+            if chunk.len() < LFN_CHARS_PER_ENTRY {
+                part[chunk.len()] = 0x0000;
+            }
+            let order = if order == count { order as u8 | LFN_LAST_ENTRY } else { order as u8 };
+            LfnEntry::new(order, checksum, part)
+        })
+        .collect()
+}
+
+// Vérifie qu'un nom peut être stocké en nom long : 1 à 255 caractères UTF-16, sans caractère interdit
+fn validate_long_name(name: &str) -> Result<(), &'static str> {
+    let length = name.encode_utf16().count();
+    if length == 0 || length > LFN_MAX_CHARS || name.ends_with('.') || name.ends_with(' ') {
+        return Err("Nom de fichier invalide (1 a 255 caracteres, sans point ni espace final)");
+    }
+    if name.chars().any(|c| c.is_control() || "\"*/:<>?\\|".contains(c)) {
+        return Err("Nom de fichier invalide (caracteres interdits : \" * / : < > ? \\ |)");
+    }
+    Ok(())
+}
+
+// Reconstitue un nom long à partir des entrées LFN lues avant l'entrée 8.3
+// Tampon fixe (20 parties de 13 caractères) : pas d'allocation pendant le parcours du répertoire
+struct LongNameBuffer {
+    units: [u16; 20 * LFN_CHARS_PER_ENTRY],
+    parts: usize, // Nombre de parties annoncé par l'entrée marquée 0x40 (0 = pas de nom long en cours)
+    checksum: u8, // Checksum porté par la première entrée lue, que toutes les parties doivent partager
+}
+
+impl LongNameBuffer {
+    fn new() -> Self {
+        LongNameBuffer { units: [0; 20 * LFN_CHARS_PER_ENTRY], parts: 0, checksum: 0 }
+    }
+
+    fn clear(&mut self) {
+        self.parts = 0;
+    }
+
+    // Range une partie à sa place ; une partie sans début de nom (pas de 0x40 avant) est ignorée
+    fn push(&mut self, entry: &LfnEntry) {
+        let order = (entry.order & !LFN_LAST_ENTRY) as usize;
+        if order == 0 || order > 20 {
+            self.clear();
+            return;
+        }
+        if entry.order & LFN_LAST_ENTRY != 0 {
+            self.parts = order;
+            self.checksum = entry.checksum;
+        }
+        if order > self.parts || entry.checksum != self.checksum {
+            self.clear();
+            return;
+        }
+        let start = (order - 1) * LFN_CHARS_PER_ENTRY;
+        self.units[start..start + LFN_CHARS_PER_ENTRY].copy_from_slice(&entry.units());
+    }
+
+    // Nom long reconstitué (s'il y en a un) pour l'entrée 8.3 `short_name`, et remise à zéro pour la suivante
+    // Des entrées LFN orphelines (checksum différent de celui du nom 8.3, ex: fichier réécrit par un
+    // système sans noms longs) sont ignorées : l'entrée garde alors son nom 8.3
+    fn take(&mut self, short_name: &[u8; 11]) -> Option<String> {
+        if self.parts == 0 || self.checksum != lfn_checksum(short_name) {
+            self.clear();
+            return None;
+        }
+        let units = &self.units[..self.parts * LFN_CHARS_PER_ENTRY];
+        let end = units.iter().position(|&u| u == 0x0000 || u == 0xFFFF).unwrap_or(units.len());
+        let name = String::from_utf16_lossy(&units[..end]);
+        self.clear();
+        Some(name)
+    }
+}
+
+// Caractères interdits dans un nom court, en plus des caractères de contrôle
+const ILLEGAL_83_CHARS: &[u8] = b"\"*+,/:;<=>?[\\]| ";
+
+// Convertit un nom "HELLO.TXT" en nom court 8.3 sur 11 octets ("HELLO   TXT"), en majuscules
+// "a.txt" et "A.TXT" donnent donc le même nom sur disque
+// Refuse : non-ASCII, base vide ou > 8 caractères, extension > 3, plusieurs points ("NO.EXT.TRA"),
+// et caractères interdits (+ , ; = [ ] ...) ou de contrôle
+fn normalize_83(name: &str) -> Result<[u8; 11], &'static str> {
+    let (base, ext) = match name.rfind('.') {
+        Some(dot) => (&name[..dot], &name[dot + 1..]),
+        None => (name, ""),
+    };
+    if !name.is_ascii() || base.is_empty() || base.len() > 8 || ext.len() > 3 || base.contains('.') {
+        return Err("Nom de fichier invalide (format 8.3 : 8 caracteres + 3 d'extension)");
+    }
+    if name.bytes().any(|b| b < 0x20 || b == 0x7F || ILLEGAL_83_CHARS.contains(&b)) {
+        return Err("Nom de fichier invalide (caracteres interdits : \" * + , / : ; < = > ? [ \\ ] | espace)");
+    }
+
+    let mut short = [b' '; 11];
+    for (slot, byte) in short[..8].iter_mut().zip(base.bytes()) {
+        *slot = byte.to_ascii_uppercase();
+    }
+    for (slot, byte) in short[8..].iter_mut().zip(ext.bytes()) {
+        *slot = byte.to_ascii_uppercase();
+    }
+    Ok(short)
+}
+
 // Constantes importantes FAT32
 // Source des valeurs : Microsoft FAT32 File System Specification
+pub const FILE_ATTRIBUTE_READ_ONLY: u8 = 0x01;  // Fichier protégé : la suppression est refusée
+pub const FILE_ATTRIBUTE_HIDDEN: u8 = 0x02;     // Fichier caché : absent des listings par défaut
+pub const FILE_ATTRIBUTE_SYSTEM: u8 = 0x04;     // Fichier du système d'exploitation
 pub const FILE_ATTRIBUTE_DIRECTORY: u8 = 0x10;  // Indique que l'entrée est un répertoire
+pub const FILE_ATTRIBUTE_VOLUME_ID: u8 = 0x08;   // Entrée "label du volume" (et partie des entrées de noms longs)
+pub const FILE_ATTRIBUTE_ARCHIVE: u8 = 0x20;    // Fichier modifié depuis la dernière sauvegarde (posé à la création)
+// Attributs que l'utilisateur peut changer (les autres décrivent la nature de l'entrée)
+pub const FILE_ATTRIBUTES_EDITABLE: u8 =
+    FILE_ATTRIBUTE_READ_ONLY | FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM | FILE_ATTRIBUTE_ARCHIVE;
+pub const FILE_ATTRIBUTE_LFN: u8 = 0x0F;        // Entrée de nom long (combinaison impossible pour un vrai fichier)
+pub const LFN_LAST_ENTRY: u8 = 0x40;            // Ajouté au numéro de la dernière partie d'un nom long
+pub const LFN_CHARS_PER_ENTRY: usize = 13;      // Caractères UTF-16 par entrée LFN
+pub const LFN_MAX_CHARS: usize = 255;           // Longueur maximale d'un nom long
+pub const DIR_ENTRY_END: u8 = 0x00;             // Premier octet du nom : fin du répertoire, rien après
+pub const DIR_ENTRY_DELETED: u8 = 0xE5;         // Premier octet du nom : entrée supprimée
+pub const ENTRY_FLAG_SPARSE: u8 = 0x80;         // Octet 12 (réservé NT) : fichier creux, extension propre à ce projet
+// Taille de cluster utilisée par new() : 8 secteurs de 512 octets = 4 KB
+pub const DEFAULT_SECTORS_PER_CLUSTER: u8 = 8;
+
+// Tailles de cluster acceptées au formatage : une puissance de 2, au plus 64 secteurs (32 KB)
+pub fn check_cluster_size(sectors_per_cluster: u8) -> Result<(), &'static str> {
+    if !sectors_per_cluster.is_power_of_two() || sectors_per_cluster > 64 {
+        return Err("Secteurs par cluster invalides (1, 2, 4, 8, 16, 32 ou 64)");
+    }
+    Ok(())
+}
+
+// Vérifie qu'un buffer de `storage_len` octets peut être formaté avec cette taille de cluster
+// 1MB minimum pour avoir assez de place (zone système de 272KB + des clusters de données)
+fn check_format(storage_len: usize, sectors_per_cluster: u8) -> Result<(), &'static str> {
+    check_cluster_size(sectors_per_cluster)?;
+    if storage_len < 1024 * 1024 {
+        return Err("Storage trop petit pour FAT32");
+    }
+    Ok(())
+}
+
+// CRC de la zone de données (feature "data-crc", extension non standard pour l'apprentissage) :
+// rangé dans les octets réservés 496-507 de FSInfo sous la forme "DCRC" + CRC32 little-endian,
+// écrit au démontage et vérifié au montage. Les autres outils FAT ne le mettent pas à jour :
+// un CRC différent au montage signale une modification des données faite ailleurs
+#[cfg(feature = "data-crc")]
+const DATA_CRC_MAGIC: &[u8; 4] = b"DCRC";
+#[cfg(feature = "data-crc")]
+const DATA_CRC_OFFSET: usize = 496;
+
+// Table du CRC32 IEEE (polynôme réfléchi 0xEDB88320, celui de zip et d'Ethernet), calculée à la compilation
+// Sert au CRC de chaque fichier (validate_all) et, avec "data-crc", à celui de la zone de données
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+// CRC32 calculé par morceaux : partir de !0, passer chaque morceau dans l'ordre, inverser le résultat
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    data.iter().fold(crc, |crc, &byte| CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+#[cfg(feature = "data-crc")]
+fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(!0, data)
+}
+
+// Compare la zone de données (du secteur `data_start_sector` à la fin du storage) au CRC rangé dans FSInfo
+// Some(true) si elle n'a pas changé, Some(false) sinon, None si aucun CRC n'a été rangé
+#[cfg(feature = "data-crc")]
+fn stored_data_crc_matches(storage: &[u8], fs_info_sector: u16, data_start_sector: u32) -> Option<bool> {
+    let offset = fs_info_sector as usize * 512 + DATA_CRC_OFFSET;
+    let stored = storage.get(offset..offset + 8)?;
+    if stored[..4] != DATA_CRC_MAGIC[..] {
+        return None;
+    }
+    let data = storage.get(data_start_sector as usize * 512..)?;
+    Some(crc32(data).to_le_bytes() == stored[4..8])
+}
+
 pub const CLUSTER_FREE: u32 = 0x00000000;       // Cluster libre dans la FAT
 pub const CLUSTER_END: u32 = 0x0FFFFFF8;        // Fin de chaîne de clusters (EOC = End Of Clusterchain)
+pub const CLUSTER_BAD: u32 = 0x0FFFFFF7;        // Cluster défectueux (ne doit jamais être alloué)
+
+// Date et heure au format des entrées de répertoire FAT (années 1980 à 2107, secondes paires)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DosDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DosDateTime {
+    // Encode en (date, heure) sur 16 bits chacune
+    // date  : bits 15-9 année depuis 1980, 8-5 mois, 4-0 jour
+    // heure : bits 15-11 heures, 10-5 minutes, 4-0 secondes / 2
+    pub fn encode(&self) -> (u16, u16) {
+        let year = self.year.clamp(1980, 2107) - 1980;
+        let date = (year << 9) | ((self.month as u16) << 5) | self.day as u16;
+        let time = ((self.hour as u16) << 11) | ((self.minute as u16) << 5) | (self.second as u16 / 2);
+        (date, time)
+    }
+
+    // Inverse de encode (la seconde revient toujours paire)
+    pub fn decode(date: u16, time: u16) -> Self {
+        DosDateTime {
+            year: 1980 + (date >> 9),
+            month: ((date >> 5) & 0x0F) as u8,
+            day: (date & 0x1F) as u8,
+            hour: (time >> 11) as u8,
+            minute: ((time >> 5) & 0x3F) as u8,
+            second: ((time & 0x1F) * 2) as u8,
+        }
+    }
+}
+
+impl core::fmt::Display for DosDateTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}:{:02}", self.year, self.month, self.day, self.hour, self.minute, self.second)
+    }
+}
+
+// Horloge par défaut : heure système en UTC (pas de fuseau horaire sans dépendance)
+// Conversion jours -> date civile : http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn system_clock() -> DosDateTime {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    DosDateTime {
+        year: year as u16,
+        month: month as u8,
+        day: day as u8,
+        hour: (rest / 3600) as u8,
+        minute: (rest / 60 % 60) as u8,
+        second: (rest % 60) as u8,
+    }
+}
 
 // Structure pour stocker les infos d'un fichier (helper pour notre implémentation)
-// Note : dans un vrai FAT32, ces infos viennent des DirectoryEntry
+// Construite à partir des DirectoryEntry du répertoire racine
 pub struct FileInfo {
     pub name: String,     // Nom du fichier
     pub cluster: u32,     // Premier cluster du fichier
-    pub size: usize,      // Taille en octets
+    pub size: usize,      // Taille en octets, lue dans l'entrée (les octets nuls du contenu ne comptent pas comme une fin)
+    pub modified: Option<DosDateTime>, // Date de dernière modification (None si jamais renseignée)
+    pub attributes: u8,   // Octet d'attributs de l'entrée (FILE_ATTRIBUTE_*)
+    pub sparse: bool,     // Fichier creux (create_sparse) : la chaîne commence par un index, pas par les données
+}
+
+// Chaîne d'un fichier dans le rapport de all_chains
+pub struct FileChain {
+    pub name: String,
+    pub clusters: Result<Vec<u32>, &'static str>, // Clusters dans l'ordre, ou l'erreur qui casse la chaîne
+    pub shared: Vec<u32>,                          // Clusters de la chaîne utilisés aussi par un autre fichier
+}
+
+// Copie des métadonnées seulement (FAT et répertoire racine), pour annuler vite une opération
+// Seules les entrées FAT non libres et les emplacements utilisés de la racine sont gardés :
+// quelques Ko au lieu des 10 Mo du disque, important avec notre heap bump qui ne libère rien
+pub struct MetaSnapshot {
+    fat_entries: Vec<(u32, u32)>, // (cluster, valeur brute de la FAT principale) pour chaque entrée non libre
+    root_slots: Vec<u8>,          // Entrées de 32 octets de la racine, jusqu'au marqueur de fin (exclu)
+}
+
+impl MetaSnapshot {
+    // Taille occupée par la copie en mémoire, pour l'affichage
+    pub fn size_bytes(&self) -> usize {
+        self.fat_entries.len() * core::mem::size_of::<(u32, u32)>() + self.root_slots.len()
+    }
+}
+
+// Ce qu'une défragmentation changerait, calculé sans toucher au disque (voir defrag_preview)
+pub struct DefragPreview {
+    pub fragmentation: f32,       // Fragmentation actuelle de l'espace libre (0.0 à 1.0)
+    pub fragmented_files: usize,  // Fichiers dont la chaîne n'est pas contiguë
+    pub cluster_moves: usize,     // Clusters qui changeraient de place
+    pub largest_free_run: u32,    // Plus longue suite de clusters libres après défragmentation
+}
+
+// Ce qu'a corrigé repair_filesystem, par catégorie
+#[derive(Default)]
+pub struct RepairReport {
+    pub crosslinks: usize,        // Chaînes qui partageaient des clusters avec une autre (clusters recopiés)
+    pub lost_clusters: usize,     // Clusters alloués mais atteints par aucune entrée (libérés)
+    pub truncated_chains: usize,  // Chaînes qui sortaient du disque, menaient à un cluster libre ou bouclaient
+}
+
+// Que faire, dans merge_from, d'un fichier dont le nom existe déjà sur ce disque
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConflictPolicy {
+    Skip,      // Garder le fichier existant
+    Overwrite, // Remplacer son contenu
+    Rename,    // Copier sous un nouveau nom : "NOTES.TXT" -> "NOTES_1.TXT"
+}
+
+// Ce qu'a fait merge_from, fichier par fichier
+#[derive(Default)]
+pub struct MergeReport {
+    pub copied: Vec<String>,           // Copiés sous leur nom (remplacements compris)
+    pub skipped: Vec<String>,          // Laissés de côté à cause d'un conflit
+    pub renamed: Vec<(String, String)>, // (nom d'origine, nom donné à la copie)
+}
+
+// Place prévue pour un fichier par la défragmentation : ses clusters actuels et leur destination
+struct PlannedFile {
+    entry_offset: usize, // Entrée du fichier dans la racine (pour mettre à jour son premier cluster)
+    current: Vec<u32>,
+    target: Vec<u32>,
+}
+
+// Nombre de clusters qu'un plan de défragmentation change de place
+fn planned_moves(plan: &[PlannedFile]) -> usize {
+    plan.iter()
+        .map(|file| file.current.iter().zip(&file.target).filter(|(from, to)| from != to).count())
+        .sum()
+}
+
+// Carte de 2 bits par cluster (valeurs 0 à 3) pour les parcours qui marquent ou comptent chaque cluster
+// 4 clusters par octet : 5 Ko pour 10 Mo de clusters de 512 octets, au lieu de 20 Ko avec un octet par cluster
+struct ClusterMap {
+    bits: Vec<u8>,
+}
+
+impl ClusterMap {
+    fn get(&self, cluster: u32) -> u8 {
+        (self.bits[cluster as usize / 4] >> (cluster % 4 * 2)) & 0b11
+    }
+
+    fn set(&mut self, cluster: u32, value: u8) {
+        let shift = cluster % 4 * 2;
+        let byte = &mut self.bits[cluster as usize / 4];
+        *byte = (*byte & !(0b11 << shift)) | ((value & 0b11) << shift);
+    }
+
+    // Compteur saturé à 3 : au-delà de 1, seul "plusieurs" compte
+    fn bump(&mut self, cluster: u32) {
+        let value = self.get(cluster);
+        if value < 3 {
+            self.set(cluster, value + 1);
+        }
+    }
+}
+
+// Opération qui modifie le disque, telle qu'enregistrée dans le journal (voir start_op_log)
+// Chaque modification faite par une méthode publique y figure, avec de quoi la refaire à l'identique ;
+// seul restore_metadata ne peut pas être rejoué (le snapshot n'est pas dans le journal)
+#[derive(Clone)]
+pub enum Operation {
+    Create { name: String, data: Vec<u8> },
+    CreateAt { name: String, data: Vec<u8>, cluster: u32 },
+    CreateLfn { long_name: String, short_name: String, data: Vec<u8> },
+    Write { name: String, data: Vec<u8> },
+    Append { name: String, data: Vec<u8> },
+    Preallocate { name: String, size: usize },
+    CreateSparse { name: String, size: usize },
+    WriteRange { name: String, offset: usize, data: Vec<u8> },
+    Truncate { name: String, size: usize },
+    SetClusters { name: String, count: u32 },
+    Compact { name: String, ranges: Vec<(usize, usize)> },
+    Delete { name: String },
+    Shred { name: String, passes: u32 },
+    Rename { from: String, to: String },
+    Swap { a: String, b: String },
+    Attrib { name: String, attributes: u8 },
+    Mkdir { path: String },
+    WriteAt { cluster: u32, offset: usize, data: Vec<u8> },
+    Reserve { count: u32, contiguous: bool },
+    FatFill { pattern: u8 },
+    Repair,
+    Recover,
+    Defrag,
+    Fragment { files: usize },
+    ImportBundle { bundle: Vec<u8> },
+}
+
+// Tentative de modification refusée en lecture seule : nom de l'opération (celui de la commande
+// du terminal), ses arguments et l'heure de la tentative
+#[derive(Clone, PartialEq, Debug)]
+pub struct AuditEntry {
+    pub at: DosDateTime,
+    pub operation: &'static str,
+    pub arguments: String,
+}
+
+// Une étape du journal : l'opération et l'heure utilisée pour ses horodatages
+// Rejouer avec la même heure redonne exactement les mêmes entrées de répertoire
+#[derive(Clone)]
+pub struct LoggedOperation {
+    pub at: DosDateTime,
+    pub operation: Operation,
+}
+
+// Format texte du journal, une étape par ligne, champs séparés par des tabulations :
+// <date DOS><heure DOS en hex>  create|write <nom> <données en hex>  |  delete <nom>  |  rename <ancien> <nouveau>
+// Les nombres sont en décimal, les données en hex, les plages de compact en "offset:longueur,offset:longueur"
+pub fn op_log_to_text(log: &[LoggedOperation]) -> String {
+    let hex = |data: &[u8]| data.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let mut text = String::new();
+    for step in log {
+        let (date, time) = step.at.encode();
+        let fields = match &step.operation {
+            Operation::Create { name, data } => format!("create\t{}\t{}", name, hex(data)),
+            Operation::CreateAt { name, data, cluster } => format!("createat\t{}\t{}\t{}", name, cluster, hex(data)),
+            Operation::CreateLfn { long_name, short_name, data } => format!("createlfn\t{}\t{}\t{}", long_name, short_name, hex(data)),
+            Operation::Write { name, data } => format!("write\t{}\t{}", name, hex(data)),
+            Operation::Append { name, data } => format!("append\t{}\t{}", name, hex(data)),
+            Operation::Preallocate { name, size } => format!("prealloc\t{}\t{}", name, size),
+            Operation::CreateSparse { name, size } => format!("sparse\t{}\t{}", name, size),
+            Operation::WriteRange { name, offset, data } => format!("writerange\t{}\t{}\t{}", name, offset, hex(data)),
+            Operation::Truncate { name, size } => format!("truncate\t{}\t{}", name, size),
+            Operation::SetClusters { name, count } => format!("setclusters\t{}\t{}", name, count),
+            Operation::Compact { name, ranges } => {
+                let ranges: Vec<String> = ranges.iter().map(|(offset, len)| format!("{}:{}", offset, len)).collect();
+                format!("compact\t{}\t{}", name, ranges.join(","))
+            },
+            Operation::Delete { name } => format!("delete\t{}", name),
+            Operation::Shred { name, passes } => format!("shred\t{}\t{}", name, passes),
+            Operation::Rename { from, to } => format!("rename\t{}\t{}", from, to),
+            Operation::Swap { a, b } => format!("swap\t{}\t{}", a, b),
+            Operation::Attrib { name, attributes } => format!("attrib\t{}\t{}", name, attributes),
+            Operation::Mkdir { path } => format!("mkdir\t{}", path),
+            Operation::WriteAt { cluster, offset, data } => format!("writeat\t{}\t{}\t{}", cluster, offset, hex(data)),
+            Operation::Reserve { count, contiguous: false } => format!("reserve\t{}", count),
+            Operation::Reserve { count, contiguous: true } => format!("reserve\t{}\tcontiguous", count),
+            Operation::FatFill { pattern } => format!("fatfill\t{}", pattern),
+            Operation::Repair => "repair".to_string(),
+            Operation::Recover => "recover".to_string(),
+            Operation::Defrag => "defrag".to_string(),
+            Operation::Fragment { files } => format!("fragment\t{}", files),
+            Operation::ImportBundle { bundle } => format!("bundle\t{}", hex(bundle)),
+        };
+        text.push_str(&format!("{:04x}{:04x}\t{}\n", date, time, fields));
+    }
+    text
+}
+
+// Relit un journal écrit par op_log_to_text
+pub fn parse_op_log(text: &str) -> Result<Vec<LoggedOperation>, &'static str> {
+    const INVALID: &str = "Journal d'operations invalide";
+    let unhex = |hex: &str| -> Result<Vec<u8>, &'static str> {
+        if !hex.len().is_multiple_of(2) {
+            return Err(INVALID);
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()).ok_or(INVALID))
+            .collect()
+    };
+
+    let mut log = Vec::new();
+    for line in text.lines().filter(|line| !line.is_empty()) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let stamp = fields.first().and_then(|s| u32::from_str_radix(s, 16).ok()).ok_or(INVALID)?;
+        let at = DosDateTime::decode((stamp >> 16) as u16, stamp as u16);
+        let operation = match fields[1..] {
+            ["create", name, data] => Operation::Create { name: name.to_string(), data: unhex(data)? },
+            ["createat", name, cluster, data] => Operation::CreateAt {
+                name: name.to_string(),
+                data: unhex(data)?,
+                cluster: cluster.parse().map_err(|_| INVALID)?,
+            },
+            ["createlfn", long_name, short_name, data] => Operation::CreateLfn {
+                long_name: long_name.to_string(),
+                short_name: short_name.to_string(),
+                data: unhex(data)?,
+            },
+            ["write", name, data] => Operation::Write { name: name.to_string(), data: unhex(data)? },
+            ["append", name, data] => Operation::Append { name: name.to_string(), data: unhex(data)? },
+            ["prealloc", name, size] => Operation::Preallocate { name: name.to_string(), size: size.parse().map_err(|_| INVALID)? },
+            ["sparse", name, size] => Operation::CreateSparse { name: name.to_string(), size: size.parse().map_err(|_| INVALID)? },
+            ["writerange", name, offset, data] => Operation::WriteRange {
+                name: name.to_string(),
+                offset: offset.parse().map_err(|_| INVALID)?,
+                data: unhex(data)?,
+            },
+            ["truncate", name, size] => Operation::Truncate { name: name.to_string(), size: size.parse().map_err(|_| INVALID)? },
+            ["setclusters", name, count] => Operation::SetClusters { name: name.to_string(), count: count.parse().map_err(|_| INVALID)? },
+            ["compact", name, ranges] => {
+                let ranges = ranges
+                    .split(',')
+                    .filter(|range| !range.is_empty())
+                    .map(|range| {
+                        let (offset, len) = range.split_once(':').ok_or(INVALID)?;
+                        Ok((offset.parse().map_err(|_| INVALID)?, len.parse().map_err(|_| INVALID)?))
+                    })
+                    .collect::<Result<Vec<(usize, usize)>, &'static str>>()?;
+                Operation::Compact { name: name.to_string(), ranges }
+            },
+            ["delete", name] => Operation::Delete { name: name.to_string() },
+            ["shred", name, passes] => Operation::Shred { name: name.to_string(), passes: passes.parse().map_err(|_| INVALID)? },
+            ["rename", from, to] => Operation::Rename { from: from.to_string(), to: to.to_string() },
+            ["swap", a, b] => Operation::Swap { a: a.to_string(), b: b.to_string() },
+            ["attrib", name, attributes] => Operation::Attrib {
+                name: name.to_string(),
+                attributes: attributes.parse().map_err(|_| INVALID)?,
+            },
+            ["mkdir", path] => Operation::Mkdir { path: path.to_string() },
+            ["writeat", cluster, offset, data] => Operation::WriteAt {
+                cluster: cluster.parse().map_err(|_| INVALID)?,
+                offset: offset.parse().map_err(|_| INVALID)?,
+                data: unhex(data)?,
+            },
+            ["reserve", count] => Operation::Reserve { count: count.parse().map_err(|_| INVALID)?, contiguous: false },
+            ["reserve", count, "contiguous"] => Operation::Reserve { count: count.parse().map_err(|_| INVALID)?, contiguous: true },
+            ["fatfill", pattern] => Operation::FatFill { pattern: pattern.parse().map_err(|_| INVALID)? },
+            ["repair"] => Operation::Repair,
+            ["recover"] => Operation::Recover,
+            ["defrag"] => Operation::Defrag,
+            ["fragment", files] => Operation::Fragment { files: files.parse().map_err(|_| INVALID)? },
+            ["bundle", bundle] => Operation::ImportBundle { bundle: unhex(bundle)? },
+            _ => return Err(INVALID),
+        };
+        log.push(LoggedOperation { at, operation });
+    }
+    Ok(log)
 }
 
 // Structure principale du système de fichiers
@@ -130,17 +930,50 @@ pub struct Fat32FileSystem {
     pub fat_start_sector: u32,          // Secteur où commence la première FAT
     pub data_start_sector: u32,         // Secteur où commencent les données (après les FATs)
     pub total_clusters: u32,            // Nombre total de clusters de données disponibles
+    pub clock: fn() -> DosDateTime,     // Source des horodatages (remplaçable pour des dates reproductibles)
+    replay_time: Option<DosDateTime>,   // Heure imposée pendant un replay, à la place de clock
+    op_log: Option<Vec<LoggedOperation>>, // Journal des opérations (None = journal inactif)
+    op_log_incomplete: bool,            // Une modification non rejouable (restore) a eu lieu pendant le journal
+    cluster_scratch: Cell<Vec<u8>>,     // Buffer des ClusterMap, gardé d'un appel à l'autre (le heap ne libère jamais)
+    cluster_limit: Option<u32>,         // Limite posée par open_limited (None = tout le buffer)
+    fat_reads: Cell<u64>,               // Entrées FAT lues depuis le montage (voir fat_reads)
+    free_count_verified: Cell<bool>,    // Le compteur de FSInfo a été confirmé par un comptage complet
+    free_space_queries: Cell<u32>,      // Appels à get_free_space, pour la revérification périodique (debug)
+    #[cfg(feature = "mmap")]
+    mapping: Option<&'static memmap2::MmapRaw>, // Projection de l'image quand le storage vient de open_mmap
+    file_crcs: Vec<(String, u32)>,      // CRC32 de chaque fichier écrit par cet outil (voir update_file_crcs)
+    read_only: bool,                    // Toute modification est refusée et notée dans audit_log
+    audit_log: Vec<AuditEntry>,         // Modifications refusées en lecture seule, dans l'ordre
+    #[cfg(feature = "data-crc")]
+    data_crc_status: Option<bool>,      // Vérification du CRC de la zone de données au montage (voir data_crc_status)
     pub storage: &'static mut [u8],     // Notre "disque" simulé en mémoire
 }
 
+// Un FS qui disparaît sans unmount range quand même son CRC (feature "data-crc")
+#[cfg(feature = "data-crc")]
+impl Drop for Fat32FileSystem {
+    fn drop(&mut self) {
+        self.store_data_crc();
+    }
+}
+
 impl Fat32FileSystem {
+    // Indique si le buffer n'a jamais été formaté : la zone du boot sector (secteur 0) est entièrement à zéro
+    // Permet à l'appelant de choisir entre formater (new) et monter un volume existant
+    pub fn is_blank(storage: &[u8]) -> bool {
+        storage.iter().take(512).all(|&b| b == 0)
+    }
+
     // Fonction pour créer et initialiser un système de fichiers FAT32 complet
     // Paramètre : un buffer mémoire qui simule notre disque dur
     pub fn new(storage: &'static mut [u8]) -> Result<Self, &'static str> {
-        // Vérification de taille minimale (1MB minimum pour avoir assez de place)
-        if storage.len() < 1024 * 1024 {  // Minimum 1MB
-            return Err("Storage trop petit pour FAT32");
-        }
+        Self::new_with_cluster_size(storage, DEFAULT_SECTORS_PER_CLUSTER)
+    }
+
+    // Comme new(), avec le nombre de secteurs par cluster choisi au formatage (1, 2, 4, ... 64)
+    // Petits clusters = moins de place perdue par fichier, grands clusters = moins de fragmentation
+    pub fn new_with_cluster_size(storage: &'static mut [u8], sectors_per_cluster: u8) -> Result<Self, &'static str> {
+        check_format(storage.len(), sectors_per_cluster)?;
 
         // Création du boot sector avec des valeurs standards FAT32
         // La plupart de ces valeurs viennent de la spec Microsoft
@@ -151,8 +984,8 @@ impl Fat32FileSystem {
             oem_name: *b"RUST_OS ",
             // 512 octets par secteur = standard depuis très longtemps
             bytes_per_sector: 512,
-            // 8 secteurs par cluster = 4KB par cluster (bon compromis taille/fragmentation)
-            sectors_per_cluster: 8,
+            // 8 secteurs par cluster par défaut = 4KB par cluster (bon compromis taille/fragmentation)
+            sectors_per_cluster,
             // 32 secteurs réservés avant la FAT (assez pour le boot sector + backup)
             reserved_sector_count: 32,
             // 2 copies de la FAT pour la redondance (si une se corrompt)
@@ -179,7 +1012,7 @@ impl Fat32FileSystem {
             fs_version: 0,
             // Le répertoire racine commence au cluster 2 (0 et 1 sont réservés)
             root_cluster: 2,
-            // Secteur 1 pour les infos FSInfo (pas implémenté ici)
+            // Secteur 1 pour les infos FSInfo (nombre de clusters libres en cache)
             fs_info: 1,
             // Secteur 6 pour la copie de backup du boot sector
             backup_boot_sector: 6,
@@ -212,12 +1045,30 @@ impl Fat32FileSystem {
             fat_start_sector,
             data_start_sector,
             total_clusters: 0, // Calculé juste après
+            clock: system_clock,
+            replay_time: None,
+            op_log: None,
+            op_log_incomplete: false,
+            cluster_scratch: Cell::new(Vec::new()),
+            fat_reads: Cell::new(0),
+            free_count_verified: Cell::new(false),
+            free_space_queries: Cell::new(0),
+            #[cfg(feature = "mmap")]
+            mapping: None,
+            #[cfg(feature = "data-crc")]
+            data_crc_status: None,
+            file_crcs: Vec::new(),
+            read_only: false,
+            audit_log: Vec::new(),
+            cluster_limit: None,
             storage,
         };
 
         // Calcul du nombre de clusters de données disponibles
         // = (taille_storage - zone_systeme) / taille_cluster
-        fs.total_clusters = (fs.storage.len() as u32 - fs.data_start_sector * 512) / (8 * 512);
+        fs.total_clusters = (fs.storage.len() - fs.data_start_sector as usize * 512) as u32 / fs.cluster_size() as u32;
+        // Avec de petits clusters, la FAT (256 secteurs) peut limiter le nombre de clusters adressables
+        fs.total_clusters = fs.total_clusters.min(fs.boot_sector.fat_size_32 * 512 / 4 - 2);
 
        // Initialisation physique du système de fichiers
         fs.write_boot_sector()?;    // Écrire le boot sector sur le "disque"
@@ -226,106 +1077,749 @@ impl Fat32FileSystem {
         Ok(fs)
     }
 
-    // Écrit le boot sector dans le storage à l'offset 0
-    // Note : on utilise unsafe car on manipule des pointeurs bruts
-    fn write_boot_sector(&mut self) -> Result<(), &'static str> {
-        // Conversion de la structure en bytes bruts
-        // → https://doc.rust-lang.org/std/slice/fn.from_raw_parts.html
-        let boot_sector_bytes = unsafe {
-            core::slice::from_raw_parts(
-                &self.boot_sector as *const _ as *const u8,
-                core::mem::size_of::<Fat32BootSector>()// Doit faire exactement 512 bytes
-            )
-        };
-        
-        // Vérification que notre storage est assez grand
-        if self.storage.len() < boot_sector_bytes.len() {
-            return Err("Storage trop petit pour le boot sector");
+    // Comme new(), mais ne rend visibles que `max_clusters` clusters de données (à partir de 3, la racine en plus)
+    // Simule un petit disque dans un grand buffer sans reformater : les clusters au-delà de la limite
+    // restent dans la FAT mais toutes les fonctions les considèrent comme inexistants
+    pub fn open_limited(storage: &'static mut [u8], max_clusters: u32) -> Result<Self, &'static str> {
+        Self::open_limited_with_cluster_size(storage, max_clusters, DEFAULT_SECTORS_PER_CLUSTER)
+    }
+
+    // Comme open_limited(), avec le nombre de secteurs par cluster choisi au formatage
+    pub fn open_limited_with_cluster_size(storage: &'static mut [u8], max_clusters: u32, sectors_per_cluster: u8) -> Result<Self, &'static str> {
+        let mut fs = Self::new_with_cluster_size(storage, sectors_per_cluster)?;
+        fs.apply_cluster_limit(max_clusters)?;
+        Ok(fs)
+    }
+
+    // Réduit le volume à `max_clusters` clusters de données et la mémorise (reprise par set_cluster_size)
+    fn apply_cluster_limit(&mut self, max_clusters: u32) -> Result<(), &'static str> {
+        self.cluster_limit = Some(max_clusters);
+        // total_clusters compte aussi le cluster 2 de la racine
+        let limit = max_clusters.saturating_add(1);
+        if limit < self.total_clusters {
+            self.total_clusters = limit;
+            // Le formatage a compté les clusters libres de tout le buffer
+            let free_count = self.count_free_clusters()?;
+            self.write_fs_info(free_count)?;
         }
-        
-        // Copie du boot sector au début du storage (secteur 0)
-        self.storage[..boot_sector_bytes.len()].copy_from_slice(boot_sector_bytes);
         Ok(())
     }
 
-    // Initialise la table FAT avec les valeurs par défaut
-    // Les premières entrées ont des significations spéciales
-    fn initialize_fat(&mut self) -> Result<(), &'static str> {
-        let fat_offset = (self.fat_start_sector * 512) as usize;
-        let fat_size = (self.boot_sector.fat_size_32 * 512) as usize;
-        
-        // Vérifier que la FAT rentre dans notre storage
-        if fat_offset + fat_size > self.storage.len() {
-            return Err("FAT ne rentre pas dans le storage");
+    // Monte une image .img existante (par exemple produite par export_image) au lieu de formater
+    // Le fichier est copié tel quel dans le storage, puis monté avec open()
+    pub fn from_image(storage: &'static mut [u8], path: &Path) -> Result<Self, &'static str> {
+        use std::io::Read;
+
+        // Lecture directe dans le storage : un Vec de la taille de l'image ne tiendrait pas dans le heap bump
+        let mut file = std::fs::File::open(path).map_err(|_| "Impossible d'ouvrir l'image")?;
+        let len = file.metadata().map_err(|_| "Impossible de lire la taille de l'image")?.len() as usize;
+        if len > storage.len() {
+            return Err("Image plus grande que le disque");
         }
+        file.read_exact(&mut storage[..len]).map_err(|_| "Lecture de l'image impossible")?;
+        storage[len..].fill(0);
+        // Le volume monté a exactement la taille de l'image
+        let fs = Self::open(storage.split_at_mut(len).0)?;
+        if &fs.boot_sector.fs_type != b"FAT32   " {
+            return Err("L'image n'est pas un volume FAT32");
+        }
+        Ok(fs)
+    }
 
-        // Nettoyer toute la zone FAT (mettre à zéro)
-        for i in fat_offset..fat_offset + fat_size {
-            self.storage[i] = 0;
+    // Monte une image de l'hôte projetée en mémoire (feature "mmap") : rien n'est copié, chaque lecture ou
+    // écriture du FS touche directement le fichier, qui n'a donc pas besoin de tenir dans le storage de main
+    // La projection n'est jamais défaite (le storage est &'static) : le système écrit les pages modifiées
+    // dans le fichier au plus tard à la fin du processus, flush_mapping le fait tout de suite
+    #[cfg(feature = "mmap")]
+    pub fn open_mmap(path: &Path) -> Result<Self, &'static str> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|_| "Impossible d'ouvrir l'image")?;
+        let len = file.metadata().map_err(|_| "Impossible de lire la taille de l'image")?.len();
+        // Des secteurs entiers, boot sector compris
+        if len < 512 || !len.is_multiple_of(512) {
+            return Err("Taille d'image invalide (multiple de 512 octets attendu)");
+        }
+        let len = usize::try_from(len).map_err(|_| "Image trop grande pour l'espace d'adressage")?;
+        let mapping = memmap2::MmapOptions::new().len(len).map_raw(&file).map_err(|_| "Projection de l'image impossible")?;
+        // Une projection commence sur une page ; les structures lues en place (FSInfo...) supposent des secteurs alignés
+        if !(mapping.as_ptr() as usize).is_multiple_of(512) {
+            return Err("Projection de l'image mal alignee");
         }
 
-        // Initialiser les entrées spéciales de la FAT
-        // FAT[0] = media descriptor (reprend la valeur du boot sector)
-        self.write_fat_entry(0, 0x0FFFFFF8)?;  
-        // FAT[1] = marqueur de fin de chaîne (toujours)
-        self.write_fat_entry(1, CLUSTER_END)?;   
-        // FAT[2] = répertoire racine (marqué comme utilisé)
-        self.write_fat_entry(2, CLUSTER_END)?; 
+        let mapping: &'static memmap2::MmapRaw = Box::leak(Box::new(mapping));
+        // SAFETY : la projection fait `len` octets, elle n'est jamais libérée (leak) et seul ce storage y accède
+        let storage = unsafe { core::slice::from_raw_parts_mut(mapping.as_mut_ptr(), len) };
+        let mut fs = Self::open(storage)?;
+        if &fs.boot_sector.fs_type != b"FAT32   " {
+            return Err("L'image n'est pas un volume FAT32");
+        }
+        fs.mapping = Some(mapping);
+        Ok(fs)
+    }
 
-        Ok(())
+    // Force l'écriture dans le fichier de l'hôte des pages modifiées d'une image montée par open_mmap
+    // Sans projection (storage en mémoire), il n'y a rien à faire
+    #[cfg(feature = "mmap")]
+    pub fn flush_mapping(&self) -> Result<(), &'static str> {
+        match self.mapping {
+            Some(mapping) => mapping.flush().map_err(|_| "Ecriture de l'image sur l'hote impossible"),
+            None => Ok(()),
+        }
     }
 
-    // Écrit une entrée dans la table FAT
-    // La FAT est un tableau qui indique pour chaque cluster soit :
-    // - 0 = cluster libre
-    // - 0x0FFFFFF8-0x0FFFFFFF = fin de fichier
-    // - autre valeur = numéro du cluster suivant dans la chaîne
-    fn write_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), &'static str> {
-        // Vérification des bornes (clusters 0 et 1 sont réservés mais accessibles)
-        if cluster >= self.total_clusters + 2 {  // +2 car les clusters commencent à 2
-            return Err("Cluster invalide");
+    // Démonte le volume et rend son storage : avec la feature "data-crc", range d'abord le CRC de la zone
+    // de données dans FSInfo ; une image montée par open_mmap est ensuite écrite dans le fichier de l'hôte
+    pub fn unmount(mut self) -> Result<&'static mut [u8], &'static str> {
+        #[cfg(feature = "data-crc")]
+        self.store_data_crc();
+        #[cfg(feature = "mmap")]
+        self.flush_mapping()?;
+        Ok(core::mem::take(&mut self.storage))
+    }
+
+    // Range le CRC actuel de la zone de données dans FSInfo (feature "data-crc")
+    // En lecture seule ou sans FSInfo valide (storage déjà rendu par unmount), on ne touche à rien
+    #[cfg(feature = "data-crc")]
+    fn store_data_crc(&mut self) {
+        let data_start = self.data_start_sector as usize * 512;
+        if self.read_only || self.fs_info().is_err() || data_start > self.storage.len() {
+            return;
         }
+        let crc = crc32(&self.storage[data_start..]);
+        let offset = self.boot_sector.fs_info as usize * 512 + DATA_CRC_OFFSET;
+        self.storage[offset..offset + 4].copy_from_slice(DATA_CRC_MAGIC);
+        self.storage[offset + 4..offset + 8].copy_from_slice(&crc.to_le_bytes());
+    }
 
-        // Calcul de l'adresse dans le storage
-        // Chaque entrée FAT32 fait 4 octets (32 bits)
-        let fat_offset = (self.fat_start_sector * 512) as usize;
-        let entry_offset = fat_offset + (cluster as usize * 4);
+    // Résultat de la vérification du CRC de la zone de données faite par open (feature "data-crc")
+    // Some(false) : les données ont changé hors de cet outil depuis le dernier démontage ; None : pas de CRC rangé
+    #[cfg(feature = "data-crc")]
+    pub fn data_crc_status(&self) -> Option<bool> {
+        self.data_crc_status
+    }
 
-        // Vérification que l'écriture ne dépasse pas le storage
-        if entry_offset + 4 > self.storage.len() {
-            return Err("Offset FAT invalide");
+    // Monte un disque déjà formaté sans rien réécrire : la géométrie (taille de cluster, taille et nombre
+    // de FATs) vient du boot sector du disque, pas des valeurs fixes de new()
+    pub fn open(storage: &'static mut [u8]) -> Result<Self, &'static str> {
+        let boot_sector = read_boot_sector(storage)?;
+        // Tout le module compte en secteurs de 512 octets
+        let bytes_per_sector = boot_sector.bytes_per_sector;
+        if bytes_per_sector != 512 {
+            return Err("Seuls les secteurs de 512 octets sont supportes");
         }
 
-        // En FAT32, seuls les 28 bits de poids faible sont utilisés
-        // Les 4 bits de poids fort sont réservés et doivent être préservés
-        // → https://wiki.osdev.org/FAT32#FAT_Entry_Values
-        let masked_value = value & 0x0FFFFFFF;
-        let bytes = masked_value.to_le_bytes(); // Little-endian comme x86
-        
-        // Écriture des 4 octets dans le storage
-        self.storage[entry_offset] = bytes[0];
-        self.storage[entry_offset + 1] = bytes[1];
-        self.storage[entry_offset + 2] = bytes[2];
-        self.storage[entry_offset + 3] = bytes[3];
+        // Mêmes calculs que new(), mais à partir des champs lus
+        // Les FATs doivent tenir dans le volume : calcul vérifié, un champ aberrant ne doit pas déborder
+        let total_sectors = boot_sector.total_sectors_32.min((storage.len() / 512) as u32);
+        let fat_start_sector = boot_sector.reserved_sector_count as u32;
+        let data_start_sector = (boot_sector.num_fats as u32)
+            .checked_mul(boot_sector.fat_size_32)
+            .and_then(|fat_sectors| fat_sectors.checked_add(fat_start_sector))
+            .filter(|&end| end <= total_sectors)
+            .ok_or("Boot sector incoherent (FAT hors du volume)")?;
+        if data_start_sector >= total_sectors {
+            return Err("Boot sector incoherent (zone de donnees hors du volume)");
+        }
+        // Pas plus de clusters que d'entrées dans la FAT (les entrées 0 et 1 sont réservées)
+        let fat_entries = boot_sector.fat_size_32 * 512 / 4;
+        let total_clusters = ((total_sectors - data_start_sector) / boot_sector.sectors_per_cluster as u32)
+            .min(fat_entries.saturating_sub(2));
+        // La racine est lue dès le premier ls : son cluster doit exister
+        let root_cluster = boot_sector.root_cluster;
+        if root_cluster < 2 || root_cluster >= total_clusters + 2 {
+            return Err("Boot sector incoherent (cluster racine hors du volume)");
+        }
 
-        // Note : dans une implémentation complète, il faudrait aussi écrire
-        // dans la deuxième FAT pour la redondance
-        Ok(())
+        Ok(Fat32FileSystem {
+            boot_sector,
+            fat_start_sector,
+            data_start_sector,
+            total_clusters,
+            clock: system_clock,
+            replay_time: None,
+            op_log: None,
+            op_log_incomplete: false,
+            cluster_scratch: Cell::new(Vec::new()),
+            fat_reads: Cell::new(0),
+            free_count_verified: Cell::new(false),
+            free_space_queries: Cell::new(0),
+            #[cfg(feature = "mmap")]
+            mapping: None,
+            #[cfg(feature = "data-crc")]
+            data_crc_status: stored_data_crc_matches(storage, boot_sector.fs_info, data_start_sector),
+            file_crcs: Vec::new(),
+            read_only: false,
+            audit_log: Vec::new(),
+            cluster_limit: None,
+            storage,
+        })
     }
 
-    // Lit une entrée de la table FAT
-    // Fonction inverse de write_fat_entry
-    pub fn read_fat_entry(&self, cluster: u32) -> Result<u32, &'static str> {
-        if cluster >= self.total_clusters + 2 {
-            return Err("Cluster invalide");
-        }
+    // Mode lecture seule : chaque tentative de modification échoue et est notée dans le journal d'audit
+    pub fn set_read_only(&mut self, enabled: bool) {
+        self.read_only = enabled;
+    }
 
-        let fat_offset = (self.fat_start_sector * 512) as usize;
-        let entry_offset = fat_offset + (cluster as usize * 4);
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
 
-        if entry_offset + 4 > self.storage.len() {
-            return Err("Offset FAT invalide");
-        }
+    // Modifications refusées depuis le montage, la plus ancienne en premier
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        &self.audit_log
+    }
+
+    // Appelé en tête de chaque méthode qui modifie le disque : en lecture seule, note la tentative et la refuse
+    // Les arguments ne sont formatés que pour une tentative refusée
+    fn check_writable(&mut self, operation: &'static str, arguments: impl FnOnce() -> String) -> Result<(), &'static str> {
+        if !self.read_only {
+            return Ok(());
+        }
+        let at = self.now();
+        self.audit_log.push(AuditEntry { at, operation, arguments: arguments() });
+        Err("Volume en lecture seule : modification refusee (voir audit)")
+    }
+
+    // Heure à utiliser pour les horodatages : celle de l'horloge, ou celle de l'étape rejouée
+    fn now(&self) -> DosDateTime {
+        self.replay_time.unwrap_or_else(self.clock)
+    }
+
+    // Démarre un journal vide : chaque opération réussie y sera ajoutée
+    // Pour pouvoir rejouer le journal sur un disque neuf, il faut le démarrer sur un disque vide
+    pub fn start_op_log(&mut self) {
+        self.op_log = Some(Vec::new());
+        self.op_log_incomplete = false;
+    }
+
+    // Arrête le journal et le renvoie
+    pub fn stop_op_log(&mut self) -> Option<Vec<LoggedOperation>> {
+        self.op_log.take()
+    }
+
+    pub fn op_log(&self) -> Option<&[LoggedOperation]> {
+        self.op_log.as_deref()
+    }
+
+    // Remplace le journal (par exemple relu depuis un fichier de l'hôte) avant un replay
+    pub fn load_op_log(&mut self, log: Vec<LoggedOperation>) {
+        self.op_log = Some(log);
+        self.op_log_incomplete = false;
+    }
+
+    fn record(&mut self, operation: Operation) {
+        self.update_file_crcs(&operation);
+        let at = self.now();
+        if let Some(log) = self.op_log.as_mut() {
+            log.push(LoggedOperation { at, operation });
+        }
+    }
+
+    // Tient à jour le CRC des fichiers après une modification réussie (appelée par record) : les fichiers
+    // nommés par l'opération sont recalculés, les noms disparus oubliés. Une opération sur tout le volume
+    // ou sur des clusters efface tout : validate_all ne vérifie plus alors que la stabilité des lectures.
+    // Les fichiers creux ne sont pas suivis (leur taille logique peut dépasser de loin celle du disque)
+    fn update_file_crcs(&mut self, operation: &Operation) {
+        let names = match operation {
+            Operation::Create { name, .. }
+            | Operation::CreateAt { name, .. }
+            | Operation::Write { name, .. }
+            | Operation::Append { name, .. }
+            | Operation::Preallocate { name, .. }
+            | Operation::CreateSparse { name, .. }
+            | Operation::WriteRange { name, .. }
+            | Operation::Truncate { name, .. }
+            | Operation::SetClusters { name, .. }
+            | Operation::Compact { name, .. } => vec![name],
+            Operation::CreateLfn { long_name, .. } => vec![long_name],
+            Operation::Rename { to, .. } => vec![to],
+            Operation::Swap { a, b } => vec![a, b],
+            Operation::Delete { .. } | Operation::Shred { .. } | Operation::Attrib { .. } | Operation::Mkdir { .. } => Vec::new(),
+            _ => {
+                self.file_crcs.clear();
+                return;
+            },
+        };
+        if matches!(operation, Operation::Delete { .. } | Operation::Shred { .. } | Operation::Rename { .. }) {
+            let mut file_crcs = core::mem::take(&mut self.file_crcs);
+            file_crcs.retain(|(name, _)| self.find_file(name).is_some());
+            self.file_crcs = file_crcs;
+        }
+        for name in names {
+            let Some(file) = self.find_file(name) else { continue };
+            let crc = if file.sparse { None } else { self.file_crc(&file).ok() };
+            let slot = self.file_crcs.iter().position(|(known, _)| *known == file.name);
+            match (crc, slot) {
+                (Some(crc), Some(slot)) => self.file_crcs[slot].1 = crc,
+                (Some(crc), None) => self.file_crcs.push((file.name, crc)),
+                (None, Some(slot)) => {
+                    self.file_crcs.remove(slot);
+                },
+                (None, None) => {},
+            }
+        }
+    }
+
+    // CRC32 du contenu d'un fichier, lu cluster par cluster
+    fn file_crc(&self, file: &FileInfo) -> Result<u32, &'static str> {
+        let mut crc = !0;
+        self.visit_range(file, 0, file.size, &mut |piece| {
+            crc = crc32_update(crc, piece);
+            Ok(())
+        })?;
+        Ok(!crc)
+    }
+
+    // Reformate le disque (tout est remis à zéro) puis réapplique le journal à partir de l'étape `from_step`
+    // Le journal est réenregistré pendant le replay : il décrit ensuite le nouveau contenu du disque
+    // Renvoie le nombre d'étapes rejouées
+    pub fn replay(&mut self, from_step: usize) -> Result<usize, &'static str> {
+        self.check_writable("replay", || from_step.to_string())?;
+        if self.op_log_incomplete {
+            return Err("Journal incomplet : le disque a ete modifie par un restore pendant l'enregistrement");
+        }
+        let log = self.op_log.take().ok_or("Journal inactif")?;
+        if from_step > log.len() {
+            self.op_log = Some(log);
+            return Err("Etape de depart au-dela de la fin du journal");
+        }
+
+        // En cas d'échec, le journal d'origine est remis en place : le problème reste rejouable
+        if let Err(e) = self.reformat_and_apply(&log[from_step..]) {
+            self.op_log = Some(log);
+            return Err(e);
+        }
+        Ok(log.len() - from_step)
+    }
+
+    // Corps de replay : reformate, puis applique chaque étape avec l'heure à laquelle elle a été enregistrée
+    fn reformat_and_apply(&mut self, steps: &[LoggedOperation]) -> Result<(), &'static str> {
+        self.storage.fill(0);
+        self.file_crcs.clear();
+        self.write_boot_sector()?;
+        self.initialize_fat()?;
+        self.op_log = Some(Vec::new());
+
+        for step in steps {
+            self.replay_time = Some(step.at);
+            let result = match &step.operation {
+                Operation::Create { name, data } => self.create_file_named(name, data).map(|_| ()),
+                Operation::CreateAt { name, data, cluster } => self.create_file_at(name, data, *cluster),
+                Operation::CreateLfn { long_name, short_name, data } => {
+                    self.create_file_with_names(long_name, short_name, data).map(|_| ())
+                },
+                Operation::Write { name, data } => self.write_file(name, data).map(|_| ()),
+                Operation::Append { name, data } => self.append_to_file(name, data),
+                Operation::Preallocate { name, size } => self.preallocate(name, *size),
+                Operation::CreateSparse { name, size } => self.create_sparse(name, *size),
+                Operation::WriteRange { name, offset, data } => self.write_file_range(name, *offset, data),
+                Operation::Truncate { name, size } => self.truncate_file(name, *size),
+                Operation::SetClusters { name, count } => self.set_file_clusters(name, *count),
+                Operation::Compact { name, ranges } => self.compact_file(name, ranges),
+                Operation::Delete { name } => self.delete_file_by_name(name),
+                Operation::Shred { name, passes } => self.secure_delete(name, *passes),
+                Operation::Rename { from, to } => self.rename_file(from, to).map(|_| ()),
+                Operation::Swap { a, b } => self.swap_names(a, b),
+                Operation::Attrib { name, attributes } => self.set_attributes(name, *attributes),
+                Operation::Mkdir { path } => self.create_directory(path).map(|_| ()),
+                Operation::WriteAt { cluster, offset, data } => self.write_cluster_at(*cluster, *offset, data),
+                Operation::Reserve { count, contiguous: false } => self.reserve_clusters(*count).map(|_| ()),
+                Operation::Reserve { count, contiguous: true } => self.allocate_contiguous(*count).map(|_| ()),
+                Operation::FatFill { pattern } => self.format_fat(*pattern),
+                Operation::Repair => self.repair_filesystem().map(|_| ()),
+                Operation::Recover => self.recover_orphans().map(|_| ()),
+                Operation::Defrag => self.defragment().map(|_| ()),
+                Operation::Fragment { files } => self.create_fragmented_state(*files).map(|_| ()),
+                Operation::ImportBundle { bundle } => self.restore_bundle(bundle).map(|_| ()),
+            };
+            self.replay_time = None;
+            result?;
+        }
+        Ok(())
+    }
+
+    // Empreinte FNV-1a 32 bits de tout le disque, pour comparer deux images
+    pub fn image_checksum(&self) -> u32 {
+        self.storage.iter().fold(0x811C9DC5u32, |hash, &byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+    }
+
+    // Écrit le boot sector dans le storage à l'offset 0
+    // Note : on utilise unsafe car on manipule des pointeurs bruts
+    fn write_boot_sector(&mut self) -> Result<(), &'static str> {
+        // Conversion de la structure en bytes bruts
+        // → https://doc.rust-lang.org/std/slice/fn.from_raw_parts.html
+        let boot_sector_bytes = unsafe {
+            core::slice::from_raw_parts(
+                &self.boot_sector as *const _ as *const u8,
+                core::mem::size_of::<Fat32BootSector>()// Doit faire exactement 512 bytes
+            )
+        };
+        
+        // Vérification que notre storage est assez grand
+        if self.storage.len() < boot_sector_bytes.len() {
+            return Err("Storage trop petit pour le boot sector");
+        }
+        
+        // Copie du boot sector au début du storage (secteur 0)
+        self.storage[..boot_sector_bytes.len()].copy_from_slice(boot_sector_bytes);
+
+        // Copie de secours au secteur backup_boot_sector (6 en général)
+        // Un vrai driver s'en sert si le secteur 0 est corrompu
+        let backup_offset = self.boot_sector.backup_boot_sector as usize * 512;
+        if backup_offset + boot_sector_bytes.len() > self.storage.len() {
+            return Err("Storage trop petit pour le backup du boot sector");
+        }
+        self.storage[backup_offset..backup_offset + boot_sector_bytes.len()].copy_from_slice(boot_sector_bytes);
+        Ok(())
+    }
+
+    // Renvoie les 512 octets du backup du boot sector tels qu'écrits sur le "disque"
+    pub fn read_backup_boot_sector(&self) -> Result<&[u8], &'static str> {
+        self.read_sector(self.boot_sector.backup_boot_sector as u32)
+    }
+
+    // Lit n'importe quel secteur brut de 512 octets (boot, FSInfo, backup, FAT, données...)
+    // Pratique pour inspecter les zones réservées qu'aucune autre méthode n'expose
+    pub fn read_sector(&self, lba: u32) -> Result<&[u8], &'static str> {
+        let total_sectors = self.boot_sector.total_sectors_32;
+        if lba >= total_sectors {
+            return Err("Secteur hors du volume");
+        }
+
+        let offset = lba as usize * 512;
+        if offset + 512 > self.storage.len() {
+            return Err("Secteur hors du storage");
+        }
+        Ok(&self.storage[offset..offset + 512])
+    }
+
+    // Copie des 512 octets du boot sector tels qu'ils sont écrits sur le disque (secteur 0)
+    // On lit le storage et non self.boot_sector : c'est ce que verrait un outil externe
+    pub fn boot_sector_bytes(&self) -> [u8; 512] {
+        let mut bytes = [0u8; 512];
+        bytes.copy_from_slice(&self.storage[..512]);
+        bytes
+    }
+
+    // Compare octet par octet le boot sector principal (secteur 0) et son backup
+    pub fn compare_boot_sectors(&self) -> bool {
+        match self.read_backup_boot_sector() {
+            Ok(backup) => self.boot_sector_bytes()[..] == *backup,
+            Err(_) => false,
+        }
+    }
+
+    // Vérifie les signatures des secteurs de la zone réservée tels qu'ils sont sur le disque :
+    // boot sector (secteur 0), FSInfo (secteur fs_info) et backup du boot sector (0 = pas de backup)
+    pub fn check_reserved_region(&self) -> Result<(), &'static str> {
+        let has_signature = |sector: &[u8]| sector[510..512] == [0x55, 0xAA];
+        if !has_signature(self.read_sector(0)?) {
+            return Err("Signature du boot sector (secteur 0) invalide sur le disque");
+        }
+        self.fs_info()?;
+        if self.boot_sector.backup_boot_sector != 0 && !has_signature(self.read_backup_boot_sector()?) {
+            return Err("Signature du backup du boot sector invalide");
+        }
+        Ok(())
+    }
+
+    // Initialise la table FAT avec les valeurs par défaut
+    // Les premières entrées ont des significations spéciales
+    fn initialize_fat(&mut self) -> Result<(), &'static str> {
+        self.format_fat(0)
+    }
+
+    // (Ré)initialise la FAT en plaçant `high_nibble` dans les 4 bits réservés de chaque entrée libre
+    // Les 28 bits bas restent à zéro : les entrées sont toujours vues comme libres grâce au masquage
+    // Avec 0 on obtient la FAT standard. Attention : tous les fichiers existants sont perdus
+    // Utile pour tester les outils qui scannent la FAT (ils doivent ignorer les bits réservés)
+    pub fn format_fat(&mut self, high_nibble: u8) -> Result<(), &'static str> {
+        self.check_writable("fatfill", || format!("{:X}", high_nibble))?;
+        if high_nibble > 0xF {
+            return Err("Le motif doit tenir sur 4 bits (0-F)");
+        }
+
+        // Toutes les copies de la FAT se suivent : on traite la zone d'un seul bloc
+        let fat_offset = self.fat_offset(0);
+        let fat_size = (self.boot_sector.num_fats as u32 * self.boot_sector.fat_size_32 * 512) as usize;
+        
+        // Vérifier que les FATs rentrent dans notre storage
+        if fat_offset + fat_size > self.storage.len() {
+            return Err("FAT ne rentre pas dans le storage");
+        }
+
+        // Remplir toute la zone FAT : valeur 0 (libre) avec le motif dans les bits de poids fort
+        let fill = ((high_nibble as u32) << 28).to_le_bytes();
+        for entry in self.storage[fat_offset..fat_offset + fat_size].chunks_exact_mut(4) {
+            entry.copy_from_slice(&fill);
+        }
+
+        // Initialiser les entrées spéciales de la FAT
+        // FAT[0] = media descriptor (reprend la valeur du boot sector)
+        self.write_fat_entry(0, 0x0FFFFFF8)?;  
+        // FAT[1] = marqueur de fin de chaîne (toujours)
+        self.write_fat_entry(1, CLUSTER_END)?;   
+        // FAT[2] = répertoire racine (marqué comme utilisé)
+        self.write_fat_entry(2, CLUSTER_END)?; 
+
+        // Répertoire racine vide : le premier octet à 0x00 marque la fin du répertoire
+        let root_cluster = self.boot_sector.root_cluster;
+        self.write_cluster(root_cluster, &[])?;
+
+        // FSInfo repart d'un comptage complet de la FAT toute neuve
+        let free_count = self.count_free_clusters()?;
+        self.write_fs_info(free_count)?;
+
+        // Au formatage (new, replay) le journal est inactif : seul un fatfill explicite y apparaît
+        self.record(Operation::FatFill { pattern: high_nibble });
+        Ok(())
+    }
+
+    // Écrit un secteur FSInfo valide avec le nombre de clusters libres donné
+    fn write_fs_info(&mut self, free_count: u32) -> Result<(), &'static str> {
+        let fs_info = Fat32FsInfo {
+            lead_signature: FSINFO_LEAD_SIGNATURE,
+            reserved1: [0; 480],
+            struct_signature: FSINFO_STRUCT_SIGNATURE,
+            free_count,
+            next_free: FSINFO_UNKNOWN,
+            reserved2: [0; 12],
+            trail_signature: FSINFO_TRAIL_SIGNATURE,
+        };
+        // Même conversion en octets bruts que pour le boot sector
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                &fs_info as *const _ as *const u8,
+                core::mem::size_of::<Fat32FsInfo>() // 512 octets
+            )
+        };
+
+        let offset = self.boot_sector.fs_info as usize * 512;
+        if offset + bytes.len() > self.storage.len() {
+            return Err("Storage trop petit pour le secteur FSInfo");
+        }
+        self.storage[offset..offset + bytes.len()].copy_from_slice(bytes);
+        // Tous les appelants passent un comptage complet : le compteur est sûr
+        self.free_count_verified.set(free_count != FSINFO_UNKNOWN);
+        Ok(())
+    }
+
+    // Relit le secteur FSInfo et vérifie ses trois signatures
+    pub fn fs_info(&self) -> Result<Fat32FsInfo, &'static str> {
+        let sector = self.read_sector(self.boot_sector.fs_info as u32)?;
+        // SAFETY : 512 octets lus, et n'importe quelle suite d'octets est un Fat32FsInfo valide (que des entiers)
+        let fs_info = unsafe { core::ptr::read_unaligned(sector.as_ptr() as *const Fat32FsInfo) };
+
+        let (lead, strukt, trail) = (fs_info.lead_signature, fs_info.struct_signature, fs_info.trail_signature);
+        if lead != FSINFO_LEAD_SIGNATURE || strukt != FSINFO_STRUCT_SIGNATURE || trail != FSINFO_TRAIL_SIGNATURE {
+            return Err("Signatures FSInfo invalides");
+        }
+        Ok(fs_info)
+    }
+
+    // Indice next_free de FSInfo : le cluster où la prochaine recherche de cluster libre commencera
+    // None si FSInfo est invalide ou si l'indice est inconnu (0xFFFFFFFF)
+    pub fn fsinfo_next_free(&self) -> Option<u32> {
+        self.fs_info().ok().map(|fs_info| fs_info.next_free).filter(|&next_free| next_free != FSINFO_UNKNOWN)
+    }
+
+    // Ajuste le compteur de clusters libres de FSInfo après une allocation (-1) ou une libération (+1)
+    // Sans FSInfo valide (pendant le formatage) ou avec un compteur inconnu, on ne touche à rien
+    fn adjust_free_count(&mut self, delta: i32) {
+        let Ok(fs_info) = self.fs_info() else { return };
+        let free_count = fs_info.free_count;
+        if free_count == FSINFO_UNKNOWN {
+            return;
+        }
+        let offset = self.boot_sector.fs_info as usize * 512 + 488;
+        let updated = free_count.wrapping_add_signed(delta);
+        self.storage[offset..offset + 4].copy_from_slice(&updated.to_le_bytes());
+    }
+
+    // Met à jour l'indice next_free de FSInfo (sans FSInfo valide, on ne touche à rien)
+    fn set_next_free_hint(&mut self, cluster: u32) {
+        if self.fs_info().is_err() {
+            return;
+        }
+        let offset = self.boot_sector.fs_info as usize * 512 + 492;
+        self.storage[offset..offset + 4].copy_from_slice(&cluster.to_le_bytes());
+    }
+
+    // Offset en octets de la copie numéro `fat_index` de la FAT (0 = principale, 1 = miroir...)
+    // Les copies sont rangées l'une après l'autre, chacune fait fat_size_32 secteurs
+    fn fat_offset(&self, fat_index: u32) -> usize {
+        ((self.fat_start_sector + fat_index * self.boot_sector.fat_size_32) * 512) as usize
+    }
+
+    // Écrit une entrée dans la table FAT
+    // La FAT est un tableau qui indique pour chaque cluster soit :
+    // - 0 = cluster libre
+    // - 0x0FFFFFF8-0x0FFFFFFF = fin de fichier
+    // - autre valeur = numéro du cluster suivant dans la chaîne
+    // L'écriture est répétée dans chaque copie de la FAT (num_fats = 2 : principale + miroir)
+    fn write_fat_entry(&mut self, cluster: u32, value: u32) -> Result<(), &'static str> {
+        // Vérification des bornes (clusters 0 et 1 sont réservés mais accessibles)
+        if cluster >= self.total_clusters + 2 {  // +2 car les clusters commencent à 2
+            return Err("Cluster invalide");
+        }
+
+        // Un cluster de données qui passe de libre à utilisé (ou l'inverse) change le compteur de FSInfo
+        let previous = self.read_fat_entry(cluster)?;
+        if cluster >= 2 {
+            match (previous == CLUSTER_FREE, value & 0x0FFFFFFF == CLUSTER_FREE) {
+                (true, false) => self.adjust_free_count(-1),
+                (false, true) => self.adjust_free_count(1),
+                _ => {}
+            }
+        }
+
+        for fat_index in 0..self.boot_sector.num_fats as u32 {
+            // Calcul de l'adresse dans le storage
+            // Chaque entrée FAT32 fait 4 octets (32 bits)
+            let entry_offset = self.fat_offset(fat_index) + (cluster as usize * 4);
+
+            // Vérification que l'écriture ne dépasse pas le storage
+            if entry_offset + 4 > self.storage.len() {
+                return Err("Offset FAT invalide");
+            }
+
+            // En FAT32, seuls les 28 bits de poids faible sont utilisés
+            // Les 4 bits de poids fort sont réservés et doivent être préservés
+            // → https://wiki.osdev.org/FAT32#FAT_Entry_Values
+            // On relit donc l'entrée actuelle pour garder ses 4 bits hauts et n'écrire que les 28 bits bas
+            let existing = u32::from_le_bytes([
+                self.storage[entry_offset],
+                self.storage[entry_offset + 1],
+                self.storage[entry_offset + 2],
+                self.storage[entry_offset + 3],
+            ]);
+            let new_entry = (existing & 0xF0000000) | (value & 0x0FFFFFFF);
+            let bytes = new_entry.to_le_bytes(); // Little-endian comme x86
+            
+            // Écriture des 4 octets dans le storage
+            self.storage[entry_offset..entry_offset + 4].copy_from_slice(&bytes);
+        }
+
+        Ok(())
+    }
+
+    // Capture la FAT et le répertoire racine (les données des fichiers ne sont pas copiées)
+    pub fn snapshot_metadata(&self) -> MetaSnapshot {
+        let mut fat_entries = Vec::new();
+        let fat = self.fat_offset(0);
+        for cluster in 2..self.total_clusters + 2 {
+            let offset = fat + cluster as usize * 4;
+            let raw = u32::from_le_bytes([
+                self.storage[offset],
+                self.storage[offset + 1],
+                self.storage[offset + 2],
+                self.storage[offset + 3],
+            ]);
+            if raw & 0x0FFFFFFF != CLUSTER_FREE {
+                fat_entries.push((cluster, raw));
+            }
+        }
+
+        let mut root_slots = Vec::new();
+        for offset in self.dir_slot_offsets(self.boot_sector.root_cluster) {
+            if self.storage[offset] == DIR_ENTRY_END {
+                break;
+            }
+            root_slots.extend_from_slice(&self.storage[offset..offset + 32]);
+        }
+
+        MetaSnapshot { fat_entries, root_slots }
+    }
+
+    // Remet la FAT et le répertoire racine dans l'état du snapshot
+    // Les clusters de données ne sont pas restaurés : c'est sûr tant qu'aucune donnée n'a été réécrite
+    // depuis (création, suppression, renommage), pas après un write ou un shred
+    pub fn restore_metadata(&mut self, snapshot: &MetaSnapshot) -> Result<(), &'static str> {
+        self.check_writable("restore", String::new)?;
+        // Les fichiers reviennent à leur état du snapshot : leurs CRC ne sont plus connus
+        self.file_crcs.clear();
+        // Toutes les copies de la FAT : clusters de données libres, puis entrées sauvegardées
+        for fat_index in 0..self.boot_sector.num_fats as u32 {
+            let fat = self.fat_offset(fat_index);
+            let start = fat + 2 * 4;
+            let end = fat + (self.total_clusters as usize + 2) * 4;
+            self.storage[start..end].fill(0);
+            for &(cluster, raw) in &snapshot.fat_entries {
+                let offset = fat + cluster as usize * 4;
+                self.storage[offset..offset + 4].copy_from_slice(&raw.to_le_bytes());
+            }
+        }
+
+        // La chaîne de la racine est celle du snapshot : on y recopie les entrées et on efface le reste
+        let root_cluster = self.boot_sector.root_cluster;
+        let slots: Vec<usize> = self.dir_slot_offsets(root_cluster).collect();
+        if snapshot.root_slots.len() > slots.len() * 32 {
+            return Err("Snapshot incoherent avec le repertoire racine");
+        }
+        let mut saved = snapshot.root_slots.chunks(32);
+        for offset in slots {
+            match saved.next() {
+                Some(entry) => self.storage[offset..offset + 32].copy_from_slice(entry),
+                None => self.storage[offset..offset + 32].fill(0),
+            }
+        }
+
+        // Le compteur de FSInfo suit la FAT restaurée
+        let free_count = self.count_free_clusters()?;
+        self.write_fs_info(free_count)?;
+
+        // Le snapshot n'est pas dans le journal : un replay ne saurait pas refaire ce retour en arrière
+        if self.op_log.is_some() {
+            self.op_log_incomplete = true;
+        }
+        Ok(())
+    }
+
+    // Première entrée où les deux FATs divergent : (cluster, valeur FAT principale, valeur miroir)
+    // Les valeurs sont comparées brutes, bits réservés compris
+    pub fn fat_divergence(&self) -> Option<(u32, u32, u32)> {
+        if self.boot_sector.num_fats < 2 {
+            return None;
+        }
+        let (primary, mirror) = (self.fat_offset(0), self.fat_offset(1));
+        let raw = |offset: usize| u32::from_le_bytes([
+            self.storage[offset],
+            self.storage[offset + 1],
+            self.storage[offset + 2],
+            self.storage[offset + 3],
+        ]);
+
+        (0..self.total_clusters + 2).find_map(|cluster| {
+            let a = raw(primary + cluster as usize * 4);
+            let b = raw(mirror + cluster as usize * 4);
+            (a != b).then_some((cluster, a, b))
+        })
+    }
+
+    // Vérifie que la FAT miroir est identique à la FAT principale
+    pub fn verify_fats_consistent(&self) -> Result<(), &'static str> {
+        match self.fat_divergence() {
+            None => Ok(()),
+            Some(_) => Err("Les deux copies de la FAT divergent"),
+        }
+    }
+
+    // Lit une entrée de la table FAT
+    // Fonction inverse de write_fat_entry
+    pub fn read_fat_entry(&self, cluster: u32) -> Result<u32, &'static str> {
+        self.fat_reads.set(self.fat_reads.get() + 1);
+        if cluster >= self.total_clusters + 2 {
+            return Err("Cluster invalide");
+        }
+
+        let entry_offset = self.fat_offset(0) + (cluster as usize * 4);
+
+        if entry_offset + 4 > self.storage.len() {
+            return Err("Offset FAT invalide");
+        }
 
         // Lecture des 4 octets et reconstruction de la valeur 32-bit
         let entry = u32::from_le_bytes([
@@ -342,6 +1836,40 @@ impl Fat32FileSystem {
         Ok(entry & 0x0FFFFFFF)
     }
 
+    // Change la taille des clusters en REFORMATANT le volume : toutes les adresses de la FAT et des
+    // répertoires sont exprimées en clusters, on ne peut pas convertir les fichiers existants (ils sont perdus)
+    // Un journal actif repart vide : il décrit le disque neuf, avec sa nouvelle géométrie
+    pub fn set_cluster_size(&mut self, sectors_per_cluster: u8) -> Result<(), &'static str> {
+        self.check_writable("setclustersize", || sectors_per_cluster.to_string())?;
+        if sectors_per_cluster == self.boot_sector.sectors_per_cluster {
+            return Ok(()); // Déjà la bonne taille, rien à faire
+        }
+        // Tout est vérifié avant d'emprunter le storage : new_with_cluster_size le consomme même en cas
+        // d'erreur, le volume resterait sans disque
+        check_format(self.storage.len(), sectors_per_cluster)?;
+
+        let storage = core::mem::take(&mut self.storage);
+        storage.fill(0);
+        let mut fs = Self::new_with_cluster_size(storage, sectors_per_cluster)?;
+        if let Some(max_clusters) = self.cluster_limit {
+            fs.apply_cluster_limit(max_clusters)?;
+        }
+        // Le reformatage efface les fichiers (et donc leurs CRC) mais pas l'état du montage
+        fs.clock = self.clock;
+        if self.op_log.is_some() {
+            fs.op_log = Some(Vec::new());
+        }
+        fs.cluster_scratch = Cell::new(self.cluster_scratch.take());
+        fs.read_only = self.read_only;
+        fs.audit_log = core::mem::take(&mut self.audit_log);
+        #[cfg(feature = "mmap")]
+        {
+            fs.mapping = self.mapping.take();
+        }
+        *self = fs;
+        Ok(())
+    }
+
     // Affiche les informations détaillées du système de fichiers
     // Utile pour débugger et comprendre la structure
     pub fn info(&self) {
@@ -376,257 +1904,3686 @@ impl Fat32FileSystem {
         }
     }
 
+    // Label du volume tel que stocké dans le boot sector (11 caractères paddés avec des espaces)
+    pub fn volume_label(&self) -> String {
+        let label = self.boot_sector.volume_label;
+        String::from_utf8_lossy(&label).trim_end().to_string()
+    }
+
+    // Numéro de série du volume
+    pub fn volume_id(&self) -> u32 {
+        self.boot_sector.volume_id
+    }
+
     // Liste de tous les fichiers créés avec un mapping dynamique
-    pub fn list_files(&self) -> Vec<FileInfo> {
-        let mut files = Vec::new();
-        let mut file_counter = 0;
-        
-        // Scanner les clusters utilisés à partir de 3
-        for cluster in 3..self.total_clusters + 2 {
-            if let Ok(fat_entry) = self.read_fat_entry(cluster) {
-                if fat_entry != CLUSTER_FREE {
-                    // Lire le début du cluster pour deviner le contenu
-                    if let Ok(cluster_data) = self.read_cluster(cluster) {
-                        // Essayer de déterminer la taille réelle du fichier
-                        let mut size = 0;
-                        for &byte in cluster_data.iter() {
-                            if byte == 0 {
-                                break;
-                            }
-                            size += 1;
-                        }
-                        
-                        // Générer un nom basé sur le cluster ou le contenu
-                        let name = if size > 0 {
-                            // Essayer de créer un nom basé sur le contenu
-                            let content = std::str::from_utf8(&cluster_data[..size.min(20)]).unwrap_or("DATA");
-                            if content.starts_with("Hello") {
-                                "HELLO.TXT".to_string()
-                            } else if content.starts_with("Donnees") || content.starts_with("aaa") {
-                                format!("FILE_{}.DAT", file_counter)
-                            } else if content.starts_with("#") {
-                                "README.MD".to_string()
-                            } else {
-                                format!("USER_{}.TXT", file_counter)
-                            }
-                        } else {
-                            format!("EMPTY_{}.DAT", cluster)
-                        };
-                        
-                        files.push(FileInfo { name, cluster, size });
-                        file_counter += 1;
-                    }
-                }
-            }
-        }
-        
-        files
+    // Les fichiers cachés n'y figurent qu'avec show_hidden (comme "dir /a" sous DOS)
+    pub fn list_files(&self, show_hidden: bool) -> Vec<FileInfo> {
+        self.files_iter()
+            .filter(|file| show_hidden || file.attributes & FILE_ATTRIBUTE_HIDDEN == 0)
+            .collect()
     }
 
-    // Trouve un fichier par son nom
-    pub fn find_file(&self, filename: &str) -> Option<FileInfo> {
-        let files = self.list_files();
-        files.into_iter().find(|f| f.name.to_uppercase() == filename.to_uppercase())
+    // Fichiers (non cachés) dont le nom, long ou 8.3, correspond à une expression régulière
+    // Le motif s'applique au nom affiché : "^HE.*\.TXT$" trouve HELLO.TXT mais pas hello.txt en nom long
+    // Motif compilé sans Unicode, sur les octets du nom : en mode Unicode, un simple ".*" réserve
+    // plus de 300 Ko pendant la compilation, bien plus que notre heap bump (qui ne libère rien)
+    #[cfg(feature = "regex")]
+    pub fn list_files_regex(&self, pattern: &str) -> Result<Vec<FileInfo>, &'static str> {
+        let regex = regex::bytes::RegexBuilder::new(pattern)
+            .unicode(false)
+            .size_limit(16 * 1024)
+            .dfa_size_limit(16 * 1024)
+            .build()
+            .map_err(|_| "Expression reguliere invalide")?;
+        Ok(self.files_iter()
+            .filter(|file| file.attributes & FILE_ATTRIBUTE_HIDDEN == 0 && regex.is_match(file.name.as_bytes()))
+            .collect())
     }
 
-    // Crée un fichier avec un nom spécifique (version améliorée)
-    pub fn create_file_named(&mut self, name: &str, data: &[u8]) -> Result<u32, &'static str> {
-        if name.len() > 11 {
-            return Err("Nom de fichier trop long (max 11 caracteres)");
-        }
+    // Attributs d'un fichier ou d'un répertoire de la racine
+    pub fn get_attributes(&self, name: &str) -> Result<u8, &'static str> {
+        let offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        Ok(self.storage[offset + 11])
+    }
 
-        // Vérifier si le fichier existe déjà
-        if self.find_file(name).is_some() {
-            return Err("Fichier deja existant");
+    // Remplace les attributs modifiables (lecture seule, caché, système, archive)
+    // Les bits répertoire et label de volume décrivent la nature de l'entrée : ils ne bougent jamais
+    pub fn set_attributes(&mut self, name: &str, attributes: u8) -> Result<(), &'static str> {
+        self.check_writable("attrib", || format!("{} 0x{:02X}", name, attributes))?;
+        if attributes & !FILE_ATTRIBUTES_EDITABLE != 0 {
+            return Err("Seuls les attributs R, H, S et A sont modifiables");
         }
+        let offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        self.storage[offset + 11] = (self.storage[offset + 11] & !FILE_ATTRIBUTES_EDITABLE) | attributes;
+        self.record(Operation::Attrib { name: name.to_string(), attributes });
+        Ok(())
+    }
 
-        // Allouer un cluster pour le fichier
-        let file_cluster = self.allocate_cluster()?;
-        
-        // Écrire les données du fichier
-        self.write_cluster(file_cluster, data)?;
+    // Parcours paresseux des fichiers : chaque FileInfo est construit à la demande
+    // Évite de remplir un Vec sur le heap bump (qui ne libère jamais) quand on cherche juste un fichier
+    // Seuls les fichiers du répertoire racine sont listés (pas les sous-répertoires, voir list_directories)
+    // Le nom d'un fichier est son nom long s'il en a un, sinon son nom 8.3
+    pub fn files_iter(&self) -> impl Iterator<Item = FileInfo> + '_ {
+        self.named_entries(self.boot_sector.root_cluster)
+            .filter(|(_, entry, _)| entry.attributes & FILE_ATTRIBUTE_DIRECTORY == 0)
+            .map(|(_, entry, name)| FileInfo {
+                name,
+                cluster: entry.first_cluster(),
+                size: entry.file_size as usize,
+                modified: entry.modified(),
+                attributes: entry.attributes,
+                sparse: entry.is_sparse(),
+            })
+    }
 
-        Ok(file_cluster)
+    // Sous-répertoires du répertoire racine (taille toujours 0)
+    pub fn list_directories(&self) -> Vec<FileInfo> {
+        self.dir_entries(self.boot_sector.root_cluster)
+            .filter(|(_, entry)| entry.attributes & FILE_ATTRIBUTE_DIRECTORY != 0)
+            .map(|(_, entry)| FileInfo {
+                name: entry.display_name(),
+                cluster: entry.first_cluster(),
+                size: 0,
+                modified: entry.modified(),
+                attributes: entry.attributes,
+                sparse: false,
+            })
+            .collect()
     }
 
-    // Lit un fichier par son nom
-    pub fn read_file_by_name(&self, filename: &str) -> Result<Vec<u8>, &'static str> {
-        if let Some(file_info) = self.find_file(filename) {
-            self.read_file(file_info.cluster, file_info.size)
-        } else {
-            Err("Fichier non trouve")
-        }
+    // Offsets (dans le storage) de chaque emplacement de 32 octets d'un répertoire, en suivant sa chaîne
+    fn dir_slot_offsets(&self, dir_cluster: u32) -> impl Iterator<Item = usize> + '_ {
+        let cluster_size = self.cluster_size();
+        core::iter::successors(Some(dir_cluster), move |&cluster| match self.read_fat_entry(cluster) {
+            Ok(next) if next >= 2 && next < self.total_clusters + 2 => Some(next),
+            _ => None,
+        })
+        .take(self.total_clusters as usize) // Garde-fou contre une boucle dans la FAT
+        .flat_map(move |cluster| {
+            let base = self.cluster_to_offset(cluster);
+            (0..cluster_size).step_by(32).map(move |slot| base + slot)
+        })
     }
 
-    // Supprime un fichier (simulation - marque le cluster comme libre)
-    pub fn delete_file_by_name(&mut self, filename: &str) -> Result<(), &'static str> {
-        if let Some(file_info) = self.find_file(filename) {
-            // Marquer le cluster comme libre
-            self.write_fat_entry(file_info.cluster, CLUSTER_FREE)?;
-            Ok(())
-        } else {
-            Err("Fichier non trouve")
-        }
+    // Entrées valides d'un répertoire, avec leur offset
+    // S'arrête au marqueur de fin (0x00), ignore les entrées supprimées (0xE5) et le label de volume
+    fn dir_entries(&self, dir_cluster: u32) -> impl Iterator<Item = (usize, DirectoryEntry)> + '_ {
+        self.dir_slot_offsets(dir_cluster)
+            .map(move |offset| (offset, DirectoryEntry::from_bytes(&self.storage[offset..offset + 32])))
+            .take_while(|(_, entry)| entry.name[0] != DIR_ENTRY_END)
+            .filter(|(_, entry)| entry.name[0] != DIR_ENTRY_DELETED && entry.attributes & FILE_ATTRIBUTE_VOLUME_ID == 0)
     }
 
-    // Trouve un cluster libre
-    pub fn find_free_cluster(&self) -> Result<u32, &'static str> {
-        for cluster in 3..self.total_clusters + 2 {  // Commence à 3 (après root)
-            if self.read_fat_entry(cluster)? == CLUSTER_FREE {
-                return Ok(cluster);
+    // Comme dir_entries, avec en plus le nom de chaque entrée : le nom long reconstitué à partir des
+    // entrées LFN qui la précèdent, ou à défaut le nom 8.3
+    fn named_entries(&self, dir_cluster: u32) -> impl Iterator<Item = (usize, DirectoryEntry, String)> + '_ {
+        self.dir_slot_offsets(dir_cluster)
+            .map(move |offset| (offset, DirectoryEntry::from_bytes(&self.storage[offset..offset + 32])))
+            .take_while(|(_, entry)| entry.name[0] != DIR_ENTRY_END)
+            .scan(LongNameBuffer::new(), move |buffer, (offset, entry)| {
+                let named = if entry.name[0] == DIR_ENTRY_DELETED {
+                    buffer.clear();
+                    None
+                } else if entry.attributes == FILE_ATTRIBUTE_LFN {
+                    buffer.push(&LfnEntry::from_bytes(&self.storage[offset..offset + 32]));
+                    None
+                } else if entry.attributes & FILE_ATTRIBUTE_VOLUME_ID != 0 {
+                    buffer.clear();
+                    None
+                } else {
+                    let name = buffer.take(&entry.name).unwrap_or_else(|| entry.display_name());
+                    Some((offset, entry, name))
+                };
+                Some(named)
+            })
+            .flatten()
+    }
+
+    // Emplacements des entrées LFN placées juste avant l'entrée 8.3 située à `entry_offset`
+    // (seulement celles dont le checksum correspond à son nom 8.3)
+    fn lfn_slots_before(&self, dir_cluster: u32, entry_offset: usize) -> Vec<usize> {
+        let mut short_name = [0u8; 11];
+        short_name.copy_from_slice(&self.storage[entry_offset..entry_offset + 11]);
+        let checksum = lfn_checksum(&short_name);
+
+        let mut slots = Vec::new();
+        for offset in self.dir_slot_offsets(dir_cluster).take_while(|&offset| offset != entry_offset) {
+            if self.storage[offset + 11] == FILE_ATTRIBUTE_LFN
+                && self.storage[offset] != DIR_ENTRY_DELETED
+                && self.storage[offset + 13] == checksum
+            {
+                slots.push(offset);
+            } else {
+                slots.clear();
             }
         }
-        Err("Pas de cluster libre")
+        slots
     }
 
-    // Alloue un nouveau cluster
-    pub fn allocate_cluster(&mut self) -> Result<u32, &'static str> {
-        let cluster = self.find_free_cluster()?;
-        self.write_fat_entry(cluster, CLUSTER_END)?;
-        Ok(cluster)
+    // Offset de l'entrée d'un fichier (ou répertoire) dans le répertoire racine
+    // Le nom peut être le nom long ou le nom 8.3 (alias compris, ex: "A_VERY~1.TXT")
+    fn find_entry_offset(&self, filename: &str) -> Option<usize> {
+        let short = normalize_83(filename).ok();
+        self.named_entries(self.boot_sector.root_cluster)
+            .find(|(_, entry, name)| Some(entry.name) == short || name.eq_ignore_ascii_case(filename))
+            .map(|(offset, _, _)| offset)
     }
 
-    // Convertit un numéro de cluster en offset dans le storage
-    fn cluster_to_offset(&self, cluster: u32) -> usize {
-        let cluster_offset = cluster - 2;  // Les clusters de données commencent à 2
-        (self.data_start_sector * 512) as usize + 
-        (cluster_offset * 8 * 512) as usize  // 8 secteurs par cluster
+    // Ajoute une entrée à un répertoire, dans le premier emplacement jamais utilisé (0x00)
+    fn add_dir_entry(&mut self, dir_cluster: u32, entry: DirectoryEntry) -> Result<(), &'static str> {
+        self.add_named_entry(dir_cluster, entry, None)
     }
 
-    // Écrit des données dans un cluster
-    pub fn write_cluster(&mut self, cluster: u32, data: &[u8]) -> Result<(), &'static str> {
-        if cluster < 2 || cluster >= self.total_clusters + 2 {
-            return Err("Cluster invalide");
+    // Ajoute une entrée précédée des entrées LFN de `long_name`, toutes à la suite dans le premier trou assez grand
+    fn add_named_entry(&mut self, dir_cluster: u32, entry: DirectoryEntry, long_name: Option<&str>) -> Result<(), &'static str> {
+        let lfn = long_name.map(|name| lfn_entries(name, &entry.name)).unwrap_or_default();
+        let slots = self.free_dir_slots(dir_cluster, lfn.len() + 1)?;
+        for (&offset, lfn_entry) in slots.iter().zip(&lfn) {
+            self.storage[offset..offset + 32].copy_from_slice(&lfn_entry.to_bytes());
         }
+        let offset = slots[lfn.len()];
+        self.storage[offset..offset + 32].copy_from_slice(&entry.to_bytes());
+        Ok(())
+    }
 
-        let offset = self.cluster_to_offset(cluster);
-        let cluster_size = 8 * 512;  // 8 secteurs * 512 octets
-        
-        if offset + cluster_size > self.storage.len() {
-            return Err("Cluster dépasse le storage");
-        }
+    // Emplacements d'un répertoire, chacun avec un indicateur "réutilisable" : entrée supprimée (0xE5),
+    // marqueur de fin (0x00) ou n'importe quel emplacement situé après ce marqueur
+    fn dir_slots_reusable(&self, dir_cluster: u32) -> impl Iterator<Item = (usize, bool)> + '_ {
+        self.dir_slot_offsets(dir_cluster).scan(false, move |ended, offset| {
+            *ended |= self.storage[offset] == DIR_ENTRY_END;
+            Some((offset, *ended || self.storage[offset] == DIR_ENTRY_DELETED))
+        })
+    }
 
-        let write_size = data.len().min(cluster_size);
-        self.storage[offset..offset + write_size].copy_from_slice(&data[..write_size]);
-        
-        // Remplir le reste avec des zéros
-        if write_size < cluster_size {
-            for i in offset + write_size..offset + cluster_size {
-                self.storage[i] = 0;
+    // `count` emplacements réutilisables qui se suivent (ils peuvent changer de cluster)
+    // Les trous laissés par des suppressions passent avant la fin du répertoire, qui ne grandit qu'en dernier recours
+    fn free_dir_slots(&self, dir_cluster: u32, count: usize) -> Result<Vec<usize>, &'static str> {
+        let mut run = Vec::with_capacity(count);
+        for (offset, reusable) in self.dir_slots_reusable(dir_cluster) {
+            if !reusable {
+                run.clear();
+                continue;
+            }
+            run.push(offset);
+            if run.len() == count {
+                return Ok(run);
             }
         }
+        Err("Repertoire plein")
+    }
+
+    // Garantit `needed_entries` emplacements réutilisables qui se suivent dans le répertoire
+    // S'il en manque, des clusters vides sont ajoutés au bout de la chaîne du répertoire (un répertoire FAT32,
+    // racine comprise, n'a pas de taille fixe) ; ils sont tout à zéro, donc lus comme "fin de répertoire"
+    // Privée : seulement appelée par les opérations qui ajoutent une entrée, qui sont vérifiées et journalisées
+    fn ensure_directory_capacity(&mut self, dir_cluster: u32, needed_entries: usize) -> Result<(), &'static str> {
+        if self.free_dir_slots(dir_cluster, needed_entries).is_ok() {
+            return Ok(());
+        }
+        // Les emplacements réutilisables en toute fin de chaîne se prolongent dans les clusters ajoutés
+        let available = self
+            .dir_slots_reusable(dir_cluster)
+            .fold(0, |run, (_, reusable)| if reusable { run + 1 } else { 0 });
 
+        let slots_per_cluster = self.cluster_size() / 32;
+        let missing = (needed_entries - available).div_ceil(slots_per_cluster) as u32;
+        let last = *self.file_chain(dir_cluster)?.last().ok_or("Chaine du repertoire vide")?;
+        // reserve_clusters rend tout en cas d'échec : le répertoire reste intact si le disque est plein
+        let added = self.reserve_free_clusters(missing)?;
+        for &cluster in &added {
+            self.write_cluster(cluster, &[])?;
+        }
+        let mut previous = last;
+        for &cluster in &added {
+            self.write_fat_entry(previous, cluster)?;
+            previous = cluster;
+        }
         Ok(())
     }
 
-    // Lit les données d'un cluster
-    pub fn read_cluster(&self, cluster: u32) -> Result<&[u8], &'static str> {
-        if cluster < 2 || cluster >= self.total_clusters + 2 {
-            return Err("Cluster invalide");
+    // Nom 8.3 de substitution pour un nom long : "a_very_long_filename.txt" -> "A_VERY~1.TXT"
+    // Le numéro après ~ est le premier qui ne correspond à aucune entrée du répertoire
+    fn short_alias(&self, dir_cluster: u32, long_name: &str) -> Result<[u8; 11], &'static str> {
+        let (base, ext) = match long_name.rfind('.') {
+            Some(dot) if dot > 0 => (&long_name[..dot], &long_name[dot + 1..]),
+            _ => (long_name, ""),
+        };
+        // Majuscules ASCII, sans points ni espaces ; tout autre caractère douteux devient '_'
+        let clean = |part: &str| -> Vec<u8> {
+            part.chars()
+                .filter(|&c| c != '.' && c != ' ')
+                .map(|c| match c.to_ascii_uppercase() {
+                    c if c.is_ascii_alphanumeric() || "!#$%&'()-@^_`{}~".contains(c) => c as u8,
+                    _ => b'_',
+                })
+                .collect()
+        };
+        let (base, ext) = (clean(base), clean(ext));
+        let base = if base.is_empty() { b"_".to_vec() } else { base };
+
+        let mut short = [b' '; 11];
+        for (slot, &byte) in short[8..].iter_mut().zip(&ext) {
+            *slot = byte;
+        }
+        for n in 1..1_000_000u32 {
+            let tail = format!("~{}", n);
+            let kept = base.len().min(8 - tail.len());
+            short[..8].fill(b' ');
+            short[..kept].copy_from_slice(&base[..kept]);
+            short[kept..kept + tail.len()].copy_from_slice(tail.as_bytes());
+            if !self.dir_entries(dir_cluster).any(|(_, entry)| entry.name == short) {
+                return Ok(short);
+            }
+        }
+        Err("Plus d'alias 8.3 disponible")
+    }
+
+    // Occupation des emplacements de 32 octets d'un répertoire, sur toute sa chaîne : (utilisés, libres, supprimés)
+    // Les entrées LFN, "." et ".." comptent comme utilisées ; tout ce qui suit le marqueur de fin (0x00) est libre
+    // Beaucoup de supprimés (0xE5) : le répertoire gagnerait à être compacté
+    pub fn directory_slot_usage(&self, dir_cluster: u32) -> (usize, usize, usize) {
+        let mut ended = false;
+        let (mut used, mut free, mut deleted) = (0, 0, 0);
+        for offset in self.dir_slot_offsets(dir_cluster) {
+            ended |= self.storage[offset] == DIR_ENTRY_END;
+            if ended {
+                free += 1;
+            } else if self.storage[offset] == DIR_ENTRY_DELETED {
+                deleted += 1;
+            } else {
+                used += 1;
+            }
+        }
+        (used, free, deleted)
+    }
+
+    // Cluster du répertoire désigné par un chemin ("/", "/DOCS", "DOCS/2024"...)
+    // Chaque composant doit être une entrée avec l'attribut répertoire
+    pub fn resolve_directory(&self, path: &str) -> Result<u32, &'static str> {
+        let root_cluster = self.boot_sector.root_cluster;
+        let mut current = root_cluster;
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            let short = normalize_83(component)?;
+            let entry = self
+                .dir_entries(current)
+                .map(|(_, entry)| entry)
+                .find(|entry| entry.name == short && entry.attributes & FILE_ATTRIBUTE_DIRECTORY != 0)
+                .ok_or("Repertoire introuvable")?;
+            // Un ".." qui pointe vers la racine contient 0 (convention FAT32)
+            current = match entry.first_cluster() {
+                0 => root_cluster,
+                cluster => cluster,
+            };
+        }
+        Ok(current)
+    }
+
+    // Contenu brut d'un répertoire : tous les clusters de sa chaîne mis bout à bout
+    // Permet d'inspecter les entrées de 32 octets telles qu'elles sont sur le disque
+    pub fn read_directory_raw(&self, path: &str) -> Result<Vec<u8>, &'static str> {
+        let dir_cluster = self.resolve_directory(path)?;
+        let mut raw = Vec::new();
+        for cluster in self.file_chain(dir_cluster)? {
+            raw.extend_from_slice(self.read_cluster(cluster)?);
+        }
+        Ok(raw)
+    }
+
+    // Crée un sous-répertoire (comme Unix mkdir) et renvoie son cluster
+    // Le nouveau cluster contient "." (lui-même) et ".." (le parent, 0 si c'est la racine)
+    // Le parent reçoit une entrée avec l'attribut FILE_ATTRIBUTE_DIRECTORY
+    pub fn create_directory(&mut self, path: &str) -> Result<u32, &'static str> {
+        self.check_writable("mkdir", || path.to_string())?;
+        let trimmed = path.trim_matches('/');
+        let (parent_path, name) = match trimmed.rsplit_once('/') {
+            Some((parent, name)) => (parent, name),
+            None => ("", trimmed),
+        };
+        let short = normalize_83(name)?;
+        let parent = self.resolve_directory(parent_path)?;
+
+        if self.dir_entries(parent).any(|(_, entry)| entry.name == short) {
+            return Err("Nom deja utilise dans ce repertoire");
         }
+        self.ensure_directory_capacity(parent, 1)?;
 
+        // Cluster vide (tout à zéro = fin de répertoire) puis les entrées "." et ".."
+        let cluster = self.allocate_cluster()?;
+        self.write_cluster(cluster, &[])?;
+        let parent_ref = if parent == self.boot_sector.root_cluster { 0 } else { parent };
+        let now = self.now();
         let offset = self.cluster_to_offset(cluster);
-        let cluster_size = 8 * 512;
-        
-        if offset + cluster_size > self.storage.len() {
-            return Err("Cluster dépasse le storage");
+        self.storage[offset..offset + 32].copy_from_slice(&DirectoryEntry::new_directory(*b".          ", cluster).stamped(now).to_bytes());
+        self.storage[offset + 32..offset + 64].copy_from_slice(&DirectoryEntry::new_directory(*b"..         ", parent_ref).stamped(now).to_bytes());
+
+        self.add_dir_entry(parent, DirectoryEntry::new_directory(short, cluster).stamped(now))?;
+        self.record(Operation::Mkdir { path: path.to_string() });
+        Ok(cluster)
+    }
+
+    // Marque l'entrée d'un fichier comme supprimée (0xE5 dans le premier octet du nom)
+    fn mark_entry_deleted(&mut self, filename: &str) -> Result<(), &'static str> {
+        let offset = self.find_entry_offset(filename).ok_or("Fichier non trouve")?;
+        // Les entrées LFN du nom long sont supprimées avec l'entrée 8.3
+        for lfn_offset in self.lfn_slots_before(self.boot_sector.root_cluster, offset) {
+            self.storage[lfn_offset] = DIR_ENTRY_DELETED;
         }
+        self.storage[offset] = DIR_ENTRY_DELETED;
+        Ok(())
+    }
 
-        Ok(&self.storage[offset..offset + cluster_size])
+    // Renomme un fichier du répertoire racine et renvoie le nom final
+    // Sans extension dans le nouveau nom, on garde celle de l'ancien : DATA1.TXT -> "DATA2" donne DATA2.TXT
+    // Seuls les 11 octets du nom changent : chaîne de clusters, taille et attributs sont conservés
+    pub fn rename_file(&mut self, old_name: &str, new_name: &str) -> Result<String, &'static str> {
+        self.check_writable("rename", || format!("{} {}", old_name, new_name))?;
+        let offset = self.find_entry_offset(old_name).ok_or("Fichier non trouve")?;
+
+        let final_name = match (new_name.contains('.'), old_name.rsplit_once('.')) {
+            (false, Some((_, ext))) => format!("{}.{}", new_name, ext),
+            _ => new_name.to_string(),
+        };
+        let short = normalize_83(&final_name)?;
+
+        // Renommer vers son propre nom (ou juste changer la casse) ne doit pas compter comme un doublon
+        if self.find_entry_offset(&final_name).is_some_and(|other| other != offset) {
+            return Err("Fichier deja existant");
+        }
+
+        // L'ancien nom long ne correspondrait plus : ses entrées LFN sont supprimées
+        for lfn_offset in self.lfn_slots_before(self.boot_sector.root_cluster, offset) {
+            self.storage[lfn_offset] = DIR_ENTRY_DELETED;
+        }
+        self.storage[offset..offset + 11].copy_from_slice(&short);
+        self.record(Operation::Rename { from: old_name.to_string(), to: new_name.to_string() });
+        Ok(final_name.to_ascii_uppercase())
     }
 
-    // Crée un fichier simple
-    pub fn create_file(&mut self, name: &str, data: &[u8]) -> Result<(), &'static str> {
-        if name.len() > 11 {
-            return Err("Nom de fichier trop long (max 11 caractères)");
+    // Échange les noms de deux fichiers existants en une seule opération, sans toucher aux données ni à la FAT
+    // Plutôt que de déplacer les noms (et leurs entrées LFN, dont le checksum dépend du nom 8.3), on échange
+    // tout le reste des deux entrées : attributs, horodatages, premier cluster et taille
+    pub fn swap_names(&mut self, a: &str, b: &str) -> Result<(), &'static str> {
+        self.check_writable("swap", || format!("{} {}", a, b))?;
+        let offset_a = self.find_entry_offset(a).ok_or("Fichier non trouve")?;
+        let offset_b = self.find_entry_offset(b).ok_or("Fichier non trouve")?;
+        if offset_a == offset_b {
+            return Err("Les deux noms designent le meme fichier");
+        }
+        let is_dir = |offset: usize| self.storage[offset + 11] & FILE_ATTRIBUTE_DIRECTORY != 0;
+        if is_dir(offset_a) || is_dir(offset_b) {
+            return Err("Seuls des fichiers peuvent echanger leurs noms");
         }
 
-        // Allouer un cluster pour le fichier
-        let file_cluster = self.allocate_cluster()?;
-        
-        // Écrire les données du fichier
-        self.write_cluster(file_cluster, data)?;
+        // Octets 11 à 31 de chaque entrée : tout sauf le nom 8.3
+        let mut entry_a = [0u8; 21];
+        entry_a.copy_from_slice(&self.storage[offset_a + 11..offset_a + 32]);
+        self.storage.copy_within(offset_b + 11..offset_b + 32, offset_a + 11);
+        self.storage[offset_b + 11..offset_b + 32].copy_from_slice(&entry_a);
 
-        println!("✅ Fichier '{}' cree dans le cluster {} ({} octets)", name, file_cluster, data.len());
-        
+        self.record(Operation::Swap { a: a.to_string(), b: b.to_string() });
         Ok(())
     }
 
-    // Lit un fichier par son cluster et taille
-    pub fn read_file(&self, cluster: u32, file_size: usize) -> Result<Vec<u8>, &'static str> {
-        let cluster_data = self.read_cluster(cluster)?;
-        
-        // Retourner seulement la taille réelle du fichier
-        if file_size > cluster_data.len() {
+    // Listing binaire compact, pratique pour un programme qui consomme la liste sans parser du texte
+    // Un enregistrement par fichier, en little-endian :
+    // u16 longueur_nom | nom (octets) | u32 taille | u32 premier_cluster
+    pub fn list_files_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for file in self.files_iter() {
+            out.extend_from_slice(&(file.name.len() as u16).to_le_bytes());
+            out.extend_from_slice(file.name.as_bytes());
+            out.extend_from_slice(&(file.size as u32).to_le_bytes());
+            out.extend_from_slice(&file.cluster.to_le_bytes());
+        }
+        out
+    }
+
+    // Suit la chaîne de clusters dans la FAT à partir du premier cluster d'un fichier
+    // Renvoie la liste ordonnée des clusters jusqu'au marqueur de fin (EOC)
+    pub fn file_chain(&self, first_cluster: u32) -> Result<Vec<u32>, &'static str> {
+        let mut chain = Vec::new();
+        let mut cluster = first_cluster;
+
+        loop {
+            if cluster < 2 || cluster >= self.total_clusters + 2 {
+                return Err("Chaine de clusters invalide");
+            }
+            // Une chaîne plus longue que le disque = boucle dans la FAT
+            if chain.len() as u32 >= self.total_clusters {
+                return Err("Boucle dans la chaine de clusters");
+            }
+            chain.push(cluster);
+
+            let next = self.read_fat_entry(cluster)?;
+            if next >= CLUSTER_END {
+                return Ok(chain);
+            }
+            cluster = next;
+        }
+    }
+
+    // Numéro du cluster à la position n (0 = premier) dans la chaîne d'un fichier
+    pub fn nth_cluster_of(&self, name: &str, n: usize) -> Result<u32, &'static str> {
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        let chain = self.file_chain(file.cluster)?;
+        chain.get(n).copied().ok_or("Position au-dela de la fin de la chaine")
+    }
+
+    // Taille "utile" d'un fichier : sa taille sans les octets nuls de fin (bourrage d'un import par exemple)
+    // La chaîne est parcourue depuis la fin : on s'arrête au premier cluster qui contient un octet non nul
+    pub fn logical_size(&self, name: &str) -> Result<usize, &'static str> {
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        if file.sparse {
+            return Err(SPARSE_UNSUPPORTED);
+        }
+        let cluster_size = self.cluster_size();
+        let chain = self.file_chain(file.cluster)?;
+        if file.size > chain.len() * cluster_size {
             return Err("Taille de fichier invalide");
         }
-        
-        Ok(cluster_data[..file_size].to_vec())
+
+        for index in (0..file.size.div_ceil(cluster_size)).rev() {
+            let start = index * cluster_size;
+            let data = &self.read_cluster(chain[index])?[..(file.size - start).min(cluster_size)];
+            if let Some(last) = data.iter().rposition(|&byte| byte != 0) {
+                return Ok(start + last + 1);
+            }
+        }
+        Ok(0)
     }
 
-    // Vérifie l'intégrité du système de fichiers
-    pub fn check_filesystem(&self) -> Result<(), &'static str> {
-        println!("\n--- Verification du systeme de fichiers ---");
-        
-        // Vérifier la signature du boot sector
-        let signature = self.boot_sector.signature;
-        if signature != 0xAA55 {
-            return Err("Signature du boot sector invalide");
+    // Toutes les chaînes de clusters, fichier par fichier (nom + clusters dans l'ordre)
+    // Une chaîne cassée garde son erreur au lieu de paraître vide, et les clusters partagés sont relevés
+    pub fn all_chains(&self) -> Vec<FileChain> {
+        let crosslinks = self.find_crosslinks();
+        self.files_iter()
+            .map(|file| {
+                let clusters = self.file_chain_or_empty(file.cluster);
+                let shared = clusters
+                    .as_deref()
+                    .unwrap_or_default()
+                    .iter()
+                    .copied()
+                    .filter(|cluster| crosslinks.binary_search_by_key(cluster, |(shared, _)| *shared).is_ok())
+                    .collect();
+                FileChain { name: file.name, clusters, shared }
+            })
+            .collect()
+    }
+
+    // Clusters présents dans la chaîne de plusieurs fichiers (cross-link), avec les noms concernés
+    // Sur un volume sain la liste est vide : chaque cluster appartient à un seul fichier
+    pub fn find_crosslinks(&self) -> Vec<(u32, Vec<String>)> {
+        // Premier passage : nombre de fichiers par cluster dans une ClusterMap, sans liste de clusters par fichier
+        let mut counts = self.cluster_map();
+        for file in self.files_iter() {
+            self.for_each_chain_cluster(file.cluster, |cluster| counts.bump(cluster));
         }
-        println!("✅ Signature du boot sector valide (0x{:04X})", signature);
-        
-        // Vérifier que les clusters système sont bien marqués
-        let fat0 = self.read_fat_entry(0)?;
-        let fat1 = self.read_fat_entry(1)?;
-        let fat2 = self.read_fat_entry(2)?;
-        
-        if fat0 == 0x0FFFFFF8 && fat1 == 0x0FFFFFF8 && fat2 == 0x0FFFFFF8 {
-            println!("✅ Clusters systeme correctement marques");
-        } else {
-            return Err("Clusters systeme incorrects");
+        let mut crosslinks: Vec<(u32, Vec<String>)> = (2..self.total_clusters + 2)
+            .filter(|&cluster| counts.get(cluster) > 1)
+            .map(|cluster| (cluster, Vec::new()))
+            .collect();
+        self.recycle_cluster_map(counts);
+
+        // Second passage, seulement s'il y a des clusters partagés : les noms de leurs fichiers
+        if !crosslinks.is_empty() {
+            for file in self.files_iter() {
+                self.for_each_chain_cluster(file.cluster, |cluster| {
+                    if let Ok(index) = crosslinks.binary_search_by_key(&cluster, |(shared, _)| *shared) {
+                        crosslinks[index].1.push(file.name.clone());
+                    }
+                });
+            }
         }
-        
-        // Compter les clusters utilisés
-        let mut used_clusters = 3; // 0, 1, 2 sont toujours utilisés
+        crosslinks
+    }
+
+    // Appelle `visit` pour chaque cluster d'une chaîne, lu au fur et à mesure dans la FAT
+    // Chaîne invalide ou qui boucle : rien n'est visité
+    fn for_each_chain_cluster(&self, first_cluster: u32, mut visit: impl FnMut(u32)) {
+        if let Ok(len) = self.chain_len(first_cluster) {
+            self.chain_clusters(first_cluster).take(len as usize).for_each(&mut visit);
+        }
+    }
+
+    // ClusterMap à zéro couvrant tous les clusters (0..total_clusters + 2)
+    // Le buffer est repris de l'appel précédent : après le premier, plus aucune allocation
+    fn cluster_map(&self) -> ClusterMap {
+        let mut bits = self.cluster_scratch.take();
+        bits.clear();
+        bits.resize((self.total_clusters as usize + 2).div_ceil(4), 0);
+        ClusterMap { bits }
+    }
+
+    // Rend le buffer d'une ClusterMap pour le prochain cluster_map
+    fn recycle_cluster_map(&self, map: ClusterMap) {
+        self.cluster_scratch.set(map.bits);
+    }
+
+    // Longueur de la chaîne qui part de `first_cluster`, avec les mêmes vérifications que file_chain
+    // mais sans construire la liste des clusters
+    fn chain_len(&self, first_cluster: u32) -> Result<u32, &'static str> {
+        let mut len = 0;
+        let mut cluster = first_cluster;
+        loop {
+            if cluster < 2 || cluster >= self.total_clusters + 2 {
+                return Err("Chaine de clusters invalide");
+            }
+            if len >= self.total_clusters {
+                return Err("Boucle dans la chaine de clusters");
+            }
+            len += 1;
+
+            let next = self.read_fat_entry(cluster)?;
+            if next >= CLUSTER_END {
+                return Ok(len);
+            }
+            cluster = next;
+        }
+    }
+
+    // Clusters d'une chaîne dans l'ordre, lus au fur et à mesure dans la FAT (à borner avec chain_len)
+    fn chain_clusters(&self, first_cluster: u32) -> impl Iterator<Item = u32> + '_ {
+        core::iter::successors(Some(first_cluster), move |&cluster| match self.read_fat_entry(cluster) {
+            Ok(next) if next < CLUSTER_END => Some(next),
+            _ => None,
+        })
+    }
+
+    // Nombre de chaînes de l'arborescence (racine, sous-répertoires, fichiers) qui passent par chaque cluster
+    // 0 = inatteignable, plus de 1 = cross-link ; à rendre avec recycle_cluster_map
+    fn cluster_refcounts(&self) -> ClusterMap {
+        let mut counts = self.cluster_map();
+        let end = self.total_clusters + 2;
+        // Chaîne invalide ou qui boucle : rien n'est compté, comme si elle n'était pas atteinte
+        let count_chain = |counts: &mut ClusterMap, first: u32| self.for_each_chain_cluster(first, |cluster| counts.bump(cluster));
+        let root_cluster = self.boot_sector.root_cluster;
+        count_chain(&mut counts, root_cluster);
+
+        let mut pending = vec![root_cluster];
+        while let Some(dir_cluster) = pending.pop() {
+            for (_, entry) in self.dir_entries(dir_cluster) {
+                // "." et ".." pointent vers des répertoires déjà comptés ; cluster 0 = pas de chaîne
+                let first = entry.first_cluster();
+                if entry.name[0] == b'.' || first < 2 {
+                    continue;
+                }
+                // Un répertoire déjà atteint (cross-link ou boucle) est compté mais pas reparcouru
+                let new_dir = entry.attributes & FILE_ATTRIBUTE_DIRECTORY != 0 && first < end && counts.get(first) == 0;
+                count_chain(&mut counts, first);
+                if new_dir {
+                    pending.push(first);
+                }
+            }
+        }
+        counts
+    }
+
+    // Premier cluster compté dans plusieurs chaînes par cluster_refcounts (cross-link), s'il y en a un
+    fn first_shared_cluster(&self) -> Option<u32> {
+        let counts = self.cluster_refcounts();
+        let shared = (2..self.total_clusters + 2).find(|&cluster| counts.get(cluster) > 1);
+        self.recycle_cluster_map(counts);
+        shared
+    }
+
+    // Chaînes orphelines : clusters alloués dans la FAT qu'aucune entrée de répertoire n'atteint
+    // Chaque chaîne commence à un cluster orphelin vers lequel aucun autre cluster ne pointe
+    fn orphan_chains(&self) -> Result<Vec<Vec<u32>>, &'static str> {
+        // Une seule carte : après le comptage des chaînes atteintes, chaque cluster pointé par un cluster alloué
+        // est marqué à son tour. Seuls les débuts de chaîne orphelins restent à 0 (la suite d'une chaîne
+        // atteinte est elle-même atteinte, le marquage ne change rien pour elle)
+        let mut marks = self.cluster_refcounts();
+        let end = self.total_clusters + 2;
+        let mut heads = Vec::new();
+        for cluster in 3..end {
+            let entry = self.read_fat_entry(cluster)?;
+            if entry >= 2 && entry < end {
+                marks.bump(entry);
+            }
+        }
+        for cluster in 3..end {
+            let entry = self.read_fat_entry(cluster)?;
+            if entry != CLUSTER_FREE && entry != CLUSTER_BAD && marks.get(cluster) == 0 {
+                heads.push(cluster);
+            }
+        }
+        self.recycle_cluster_map(marks);
+
+        // Une chaîne cassée (cluster invalide, boucle) est laissée de côté
+        Ok(heads.into_iter().filter_map(|head| self.file_chain(head).ok()).collect())
+    }
+
+    // Passe de réparation façon fsck, à partir de la racine et de toutes les entrées de l'arborescence :
+    // - une chaîne qui sort du disque, tombe sur un cluster libre/défectueux ou boucle est coupée à cet endroit
+    // - une chaîne qui rejoint des clusters déjà utilisés par une autre reçoit sa propre copie de ces clusters
+    // - les clusters alloués qu'aucune chaîne n'atteint (perdus) sont libérés
+    pub fn repair_filesystem(&mut self) -> Result<RepairReport, &'static str> {
+        self.check_writable("repair", String::new)?;
+        let mut report = RepairReport::default();
+        // 0 = pas encore vu, 1 = appartient à une chaîne déjà réparée, 2 = à la chaîne en cours
+        let mut marks = self.cluster_map();
+
+        let root_cluster = self.boot_sector.root_cluster;
+        if self.repair_chain(root_cluster, &mut marks, &mut report)?.0 != root_cluster {
+            return Err("Chaine du repertoire racine irreparable");
+        }
+
+        let mut pending = vec![root_cluster];
+        while let Some(dir_cluster) = pending.pop() {
+            let entries: Vec<(usize, DirectoryEntry)> = self.dir_entries(dir_cluster).collect();
+            for (offset, mut entry) in entries {
+                let first = entry.first_cluster();
+                // "." et ".." pointent vers des répertoires déjà traités ; cluster 0 = fichier vide sans chaîne
+                if entry.name[0] == b'.' || first == 0 {
+                    continue;
+                }
+                let is_dir = entry.attributes & FILE_ATTRIBUTE_DIRECTORY != 0;
+                let (new_first, len) = self.repair_chain(first, &mut marks, &mut report)?;
+
+                // La taille annoncée ne peut pas dépasser la chaîne réparée
+                let capacity = (len * self.cluster_size()) as u32;
+                // Un fichier creux annonce plus que sa chaîne, par construction
+                let oversized = !is_dir && !entry.is_sparse() && entry.file_size > capacity;
+                if new_first != first || oversized {
+                    entry.first_cluster_high = (new_first >> 16) as u16;
+                    entry.first_cluster_low = new_first as u16;
+                    if oversized {
+                        entry.file_size = capacity;
+                    }
+                    self.storage[offset..offset + 32].copy_from_slice(&entry.to_bytes());
+                }
+                if is_dir && new_first != 0 {
+                    pending.push(new_first);
+                }
+            }
+        }
+
         for cluster in 3..self.total_clusters + 2 {
-            if self.read_fat_entry(cluster)? != CLUSTER_FREE {
-                used_clusters += 1;
+            let value = self.read_fat_entry(cluster)?;
+            if marks.get(cluster) == 0 && value != CLUSTER_FREE && value != CLUSTER_BAD {
+                self.write_fat_entry(cluster, CLUSTER_FREE)?;
+                report.lost_clusters += 1;
             }
         }
-        
-        println!("✅ Clusters utilises: {} / {}", used_clusters, self.total_clusters + 2);
-        
+        self.recycle_cluster_map(marks);
+        self.record(Operation::Repair);
+        Ok(report)
+    }
+
+    // Répare une chaîne pour repair_filesystem et renvoie son (nouveau) premier cluster et sa longueur
+    // Premier cluster 0 = plus aucune chaîne (le premier cluster lui-même était invalide)
+    fn repair_chain(&mut self, first: u32, marks: &mut ClusterMap, report: &mut RepairReport) -> Result<(u32, usize), &'static str> {
+        let end = self.total_clusters + 2;
+        let cluster_size = self.cluster_size();
+        let mut head = 0;
+        let mut prev: Option<u32> = None;
+        let mut len = 0;
+        let mut cluster = first;
+        let mut crosslinked = false;
+        let mut truncated = false;
+
+        loop {
+            // Cluster réellement utilisé à cette position : le cluster lui-même, ou sa copie s'il est déjà pris
+            let actual = if cluster < 2 || cluster >= end || marks.get(cluster) == 2 {
+                // Hors du disque, ou déjà vu dans cette même chaîne (boucle) : on coupe avant
+                truncated = true;
+                None
+            } else if marks.get(cluster) == 1 {
+                let copy = self.allocate_cluster()?;
+                let (from, to) = (self.cluster_to_offset(cluster), self.cluster_to_offset(copy));
+                self.storage.copy_within(from..from + cluster_size, to);
+                crosslinked = true;
+                Some(copy)
+            } else {
+                Some(cluster)
+            };
+            let Some(actual) = actual else {
+                if let Some(prev) = prev {
+                    self.write_fat_entry(prev, CLUSTER_END)?;
+                }
+                break;
+            };
+
+            marks.set(actual, 2);
+            match prev {
+                // Après une copie, le cluster précédent est EOC : on le relie au cluster de cette position
+                Some(prev) if self.read_fat_entry(prev)? != actual => self.write_fat_entry(prev, actual)?,
+                Some(_) => {}
+                None => head = actual,
+            }
+            prev = Some(actual);
+            len += 1;
+
+            let next = self.read_fat_entry(cluster)?;
+            if next >= CLUSTER_END {
+                if actual != cluster {
+                    self.write_fat_entry(actual, CLUSTER_END)?;
+                }
+                break;
+            }
+            if next == CLUSTER_FREE || next == CLUSTER_BAD {
+                self.write_fat_entry(actual, CLUSTER_END)?;
+                truncated = true;
+                break;
+            }
+            cluster = next;
+        }
+
+        // La chaîne est terminée : ses clusters passent dans le camp des chaînes réparées
+        for cluster in 2..end {
+            if marks.get(cluster) == 2 {
+                marks.set(cluster, 1);
+            }
+        }
+        report.crosslinks += crosslinked as usize;
+        report.truncated_chains += truncated as usize;
+        Ok((head, len))
+    }
+
+    // Rattache chaque chaîne orpheline à la racine sous le nom RECOVER_N.DAT, au lieu de la perdre
+    // La taille est estimée depuis la longueur de la chaîne, sans les zéros de fin du dernier cluster
+    // Renvoie les noms des fichiers créés
+    pub fn recover_orphans(&mut self) -> Result<Vec<String>, &'static str> {
+        self.check_writable("recover", String::new)?;
+        let cluster_size = self.cluster_size();
+        let root_cluster = self.boot_sector.root_cluster;
+        let mut recovered = Vec::new();
+        let mut n = 0;
+
+        for chain in self.orphan_chains()? {
+            let last = self.read_cluster(chain[chain.len() - 1])?;
+            let tail = last.iter().rposition(|&byte| byte != 0).map_or(0, |index| index + 1);
+            let size = (chain.len() - 1) * cluster_size + tail;
+
+            // Premier RECOVER_N.DAT libre (un ancien fichier récupéré peut déjà porter le nom)
+            let name = loop {
+                let name = format!("RECOVER_{}.DAT", n);
+                n += 1;
+                if self.find_entry_offset(&name).is_none() {
+                    break name;
+                }
+            };
+            let alias = self.short_alias(root_cluster, &name)?;
+            let entry = DirectoryEntry::new_file(alias, chain[0], size as u32).stamped(self.now());
+            self.add_named_entry(root_cluster, entry, Some(&name))?;
+            recovered.push(name);
+        }
+        self.record(Operation::Recover);
+        Ok(recovered)
+    }
+
+    // Trouve un fichier par son nom
+    // S'arrête au premier fichier qui correspond, sans construire la liste complète
+    // La comparaison se fait sur le nom 8.3 normalisé ("hello.txt" trouve "HELLO.TXT") ou sur le nom long
+    pub fn find_file(&self, filename: &str) -> Option<FileInfo> {
+        let short = normalize_83(filename).ok();
+        self.named_entries(self.boot_sector.root_cluster)
+            .filter(|(_, entry, _)| entry.attributes & FILE_ATTRIBUTE_DIRECTORY == 0)
+            .find(|(_, entry, name)| Some(entry.name) == short || name.eq_ignore_ascii_case(filename))
+            .map(|(_, entry, name)| FileInfo {
+                name,
+                cluster: entry.first_cluster(),
+                size: entry.file_size as usize,
+                modified: entry.modified(),
+                attributes: entry.attributes,
+                sparse: entry.is_sparse(),
+            })
+    }
+
+    // Crée un fichier avec un nom spécifique (version améliorée)
+    // Un nom qui ne tient pas en 8.3 est gardé en nom long (LFN), avec un alias 8.3 généré
+    pub fn create_file_named(&mut self, name: &str, data: &[u8]) -> Result<u32, &'static str> {
+        self.check_writable("create", || format!("{} ({} octets)", name, data.len()))?;
+        let (short, long_name) = self.new_file_names(name)?;
+        let first_cluster = self.write_new_file(short, long_name, data, data.len())?;
+        self.record(Operation::Create { name: name.to_string(), data: data.to_vec() });
+        Ok(first_cluster)
+    }
+
+    // Réserve tout de suite la chaîne d'un fichier de `size_bytes` octets, sans données (clusters remis à zéro)
+    // La taille est fixée dès maintenant : write_file_range remplit ensuite le fichier, le reste se lit en zéros
+    pub fn preallocate(&mut self, name: &str, size_bytes: usize) -> Result<(), &'static str> {
+        self.check_writable("prealloc", || format!("{} {}", name, size_bytes))?;
+        let (short, long_name) = self.new_file_names(name)?;
+        self.write_new_file(short, long_name, &[], size_bytes)?;
+        self.record(Operation::Preallocate { name: name.to_string(), size: size_bytes });
         Ok(())
     }
 
-    // Affiche un résumé du système
-    pub fn summary(&self) {
-        println!("\n=== Résumé du système FAT32 ===");
-        let bytes_per_sector = self.boot_sector.bytes_per_sector;
-        let sectors_per_cluster = self.boot_sector.sectors_per_cluster;
-        let cluster_size = bytes_per_sector as u32 * sectors_per_cluster as u32;
-        
-        println!("Taille cluster: {} octets", cluster_size);
-        println!("Capacite totale: {} octets ({} MB)", 
-                 self.storage.len(), self.storage.len() / (1024 * 1024));
-        
-        if let Ok(free_space) = self.get_free_space() {
-            let used_space = self.storage.len() as u32 - free_space;
-            println!("Espace utilise: {} octets ({} KB)", used_space, used_space / 1024);
-            println!("Espace libre: {} octets ({} KB)", free_space, free_space / 1024);
+    // Nom 8.3 et éventuel nom long d'un nouveau fichier à la racine ; le nom ne doit pas être déjà pris
+    // Un nom qui ne tient pas en 8.3 devient un nom long, avec un alias 8.3 généré
+    fn new_file_names<'a>(&self, name: &'a str) -> Result<([u8; 11], Option<&'a str>), &'static str> {
+        let (short, long_name) = match normalize_83(name) {
+            Ok(short) => (short, None),
+            Err(_) => {
+                validate_long_name(name)?;
+                (self.short_alias(self.boot_sector.root_cluster, name)?, Some(name))
+            }
+        };
+        // Vérifier si le nom est déjà pris (fichier ou répertoire)
+        if self.find_entry_offset(name).is_some() {
+            return Err("Fichier deja existant");
         }
+        Ok((short, long_name))
     }
 
-    // Calcule l'espace libre
-    pub fn get_free_space(&self) -> Result<u32, &'static str> {
-        let mut free_clusters = 0;
+    // Crée un fichier dont l'appelant choisit à la fois le nom long et l'alias 8.3 (au lieu d'un alias généré)
+    // Permet de reproduire exactement un répertoire existant ; le checksum des entrées LFN est calculé sur cet alias
+    pub fn create_file_with_names(&mut self, long_name: &str, short_name: &str, data: &[u8]) -> Result<u32, &'static str> {
+        self.check_writable("createlfn", || format!("{} {} ({} octets)", long_name, short_name, data.len()))?;
+        validate_long_name(long_name)?;
+        let short = normalize_83(short_name)?;
+
+        if self.find_entry_offset(long_name).is_some() {
+            return Err("Fichier deja existant");
+        }
+        if self.dir_entries(self.boot_sector.root_cluster).any(|(_, entry)| entry.name == short) {
+            return Err("Nom 8.3 deja utilise");
+        }
+        let first_cluster = self.write_new_file(short, Some(long_name), data, data.len())?;
+        self.record(Operation::CreateLfn {
+            long_name: long_name.to_string(),
+            short_name: short_name.to_string(),
+            data: data.to_vec(),
+        });
+        Ok(first_cluster)
+    }
+
+    // Alloue la chaîne, écrit les données puis ajoute l'entrée (précédée des entrées LFN du nom long)
+    // Le fichier fait `size` octets (au moins data.len()) : ce qui suit les données est rempli de zéros
+    // Les noms sont déjà validés et libres ; renvoie le premier cluster
+    fn write_new_file(&mut self, short: [u8; 11], long_name: Option<&str>, data: &[u8], size: usize) -> Result<u32, &'static str> {
+        let root_cluster = self.boot_sector.root_cluster;
+        if size > u32::MAX as usize {
+            return Err("Fichier trop gros pour FAT32 (max 4 Go)");
+        }
+        let size = size.max(data.len());
+
+        // Place dans le répertoire (entrées LFN comprises), en l'agrandissant si besoin, avant d'allouer les données
+        let lfn_count = long_name.map_or(0, |name| name.encode_utf16().count().div_ceil(LFN_CHARS_PER_ENTRY));
+        self.ensure_directory_capacity(root_cluster, lfn_count + 1)?;
+
+        // Allouer la chaîne de clusters : au moins un cluster, même pour un fichier vide
+        let cluster_size = self.cluster_size();
+        let clusters_needed = size.div_ceil(cluster_size).max(1);
+        let mut chain: Vec<u32> = Vec::with_capacity(clusters_needed);
         
-        for cluster in 3..self.total_clusters + 2 {
-            if self.read_fat_entry(cluster)? == CLUSTER_FREE {
-                free_clusters += 1;
+        for _ in 0..clusters_needed {
+            let cluster = match self.allocate_cluster() {
+                Ok(cluster) => cluster,
+                Err(e) => {
+                    // Disque plein en cours de route : on rend les clusters déjà pris
+                    self.release_clusters(&chain)?;
+                    return Err(e);
+                }
+            };
+            // allocate_cluster marque le nouveau cluster EOC, il suffit de relier le précédent
+            if let Some(&previous) = chain.last() {
+                self.write_fat_entry(previous, cluster)?;
             }
+            chain.push(cluster);
         }
         
-        Ok(free_clusters * 8 * 512)  // clusters * secteurs/cluster * octets/secteur
+        // Écrire les données du fichier, un morceau par cluster
+        for (index, &cluster) in chain.iter().enumerate() {
+            let start = (index * cluster_size).min(data.len());
+            let end = (start + cluster_size).min(data.len());
+            self.write_cluster(cluster, &data[start..end])?;
+        }
+
+        // L'entrée de répertoire rend le fichier visible : nom, premier cluster et taille exacte
+        let entry = DirectoryEntry::new_file(short, chain[0], size as u32).stamped(self.now());
+        self.add_named_entry(root_cluster, entry, long_name)?;
+        Ok(chain[0])
+    }
+
+    // Crée plusieurs fichiers d'un coup : tout ou rien
+    // Si un fichier échoue (nom pris, disque plein...), ceux déjà créés par ce lot sont retirés
+    pub fn create_files(&mut self, files: impl IntoIterator<Item = (String, Vec<u8>)>) -> Result<(), &'static str> {
+        let mut created: Vec<String> = Vec::new();
+        let logged = self.op_log.as_ref().map_or(0, |log| log.len());
+        // La racine peut grandir pendant le lot (ensure_directory_capacity) : elle reprend sa taille en cas d'échec
+        let root_cluster = self.boot_sector.root_cluster;
+        let root_clusters = self.chain_len(root_cluster)? as usize;
+
+        for (name, data) in files {
+            if let Err(e) = self.create_file_named(&name, &data) {
+                // Annulation dans l'ordre inverse : chaque entrée retirée redevient libre
+                for name in created.iter().rev() {
+                    self.undo_create(name)?;
+                }
+                let root_chain = self.file_chain(root_cluster)?;
+                self.shrink_chain(&root_chain, root_clusters)?;
+                // Les créations annulées disparaissent aussi du journal
+                if let Some(log) = self.op_log.as_mut() {
+                    log.truncate(logged);
+                }
+                return Err(e);
+            }
+            created.push(name);
+        }
+
+        Ok(())
+    }
+
+    // Copie tous les fichiers de la racine de `other` dans la racine de ce disque
+    // Les conflits de nom sont traités selon `on_conflict` ; la place nécessaire (données et agrandissement
+    // de la racine) est vérifiée avant la première écriture, d'après les tailles : un disque trop petit n'est
+    // pas modifié. Les fichiers sont ensuite copiés un par un, cluster par cluster (voir copy_file_from)
+    pub fn merge_from(&mut self, other: &Fat32FileSystem, on_conflict: ConflictPolicy) -> Result<MergeReport, &'static str> {
+        self.check_writable("merge", || format!("{:?}", on_conflict))?;
+        let cluster_size = self.cluster_size();
+        let sources: Vec<FileInfo> = other.list_files(true);
+
+        // Plan complet d'abord : nom de destination de chaque fichier et place qu'il demande
+        let mut report = MergeReport::default();
+        let mut plan: Vec<(&FileInfo, String)> = Vec::new();
+        let (mut clusters_needed, mut new_entries) = (0usize, 0usize);
+        for source in &sources {
+            let name = &source.name;
+            let needed = source.size.div_ceil(cluster_size).max(1);
+            let target = match (self.find_entry_offset(name).is_some(), on_conflict) {
+                (false, _) => name.clone(),
+                (true, ConflictPolicy::Skip) => {
+                    report.skipped.push(name.clone());
+                    continue;
+                },
+                (true, ConflictPolicy::Overwrite) => {
+                    let existing = self.find_file(name).ok_or("Un repertoire porte deja ce nom")?;
+                    // L'ancien fichier est supprimé avant la copie : seuls les clusters en plus comptent
+                    clusters_needed += needed.saturating_sub(self.chain_len(existing.cluster)? as usize);
+                    plan.push((source, name.clone()));
+                    continue;
+                },
+                (true, ConflictPolicy::Rename) => {
+                    let renamed = self.merge_name(name, &sources, &plan)?;
+                    report.renamed.push((name.clone(), renamed.clone()));
+                    renamed
+                },
+            };
+            clusters_needed += needed;
+            new_entries += match normalize_83(&target) {
+                Ok(_) => 1,
+                Err(_) => target.encode_utf16().count().div_ceil(LFN_CHARS_PER_ENTRY) + 1,
+            };
+            plan.push((source, target));
+        }
+
+        // Seuls les emplacements après la fin de la racine sont sûrs d'être à la suite ; les trous sont un bonus
+        let root_cluster = self.boot_sector.root_cluster;
+        let (_, free_slots, _) = self.directory_slot_usage(root_cluster);
+        let root_growth = new_entries.saturating_sub(free_slots).div_ceil(cluster_size / 32);
+        if clusters_needed + root_growth > self.count_free_clusters()? as usize {
+            return Err("Espace insuffisant pour la fusion");
+        }
+
+        for (source, target) in plan {
+            if self.find_entry_offset(&target).is_some() {
+                self.delete_file_by_name(&target)?;
+            }
+            self.copy_file_from(other, source, &target)?;
+            if !report.renamed.iter().any(|(_, to)| *to == target) {
+                report.copied.push(target);
+            }
+        }
+        Ok(report)
+    }
+
+    // Crée `target` (rempli de zéros) puis y recopie `file` de `other` cluster par cluster, directement
+    // d'un storage à l'autre : le contenu ne passe par le heap (qui ne libère rien) que si le journal est actif
+    fn copy_file_from(&mut self, other: &Fat32FileSystem, file: &FileInfo, target: &str) -> Result<(), &'static str> {
+        let (short, long_name) = self.new_file_names(target)?;
+        let first_cluster = self.write_new_file(short, long_name, &[], file.size)?;
+        let chain = self.file_chain(first_cluster)?;
+        let source_chain = other.read_chain(file)?;
+        for (index, &cluster) in chain.iter().enumerate().take(file.size.div_ceil(self.cluster_size())) {
+            // Un trou de fichier creux reste à zéro
+            if let Some(bytes) = other.cluster_piece(file, &source_chain, index)? {
+                self.write_in_cluster(cluster, 0, bytes)?;
+            }
+        }
+        // Dans le journal, la copie est l'écriture du fichier ; l'enregistrement met aussi son CRC à jour
+        let data = match self.op_log {
+            Some(_) => other.read_contents(file)?,
+            None => Vec::new(),
+        };
+        self.record(Operation::Write { name: target.to_string(), data });
+        Ok(())
+    }
+
+    // Premier nom "{base}_{n}.{ext}" libre à la fois sur ce disque, parmi les fichiers à fusionner
+    // et parmi les noms déjà choisis pour la fusion
+    fn merge_name(&self, name: &str, sources: &[FileInfo], plan: &[(&FileInfo, String)]) -> Result<String, &'static str> {
+        let (base, ext) = match name.rfind('.') {
+            Some(dot) if dot > 0 => (&name[..dot], &name[dot..]),
+            _ => (name, ""),
+        };
+        (1..1_000_000u32)
+            .map(|n| format!("{}_{}{}", base, n, ext))
+            .find(|candidate| {
+                self.find_entry_offset(candidate).is_none()
+                    && !sources
+                        .iter()
+                        .map(|source| &source.name)
+                        .chain(plan.iter().map(|(_, target)| target))
+                        .any(|taken| taken.eq_ignore_ascii_case(candidate))
+            })
+            .ok_or("Plus de nom disponible pour la fusion")
+    }
+
+    // Défait la création d'un fichier : libère sa chaîne et remet son entrée à zéro (pas 0xE5) si elle
+    // termine le répertoire ; une entrée posée dans le trou d'une suppression redevient 0xE5, sinon les
+    // entrées suivantes seraient cachées derrière un faux marqueur de fin
+    // N'est correct que pour la dernière entrée ajoutée, comme dans create_files
+    fn undo_create(&mut self, name: &str) -> Result<(), &'static str> {
+        let root_cluster = self.boot_sector.root_cluster;
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        let offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        let chain = self.file_chain(file.cluster)?;
+        self.release_clusters(&chain)?;
+        let is_last = self
+            .dir_slot_offsets(root_cluster)
+            .skip_while(|&slot| slot != offset)
+            .nth(1)
+            .is_none_or(|next| self.storage[next] == DIR_ENTRY_END);
+        for slot in self.lfn_slots_before(root_cluster, offset).into_iter().chain([offset]) {
+            if is_last {
+                self.storage[slot..slot + 32].fill(0);
+            } else {
+                self.storage[slot] = DIR_ENTRY_DELETED;
+            }
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    // Crée un fichier en imposant son premier cluster ; la suite de la chaîne est contiguë (first_cluster,
+    // first_cluster + 1, ...) et tous ces clusters doivent être libres
+    // Utile pour reproduire exactement un placement sur le disque (tests, démos)
+    pub fn create_file_at(&mut self, name: &str, data: &[u8], first_cluster: u32) -> Result<(), &'static str> {
+        self.check_writable("createat", || format!("{} {} ({} octets)", first_cluster, name, data.len()))?;
+        let short = normalize_83(name)?;
+
+        if self.find_entry_offset(name).is_some() {
+            return Err("Fichier deja existant");
+        }
+        if data.len() > u32::MAX as usize {
+            return Err("Fichier trop gros pour FAT32 (max 4 Go)");
+        }
+
+        // Les clusters 0, 1 et 2 (racine) ne sont jamais disponibles pour un fichier
+        if first_cluster < 3 || first_cluster >= self.total_clusters + 2 {
+            return Err("Cluster invalide");
+        }
+
+        let cluster_size = self.cluster_size();
+        let count = data.len().div_ceil(cluster_size).max(1) as u32;
+        if count > self.total_clusters + 2 - first_cluster {
+            return Err("Pas assez de clusters apres le cluster demande");
+        }
+        let chain: Vec<u32> = (first_cluster..first_cluster + count).collect();
+        for &cluster in &chain {
+            if self.read_fat_entry(cluster)? != CLUSTER_FREE {
+                return Err("Cluster deja utilise");
+            }
+        }
+
+        // La chaîne est prise avant d'agrandir la racine, qui pourrait sinon utiliser ces clusters
+        for (index, &cluster) in chain.iter().enumerate() {
+            self.write_fat_entry(cluster, chain.get(index + 1).copied().unwrap_or(CLUSTER_END))?;
+        }
+        let root_cluster = self.boot_sector.root_cluster;
+        if let Err(e) = self.ensure_directory_capacity(root_cluster, 1) {
+            self.release_clusters(&chain)?;
+            return Err(e);
+        }
+
+        // Écrire les données du fichier, un morceau par cluster
+        for (index, &cluster) in chain.iter().enumerate() {
+            let start = (index * cluster_size).min(data.len());
+            let end = (start + cluster_size).min(data.len());
+            self.write_cluster(cluster, &data[start..end])?;
+        }
+        self.add_dir_entry(root_cluster, DirectoryEntry::new_file(short, first_cluster, data.len() as u32).stamped(self.now()))?;
+        self.record(Operation::CreateAt { name: name.to_string(), data: data.to_vec(), cluster: first_cluster });
+
+        Ok(())
+    }
+
+    // Écrit un fichier en remplaçant son contenu s'il existe déjà (sinon le crée)
+    // La chaîne existante est réutilisée : on ajoute ou libère seulement les clusters de fin,
+    // ce qui évite la fragmentation d'un delete + create. Renvoie le premier cluster
+    pub fn write_file(&mut self, name: &str, data: &[u8]) -> Result<u32, &'static str> {
+        self.check_writable("write", || format!("{} ({} octets)", name, data.len()))?;
+        let file = match self.find_file(name) {
+            Some(file) => file,
+            None => return self.create_file_named(name, data),
+        };
+        if file.sparse {
+            return Err(SPARSE_UNSUPPORTED);
+        }
+        let entry_offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        if data.len() > u32::MAX as usize {
+            return Err("Fichier trop gros pour FAT32 (max 4 Go)");
+        }
+
+        // Ajuster la longueur de la chaîne : d'abord agrandir (peut échouer sans rien casser)
+        let cluster_size = self.cluster_size();
+        let needed = data.len().div_ceil(cluster_size).max(1);
+        let mut chain = self.file_chain_or_empty(file.cluster)?;
+        self.grow_chain(entry_offset, &mut chain, needed)?;
+
+        for (index, &cluster) in chain.iter().take(needed).enumerate() {
+            let start = (index * cluster_size).min(data.len());
+            let end = (start + cluster_size).min(data.len());
+            self.write_cluster(cluster, &data[start..end])?;
+        }
+
+        // Puis libérer ce qui dépasse si le nouveau contenu est plus court
+        self.shrink_chain(&chain, needed)?;
+        self.set_entry_size(entry_offset, data.len() as u32);
+        self.record(Operation::Write { name: name.to_string(), data: data.to_vec() });
+        Ok(file.cluster)
+    }
+
+    // Ajoute des données à la fin d'un fichier existant, sans réécrire ce qui est déjà là
+    // Le dernier cluster est complété, puis de nouveaux clusters sont chaînés pour le surplus
+    // Si la taille est un multiple exact de la taille de cluster, on commence directement dans un cluster neuf
+    pub fn append_to_file(&mut self, name: &str, data: &[u8]) -> Result<(), &'static str> {
+        self.check_writable("append", || format!("{} ({} octets)", name, data.len()))?;
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        if file.sparse {
+            return Err(SPARSE_UNSUPPORTED);
+        }
+        let entry_offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        let new_size = file.size.checked_add(data.len()).filter(|&size| size <= u32::MAX as usize)
+            .ok_or("Fichier trop gros pour FAT32 (max 4 Go)")?;
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        // Allouer d'abord tous les clusters manquants : en cas d'échec rien n'a été écrit
+        let cluster_size = self.cluster_size();
+        let mut chain = self.file_chain_or_empty(file.cluster)?;
+        self.grow_chain(entry_offset, &mut chain, new_size.div_ceil(cluster_size))?;
+
+        // Écrire à partir de l'ancienne fin du fichier, cluster par cluster
+        let mut position = file.size;
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            let (index, offset_in_cluster) = (position / cluster_size, position % cluster_size);
+            let count = remaining.len().min(cluster_size - offset_in_cluster);
+            self.write_in_cluster(chain[index], offset_in_cluster, &remaining[..count])?;
+            position += count;
+            remaining = &remaining[count..];
+        }
+
+        self.set_entry_size(entry_offset, new_size as u32);
+        self.record(Operation::Append { name: name.to_string(), data: data.to_vec() });
+        Ok(())
+    }
+
+    // Écrit `data` à partir de `offset` dans un fichier existant, sans changer sa taille
+    // Sert à remplir un fichier réservé par preallocate : la zone doit rester dans la taille actuelle
+    pub fn write_file_range(&mut self, name: &str, offset: usize, data: &[u8]) -> Result<(), &'static str> {
+        self.check_writable("writerange", || format!("{} {} ({} octets)", name, offset, data.len()))?;
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        let entry_offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        if offset.checked_add(data.len()).is_none_or(|end| end > file.size) {
+            return Err("Ecriture au-dela de la fin du fichier (utiliser append)");
+        }
+
+        // Fichier creux : les clusters de la zone sont alloués à la demande, `chain` suit alors l'ordre logique
+        let cluster_size = self.cluster_size();
+        let chain = match file.sparse {
+            true => self.sparse_clusters_for(entry_offset, &file, offset, data.len())?,
+            false => self.file_chain_or_empty(file.cluster)?,
+        };
+        let mut position = offset;
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            let (index, offset_in_cluster) = (position / cluster_size, position % cluster_size);
+            let count = remaining.len().min(cluster_size - offset_in_cluster);
+            let cluster = *chain.get(index).ok_or("Chaine de clusters invalide")?;
+            self.write_in_cluster(cluster, offset_in_cluster, &remaining[..count])?;
+            position += count;
+            remaining = &remaining[count..];
+        }
+
+        self.set_entry_size(entry_offset, file.size as u32);
+        self.record(Operation::WriteRange { name: name.to_string(), offset, data: data.to_vec() });
+        Ok(())
+    }
+
+    // Fichiers creux : extension NON STANDARD, propre à ce projet (pour la démo)
+    // Un fichier creux annonce toute sa taille mais n'alloue que les clusters écrits par write_file_range ;
+    // les trous se lisent comme des zéros. Sa chaîne commence par des clusters d'index (un u32 par cluster
+    // logique : numéro du cluster de données, 0 pour un trou), suivis des clusters de données dans l'ordre
+    // où ils ont été écrits. L'entrée porte ENTRY_FLAG_SPARSE dans l'octet 12 (réservé à Windows NT) et son
+    // premier cluster reste à 0 jusqu'à la première écriture
+    // Un autre système FAT32 ignore l'octet 12 et lirait l'index comme des données : à ne pas sortir des démos
+    // Seules la lecture, write_file_range, le renommage et la suppression les acceptent ; defrag les laisse en place
+    pub fn create_sparse(&mut self, name: &str, logical_size: usize) -> Result<(), &'static str> {
+        self.check_writable("sparse", || format!("{} {}", name, logical_size))?;
+        if logical_size > u32::MAX as usize {
+            return Err("Fichier trop gros pour FAT32 (max 4 Go)");
+        }
+        let (short, long_name) = self.new_file_names(name)?;
+        let root_cluster = self.boot_sector.root_cluster;
+        let lfn_count = long_name.map_or(0, |name| name.encode_utf16().count().div_ceil(LFN_CHARS_PER_ENTRY));
+        self.ensure_directory_capacity(root_cluster, lfn_count + 1)?;
+
+        let mut entry = DirectoryEntry::new_file(short, 0, logical_size as u32).stamped(self.now());
+        entry.reserved |= ENTRY_FLAG_SPARSE;
+        self.add_named_entry(root_cluster, entry, long_name)?;
+        self.record(Operation::CreateSparse { name: name.to_string(), size: logical_size });
+        Ok(())
+    }
+
+    // Nombre de clusters d'index d'un fichier creux de `size` octets (4 octets par cluster logique)
+    fn sparse_index_clusters(&self, size: usize) -> usize {
+        let cluster_size = self.cluster_size();
+        (size.div_ceil(cluster_size) * 4).div_ceil(cluster_size).max(1)
+    }
+
+    // Position dans l'index (cluster d'index, offset dans ce cluster) du cluster logique `index`
+    fn sparse_slot(&self, index_chain: &[u32], index: usize) -> Result<(u32, usize), &'static str> {
+        let position = index * 4;
+        let cluster = *index_chain.get(position / self.cluster_size()).ok_or("Index de fichier creux invalide")?;
+        Ok((cluster, position % self.cluster_size()))
+    }
+
+    // Cluster de données du cluster logique `index` d'un fichier creux, 0 pour un trou
+    fn sparse_lookup(&self, index_chain: &[u32], index: usize) -> Result<u32, &'static str> {
+        let (cluster, offset) = self.sparse_slot(index_chain, index)?;
+        let bytes = &self.read_cluster(cluster)?[offset..offset + 4];
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // Contenu d'un fichier creux : les trous (et tout le fichier avant la première écriture) valent zéro
+    fn read_sparse(&self, file: &FileInfo) -> Result<Vec<u8>, &'static str> {
+        self.read_range(file, 0, file.size)
+    }
+
+    // Clusters de données d'un fichier creux, par cluster logique, jusqu'à la fin de la zone [offset, offset + len)
+    // Les trous de cette zone reçoivent un cluster neuf (à zéro) ajouté en fin de chaîne ; à la première
+    // écriture l'index est alloué d'abord. Tout ce qu'il faut est compté avant : un disque plein ne change rien
+    fn sparse_clusters_for(&mut self, entry_offset: usize, file: &FileInfo, offset: usize, len: usize) -> Result<Vec<u32>, &'static str> {
+        let cluster_size = self.cluster_size();
+        let touched = match len {
+            0 => 0..0,
+            _ => offset / cluster_size..(offset + len - 1) / cluster_size + 1,
+        };
+        let index_clusters = self.sparse_index_clusters(file.size);
+        let mut chain = self.file_chain_or_empty(file.cluster)?;
+
+        let mut clusters = vec![0u32; touched.end];
+        if !chain.is_empty() {
+            let index_chain = chain.get(..index_clusters).ok_or("Index de fichier creux invalide")?;
+            for (index, cluster) in clusters.iter_mut().enumerate() {
+                *cluster = self.sparse_lookup(index_chain, index)?;
+            }
+        }
+        let holes = clusters[touched.clone()].iter().filter(|&&cluster| cluster == 0).count();
+        let needed = holes + if chain.is_empty() { index_clusters } else { 0 };
+        if needed > self.count_free_clusters()? as usize {
+            return Err("Pas de cluster libre");
+        }
+
+        self.grow_chain(entry_offset, &mut chain, index_clusters)?;
+        for index in touched {
+            if clusters[index] != 0 {
+                continue;
+            }
+            let last = *chain.last().ok_or("Chaine de clusters invalide")?;
+            let cluster = self.extend_chain(last, 1)?[0];
+            chain.push(cluster);
+            clusters[index] = cluster;
+            let (index_cluster, slot) = self.sparse_slot(&chain[..index_clusters], index)?;
+            self.write_in_cluster(index_cluster, slot, &cluster.to_le_bytes())?;
+        }
+        Ok(clusters)
+    }
+
+    // Réécrit un fichier en ne gardant que certaines plages (offset, longueur), mises bout à bout
+    // Les données sont réécrites au début de la chaîne existante, les clusters devenus inutiles sont libérés
+    pub fn compact_file(&mut self, name: &str, keep_ranges: &[(usize, usize)]) -> Result<(), &'static str> {
+        self.check_writable("compact", || format!("{} {:?}", name, keep_ranges))?;
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        if file.sparse {
+            return Err(SPARSE_UNSUPPORTED);
+        }
+        let entry_offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        let data = self.read_file(file.cluster, file.size)?;
+
+        // Plages triées et disjointes : chaque octet est gardé au plus une fois
+        let mut kept = Vec::new();
+        let mut previous_end = 0;
+        for &(offset, len) in keep_ranges {
+            let end = offset.checked_add(len).filter(|&end| end <= data.len()).ok_or("Plage hors du fichier")?;
+            if offset < previous_end {
+                return Err("Plages non triees ou qui se chevauchent");
+            }
+            kept.extend_from_slice(&data[offset..end]);
+            previous_end = end;
+        }
+
+        // Plages disjointes : le résultat n'est jamais plus grand que l'original, la chaîne actuelle suffit
+        let cluster_size = self.cluster_size();
+        let chain = self.file_chain(file.cluster)?;
+        let keep_clusters = kept.len().div_ceil(cluster_size).max(1);
+        for (index, &cluster) in chain.iter().take(keep_clusters).enumerate() {
+            let start = (index * cluster_size).min(kept.len());
+            let end = (start + cluster_size).min(kept.len());
+            self.write_cluster(cluster, &kept[start..end])?;
+        }
+
+        self.shrink_chain(&chain, keep_clusters)?;
+        self.set_entry_size(entry_offset, kept.len() as u32);
+        self.record(Operation::Compact { name: name.to_string(), ranges: keep_ranges.to_vec() });
+        Ok(())
+    }
+
+    // Raccourcit un fichier à `new_size` octets (comme Unix truncate) et libère les clusters de fin
+    // Agrandir n'est pas permis ici : c'est le rôle de append_to_file
+    pub fn truncate_file(&mut self, name: &str, new_size: usize) -> Result<(), &'static str> {
+        self.check_writable("truncate", || format!("{} {}", name, new_size))?;
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        if file.sparse {
+            return Err(SPARSE_UNSUPPORTED);
+        }
+        let entry_offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        if new_size > file.size {
+            return Err("Nouvelle taille plus grande que le fichier (utiliser append)");
+        }
+
+        // Un fichier vide garde son premier cluster, comme à la création
+        let cluster_size = self.cluster_size();
+        let chain = self.file_chain_or_empty(file.cluster)?;
+        self.shrink_chain(&chain, new_size.div_ceil(cluster_size).max(1))?;
+        self.set_entry_size(entry_offset, new_size as u32);
+        self.record(Operation::Truncate { name: name.to_string(), size: new_size });
+        Ok(())
+    }
+
+    // Donne exactement `count` clusters à la chaîne d'un fichier (comme un fallocate en clusters)
+    // Agrandir ajoute des clusters à zéro et porte la taille à count * taille de cluster si elle était plus petite ;
+    // réduire libère la fin de la chaîne et ramène la taille à count * taille de cluster au plus
+    // 0 libère toute la chaîne : le fichier reste, vide et sans cluster (premier cluster à 0)
+    pub fn set_file_clusters(&mut self, name: &str, count: u32) -> Result<(), &'static str> {
+        self.check_writable("setclusters", || format!("{} {}", name, count))?;
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        if file.sparse {
+            return Err(SPARSE_UNSUPPORTED);
+        }
+        let entry_offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        let capacity = count as usize * self.cluster_size();
+        if capacity > u32::MAX as usize {
+            return Err("Fichier trop gros pour FAT32 (max 4 Go)");
+        }
+
+        let mut chain = self.file_chain_or_empty(file.cluster)?;
+        let grows = count as usize > chain.len();
+        if count == 0 {
+            self.release_clusters(&chain)?;
+            self.set_entry_cluster(entry_offset, 0);
+        } else if grows {
+            self.grow_chain(entry_offset, &mut chain, count as usize)?;
+        } else {
+            self.shrink_chain(&chain, count as usize)?;
+        }
+
+        let size = if grows { capacity.max(file.size) } else { capacity.min(file.size) };
+        self.set_entry_size(entry_offset, size as u32);
+        self.record(Operation::SetClusters { name: name.to_string(), count });
+        Ok(())
+    }
+
+    // Chaîne d'un fichier, vide pour un fichier sans cluster (premier cluster à 0, voir set_file_clusters)
+    fn file_chain_or_empty(&self, first_cluster: u32) -> Result<Vec<u32>, &'static str> {
+        match first_cluster {
+            0 => Ok(Vec::new()),
+            cluster => self.file_chain(cluster),
+        }
+    }
+
+    // Allonge la chaîne du fichier dont l'entrée est à `entry_offset` jusqu'à `count` clusters (remis à zéro)
+    // Un fichier sans cluster reçoit d'abord un premier cluster ; en cas de disque plein, rien ne change
+    fn grow_chain(&mut self, entry_offset: usize, chain: &mut Vec<u32>, count: usize) -> Result<(), &'static str> {
+        if count <= chain.len() {
+            return Ok(());
+        }
+        let first_added = chain.is_empty();
+        if first_added {
+            let first = self.allocate_cluster()?;
+            self.write_cluster(first, &[])?;
+            self.set_entry_cluster(entry_offset, first);
+            chain.push(first);
+        }
+        match self.extend_chain(chain[chain.len() - 1], count - chain.len()) {
+            Ok(added) => {
+                chain.extend(added);
+                Ok(())
+            },
+            Err(e) => {
+                if first_added {
+                    self.release_clusters(chain)?;
+                    self.set_entry_cluster(entry_offset, 0);
+                    chain.clear();
+                }
+                Err(e)
+            }
+        }
+    }
+
+    // Écrit le premier cluster d'une entrée de répertoire (poids fort octets 20-21, poids faible 26-27)
+    fn set_entry_cluster(&mut self, entry_offset: usize, cluster: u32) {
+        self.storage[entry_offset + 20..entry_offset + 22].copy_from_slice(&((cluster >> 16) as u16).to_le_bytes());
+        self.storage[entry_offset + 26..entry_offset + 28].copy_from_slice(&(cluster as u16).to_le_bytes());
+    }
+
+    // Ne garde que les `keep` premiers clusters d'une chaîne : le dernier gardé devient EOC, la suite est libérée
+    fn shrink_chain(&mut self, chain: &[u32], keep: usize) -> Result<(), &'static str> {
+        if keep == 0 || keep >= chain.len() {
+            return Ok(());
+        }
+        self.write_fat_entry(chain[keep - 1], CLUSTER_END)?;
+        self.release_clusters(&chain[keep..])
+    }
+
+    // Chaîne `count` nouveaux clusters (remis à zéro) après `last` et renvoie leurs numéros
+    // En cas de disque plein, les clusters déjà pris sont libérés et `last` redevient la fin de chaîne
+    fn extend_chain(&mut self, last: u32, count: usize) -> Result<Vec<u32>, &'static str> {
+        let mut added: Vec<u32> = Vec::with_capacity(count);
+        let mut previous = last;
+
+        for _ in 0..count {
+            match self.allocate_cluster() {
+                Ok(cluster) => {
+                    self.write_fat_entry(previous, cluster)?;
+                    self.write_cluster(cluster, &[])?;
+                    added.push(cluster);
+                    previous = cluster;
+                },
+                Err(e) => {
+                    self.release_clusters(&added)?;
+                    self.write_fat_entry(last, CLUSTER_END)?;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(added)
+    }
+
+    // Met à jour la taille stockée dans une entrée de répertoire (octets 28-31)
+    // Appelé après chaque modification du contenu : la date de modification (octets 22-25) suit
+    fn set_entry_size(&mut self, entry_offset: usize, size: u32) {
+        let (date, time) = self.now().encode();
+        self.storage[entry_offset + 22..entry_offset + 24].copy_from_slice(&time.to_le_bytes());
+        self.storage[entry_offset + 24..entry_offset + 26].copy_from_slice(&date.to_le_bytes());
+        self.storage[entry_offset + 28..entry_offset + 32].copy_from_slice(&size.to_le_bytes());
+    }
+
+    // Lit un fichier par son nom
+    // Un fichier vide sans chaîne (cluster 0) se lit comme vide ; un fichier non vide qui commence aux
+    // clusters réservés 0 ou 1 a une entrée corrompue
+    pub fn read_file_by_name(&self, filename: &str) -> Result<Vec<u8>, &'static str> {
+        let file_info = self.find_file(filename).ok_or("Fichier non trouve")?;
+        self.read_contents(&file_info)
+    }
+
+    // Lit au plus `len` octets d'un fichier à partir de `offset` (fichiers creux compris)
+    // Seuls les clusters qui recouvrent la plage sont lus : un grand fichier ne passe jamais en entier par le heap
+    pub fn read_file_range(&self, filename: &str, offset: usize, len: usize) -> Result<Vec<u8>, &'static str> {
+        let file_info = self.find_file(filename).ok_or("Fichier non trouve")?;
+        self.read_range(&file_info, offset, len)
+    }
+
+    fn read_range(&self, file: &FileInfo, offset: usize, len: usize) -> Result<Vec<u8>, &'static str> {
+        let mut data = Vec::new();
+        self.visit_range(file, offset, len, &mut |piece| {
+            data.extend_from_slice(piece);
+            Ok(())
+        })?;
+        Ok(data)
+    }
+
+    // Passe à `visit`, dans l'ordre, les morceaux de la plage [offset, offset + len) du fichier : des tranches
+    // de clusters lus directement dans le storage, et des zéros (par 512 octets) pour les trous d'un fichier creux
+    fn visit_range(&self, file: &FileInfo, offset: usize, len: usize, visit: &mut dyn FnMut(&[u8]) -> Result<(), &'static str>) -> Result<(), &'static str> {
+        const ZEROS: [u8; 512] = [0; 512];
+        let start = offset.min(file.size);
+        let end = start.saturating_add(len).min(file.size);
+        if start == end {
+            return Ok(());
+        }
+
+        let cluster_size = self.cluster_size();
+        let chain = self.read_chain(file)?;
+        for index in start / cluster_size..=(end - 1) / cluster_size {
+            let cluster_start = index * cluster_size;
+            let piece = start.max(cluster_start) - cluster_start..end.min(cluster_start + cluster_size) - cluster_start;
+            match self.cluster_piece(file, &chain, index)? {
+                Some(bytes) => visit(&bytes[piece])?,
+                None => (0..piece.len())
+                    .step_by(ZEROS.len())
+                    .try_for_each(|done| visit(&ZEROS[..(piece.len() - done).min(ZEROS.len())]))?,
+            }
+        }
+        Ok(())
+    }
+
+    // Chaîne d'un fichier pour une lecture par clusters (voir cluster_piece)
+    // Vide pour un fichier sans cluster : vide, ou creux jamais écrit
+    fn read_chain(&self, file: &FileInfo) -> Result<Vec<u32>, &'static str> {
+        match (file.cluster, file.size) {
+            (0, _) if file.sparse => Ok(Vec::new()),
+            (0, 0) => Ok(Vec::new()),
+            (0 | 1, _) => Err("Entree corrompue : le fichier commence a un cluster reserve (0 ou 1)"),
+            (cluster, _) => self.file_chain(cluster),
+        }
+    }
+
+    // Octets du cluster logique `index` d'un fichier (jusqu'à sa taille), lus avec la chaîne de read_chain
+    // None pour un trou de fichier creux (des zéros)
+    fn cluster_piece(&self, file: &FileInfo, chain: &[u32], index: usize) -> Result<Option<&[u8]>, &'static str> {
+        let cluster_size = self.cluster_size();
+        let cluster = match file.sparse {
+            true if chain.is_empty() => 0,
+            true => {
+                let index_chain = chain.get(..self.sparse_index_clusters(file.size)).ok_or("Index de fichier creux invalide")?;
+                self.sparse_lookup(index_chain, index)?
+            },
+            false => *chain.get(index).ok_or("Taille de fichier invalide")?,
+        };
+        let len = file.size.saturating_sub(index * cluster_size).min(cluster_size);
+        match cluster {
+            0 => Ok(None),
+            cluster => Ok(Some(&self.read_cluster(cluster)?[..len])),
+        }
+    }
+
+    // Contenu d'un fichier de la racine, fichiers creux compris
+    fn read_contents(&self, file: &FileInfo) -> Result<Vec<u8>, &'static str> {
+        match (file.cluster, file.size) {
+            _ if file.sparse => self.read_sparse(file),
+            (0, 0) => Ok(Vec::new()),
+            (0 | 1, _) => Err("Entree corrompue : le fichier commence a un cluster reserve (0 ou 1)"),
+            (cluster, size) => self.read_file(cluster, size),
+        }
+    }
+
+    // Exporte le contenu d'un fichier vers un fichier de la machine hôte
+    // Pratique pour récupérer des fichiers binaires qu'on ne peut pas afficher dans le terminal
+    // Copie cluster par cluster : un grand fichier (creux par exemple) ne passe jamais en entier par le heap
+    pub fn export_host_file(&self, filename: &str, host_path: &str) -> Result<usize, &'static str> {
+        use std::io::Write;
+
+        let file_info = self.find_file(filename).ok_or("Fichier non trouve")?;
+        let mut host = std::fs::File::create(host_path).map_err(|_| "Ecriture du fichier hote impossible")?;
+        self.visit_range(&file_info, 0, file_info.size, &mut |piece| {
+            host.write_all(piece).map_err(|_| "Ecriture du fichier hote impossible")
+        })?;
+        Ok(file_info.size)
+    }
+
+    // Écrit tout le disque (boot sector, FATs, données) dans un fichier image de l'hôte
+    // L'image peut ensuite être inspectée avec les outils habituels (mount -o loop, mdir, hexdump...)
+    pub fn export_image<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, &*self.storage)
+    }
+
+    // Exporte un fichier avec ses métadonnées dans un "bundle" de l'hôte, relu par import_bundle
+    // Format (little-endian) : BUNDLE_MAGIC, longueur du nom (u16) et nom UTF-8, taille (u32), attributs (u8),
+    // les 11 octets d'horodatage de l'entrée (octets 13-19 et 22-25 : création, dernier accès, modification), données
+    pub fn export_bundle(&self, name: &str, host_path: &str) -> Result<usize, &'static str> {
+        let bundle = self.bundle_bytes(name)?;
+        std::fs::write(host_path, &bundle).map_err(|_| "Ecriture du fichier hote impossible")?;
+        Ok(bundle.len())
+    }
+
+    fn bundle_bytes(&self, name: &str) -> Result<Vec<u8>, &'static str> {
+        let file = self.find_file(name).ok_or("Fichier non trouve")?;
+        let offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        let data = self.read_file_by_name(name)?;
+        let name_len = u16::try_from(file.name.len()).map_err(|_| "Nom trop long")?;
+
+        let mut bundle = Vec::with_capacity(BUNDLE_MAGIC.len() + 2 + file.name.len() + 16 + data.len());
+        bundle.extend_from_slice(BUNDLE_MAGIC);
+        bundle.extend_from_slice(&name_len.to_le_bytes());
+        bundle.extend_from_slice(file.name.as_bytes());
+        bundle.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bundle.push(file.attributes);
+        bundle.extend_from_slice(&self.storage[offset + 13..offset + 20]);
+        bundle.extend_from_slice(&self.storage[offset + 22..offset + 26]);
+        bundle.extend_from_slice(&data);
+        Ok(bundle)
+    }
+
+    // Recrée à la racine le fichier d'un bundle écrit par export_bundle, avec ses attributs et ses dates
+    // Le nom ne doit pas être déjà pris ; renvoie le nom du fichier créé
+    pub fn import_bundle(&mut self, host_path: &str) -> Result<String, &'static str> {
+        self.check_writable("unbundle", || host_path.to_string())?;
+        let bundle = std::fs::read(host_path).map_err(|_| "Lecture du fichier hote impossible")?;
+        self.restore_bundle(&bundle)
+    }
+
+    fn restore_bundle(&mut self, bundle: &[u8]) -> Result<String, &'static str> {
+        const INVALID: &str = "Bundle invalide";
+        let rest = bundle.strip_prefix(BUNDLE_MAGIC.as_slice()).ok_or(INVALID)?;
+        let (name_len, rest) = rest.split_first_chunk::<2>().ok_or(INVALID)?;
+        let (name, rest) = rest.split_at_checked(u16::from_le_bytes(*name_len) as usize).ok_or(INVALID)?;
+        let name = core::str::from_utf8(name).map_err(|_| INVALID)?;
+        let (size, rest) = rest.split_first_chunk::<4>().ok_or(INVALID)?;
+        let (&attributes, rest) = rest.split_first().ok_or(INVALID)?;
+        let (times, data) = rest.split_first_chunk::<11>().ok_or(INVALID)?;
+        if data.len() != u32::from_le_bytes(*size) as usize {
+            return Err(INVALID);
+        }
+
+        let (short, long_name) = self.new_file_names(name)?;
+        self.write_new_file(short, long_name, data, data.len())?;
+        let offset = self.find_entry_offset(name).ok_or("Fichier non trouve")?;
+        self.storage[offset + 11] = attributes & FILE_ATTRIBUTES_EDITABLE;
+        self.storage[offset + 13..offset + 20].copy_from_slice(&times[..7]);
+        self.storage[offset + 22..offset + 26].copy_from_slice(&times[7..]);
+        self.record(Operation::ImportBundle { bundle: bundle.to_vec() });
+        Ok(name.to_string())
+    }
+
+    // Supprime un fichier : libère toute sa chaîne de clusters puis retire son entrée du répertoire
+    pub fn delete_file_by_name(&mut self, filename: &str) -> Result<(), &'static str> {
+        self.check_writable("delete", || filename.to_string())?;
+        let file_info = self.find_file(filename).ok_or("Fichier non trouve")?;
+        if file_info.attributes & FILE_ATTRIBUTE_READ_ONLY != 0 {
+            return Err("Fichier en lecture seule");
+        }
+
+        // Suivre la chaîne en lisant le suivant AVANT d'effacer l'entrée courante
+        let mut cluster = file_info.cluster;
+        let mut freed = 0;
+        while cluster >= 2 && cluster < self.total_clusters + 2 && freed < self.total_clusters {
+            let next = self.read_fat_entry(cluster)?;
+            self.write_fat_entry(cluster, CLUSTER_FREE)?;
+            freed += 1;
+            if next >= CLUSTER_END || next == CLUSTER_FREE {
+                break;
+            }
+            cluster = next;
+        }
+
+        self.mark_entry_deleted(filename)?;
+        self.record(Operation::Delete { name: filename.to_string() });
+        Ok(())
+    }
+
+    // Suppression sécurisée : écrase les données du fichier en plusieurs passes avant de libérer ses clusters
+    // Motifs successifs : 0x00, 0xFF, puis pseudo-aléatoire (et on recommence si passes > 3)
+    // Chaque passe écrit réellement dans le storage, un dump brut montre donc le motif de la dernière passe
+    pub fn secure_delete(&mut self, filename: &str, passes: u32) -> Result<(), &'static str> {
+        self.check_writable("shred", || format!("{} {}", filename, passes))?;
+        if passes == 0 {
+            return Err("Il faut au moins une passe");
+        }
+
+        let file_info = self.find_file(filename).ok_or("Fichier non trouve")?;
+        if file_info.attributes & FILE_ATTRIBUTE_READ_ONLY != 0 {
+            return Err("Fichier en lecture seule");
+        }
+        let chain = self.file_chain_or_empty(file_info.cluster)?;
+        let cluster_size = self.cluster_size();
+
+        for pass in 0..passes {
+            // xorshift32 : pas besoin d'un vrai RNG pour une démo, juste d'octets qui ne se répètent pas
+            let mut seed = self.boot_sector.volume_id ^ (pass + 1).wrapping_mul(0x9E3779B9);
+            for &cluster in &chain {
+                let offset = self.cluster_to_offset(cluster);
+                for byte in &mut self.storage[offset..offset + cluster_size] {
+                    *byte = match pass % 3 {
+                        0 => 0x00,
+                        1 => 0xFF,
+                        _ => {
+                            seed ^= seed << 13;
+                            seed ^= seed >> 17;
+                            seed ^= seed << 5;
+                            seed as u8
+                        }
+                    };
+                }
+            }
+        }
+
+        // Les données sont illisibles, on peut libérer les clusters et l'entrée
+        self.release_clusters(&chain)?;
+        self.mark_entry_deleted(filename)?;
+        // Journalisé comme shred et pas comme delete : les motifs sont déterministes, le replay réécrit les mêmes octets
+        self.record(Operation::Shred { name: filename.to_string(), passes });
+        Ok(())
+    }
+
+    // Trouve un cluster libre
+    // Part de l'indice next_free de FSInfo puis reboucle sur 3 (après root) : un indice périmé
+    // ne coûte qu'un scan plus long, jamais un mauvais résultat
+    pub fn find_free_cluster(&self) -> Result<u32, &'static str> {
+        let end = self.total_clusters + 2;
+        let start = match self.fs_info() {
+            Ok(fs_info) if fs_info.next_free >= 3 && fs_info.next_free < end => fs_info.next_free,
+            _ => 3,
+        };
+
+        for cluster in (start..end).chain(3..start) {
+            if self.read_fat_entry(cluster)? == CLUSTER_FREE {
+                return Ok(cluster);
+            }
+        }
+        Err("Pas de cluster libre")
+    }
+
+    // Alloue un nouveau cluster
+    pub fn allocate_cluster(&mut self) -> Result<u32, &'static str> {
+        self.check_writable("alloc", String::new)?;
+        let cluster = self.find_free_cluster()?;
+        self.write_fat_entry(cluster, CLUSTER_END)?;
+        // La prochaine recherche reprendra juste après
+        self.set_next_free_hint(cluster + 1);
+        Ok(cluster)
+    }
+
+    // Alloue `count` clusters libres consécutifs, déjà chaînés entre eux (le dernier est EOC), et renvoie le premier
+    // Contrairement à une boucle sur allocate_cluster, le résultat n'est jamais fragmenté : s'il n'existe pas
+    // de suite libre assez longue, c'est une erreur même si l'espace libre total suffirait
+    pub fn allocate_contiguous(&mut self, count: u32) -> Result<u32, &'static str> {
+        self.check_writable("reserve", || format!("{} --contiguous", count))?;
+        if count == 0 {
+            return Err("Il faut allouer au moins un cluster");
+        }
+
+        // Première suite assez longue en partant du début de la zone de données
+        let mut run_start = 3;
+        let mut run_len = 0;
+        let mut free_total = 0;
+        for cluster in 3..self.total_clusters + 2 {
+            if self.read_fat_entry(cluster)? != CLUSTER_FREE {
+                run_len = 0;
+                continue;
+            }
+            if run_len == 0 {
+                run_start = cluster;
+            }
+            run_len += 1;
+            free_total += 1;
+            if run_len == count {
+                break;
+            }
+        }
+        if run_len < count {
+            return Err(if free_total < count {
+                "Espace disque insuffisant"
+            } else {
+                "Aucune suite de clusters libres contigus assez longue (espace libre trop fragmente)"
+            });
+        }
+
+        let last = run_start + count - 1;
+        for cluster in run_start..last {
+            self.write_fat_entry(cluster, cluster + 1)?;
+        }
+        self.write_fat_entry(last, CLUSTER_END)?;
+        self.set_next_free_hint(last + 1);
+        self.record(Operation::Reserve { count, contiguous: true });
+        Ok(run_start)
+    }
+
+    // Réserve `count` clusters libres (à partir de l'indice next_free) sans les chaîner entre eux
+    // Chacun est marqué EOC, c'est à l'appelant de faire les liens s'il le souhaite
+    // En cas d'échec, les clusters déjà marqués sont libérés : la FAT revient à son état initial
+    pub fn reserve_clusters(&mut self, count: u32) -> Result<Vec<u32>, &'static str> {
+        self.check_writable("reserve", || count.to_string())?;
+        let reserved = self.reserve_free_clusters(count)?;
+        self.record(Operation::Reserve { count, contiguous: false });
+        Ok(reserved)
+    }
+
+    // reserve_clusters sans journalisation, pour les opérations qui s'en servent en interne
+    fn reserve_free_clusters(&mut self, count: u32) -> Result<Vec<u32>, &'static str> {
+        let mut reserved = Vec::new();
+
+        for _ in 0..count {
+            match self.allocate_cluster() {
+                Ok(cluster) => reserved.push(cluster),
+                Err(e) => {
+                    self.release_clusters(&reserved)?;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(reserved)
+    }
+
+    // Remet à libre une liste de clusters (annulation d'une allocation partielle)
+    fn release_clusters(&mut self, clusters: &[u32]) -> Result<(), &'static str> {
+        for &cluster in clusters {
+            self.write_fat_entry(cluster, CLUSTER_FREE)?;
+        }
+        Ok(())
+    }
+
+    // Taille d'un cluster en octets, lue dans le boot sector (secteurs par cluster * 512)
+    pub fn cluster_size(&self) -> usize {
+        self.boot_sector.sectors_per_cluster as usize * 512
+    }
+
+    // Convertit un numéro de cluster en offset dans le storage
+    fn cluster_to_offset(&self, cluster: u32) -> usize {
+        let cluster_offset = cluster - 2;  // Les clusters de données commencent à 2
+        (self.data_start_sector * 512) as usize + 
+        cluster_offset as usize * self.cluster_size()
+    }
+
+    // Offset en octets d'un cluster de données dans le storage, avec vérification des bornes
+    // Version publique de cluster_to_offset, pour rendre le calcul visible (commande geometry)
+    pub fn cluster_offset(&self, cluster: u32) -> Result<usize, &'static str> {
+        if cluster < 2 || cluster >= self.total_clusters + 2 {
+            return Err("Cluster invalide");
+        }
+        Ok(self.cluster_to_offset(cluster))
+    }
+
+    // Écrit des données dans un cluster (le reste du cluster est remis à zéro)
+    pub fn write_cluster(&mut self, cluster: u32, data: &[u8]) -> Result<(), &'static str> {
+        self.check_writable("writecluster", || format!("{} ({} octets)", cluster, data.len()))?;
+        let cluster_size = self.cluster_size();
+
+        let write_size = data.len().min(cluster_size);
+        self.write_in_cluster(cluster, 0, &data[..write_size])?;
+        
+        // Remplir le reste avec des zéros
+        let offset = self.cluster_to_offset(cluster);
+        self.storage[offset + write_size..offset + cluster_size].fill(0);
+
+        Ok(())
+    }
+
+    // Écrit des données à partir de `offset_in_cluster` sans toucher au reste du cluster
+    // Contrairement à write_cluster, les octets avant et après la zone écrite sont conservés
+    pub fn write_cluster_at(&mut self, cluster: u32, offset_in_cluster: usize, data: &[u8]) -> Result<(), &'static str> {
+        self.check_writable("writeat", || format!("{} {} ({} octets)", cluster, offset_in_cluster, data.len()))?;
+        self.write_in_cluster(cluster, offset_in_cluster, data)?;
+        self.record(Operation::WriteAt { cluster, offset: offset_in_cluster, data: data.to_vec() });
+        Ok(())
+    }
+
+    // write_cluster_at sans journalisation, pour les opérations qui s'en servent en interne
+    fn write_in_cluster(&mut self, cluster: u32, offset_in_cluster: usize, data: &[u8]) -> Result<(), &'static str> {
+        if cluster < 2 || cluster >= self.total_clusters + 2 {
+            return Err("Cluster invalide");
+        }
+
+        let offset = self.cluster_to_offset(cluster);
+        let cluster_size = self.cluster_size();
+        
+        if offset + cluster_size > self.storage.len() {
+            return Err("Cluster dépasse le storage");
+        }
+
+        // L'écriture doit rester entièrement dans ce cluster
+        if offset_in_cluster + data.len() > cluster_size {
+            return Err("Ecriture hors du cluster");
+        }
+
+        let start = offset + offset_in_cluster;
+        self.storage[start..start + data.len()].copy_from_slice(data);
+
+        Ok(())
+    }
+
+    // Lit les données d'un cluster
+    pub fn read_cluster(&self, cluster: u32) -> Result<&[u8], &'static str> {
+        if cluster < 2 || cluster >= self.total_clusters + 2 {
+            return Err("Cluster invalide");
+        }
+
+        let offset = self.cluster_to_offset(cluster);
+        let cluster_size = self.cluster_size();
+        
+        if offset + cluster_size > self.storage.len() {
+            return Err("Cluster dépasse le storage");
+        }
+
+        Ok(&self.storage[offset..offset + cluster_size])
+    }
+
+    // Lit un fichier par son premier cluster et sa taille, en suivant la chaîne dans la FAT
+    // Une chaîne cassée (cluster libre ou hors limites au milieu, boucle) est une erreur
+    // Un premier cluster à 0 (fichier sans chaîne) ne convient qu'à une taille nulle
+    pub fn read_file(&self, cluster: u32, file_size: usize) -> Result<Vec<u8>, &'static str> {
+        let chain = self.file_chain_or_empty(cluster)?;
+        let cluster_size = self.cluster_size();
+        
+        // La chaîne doit contenir au moins toute la taille annoncée
+        if file_size > chain.len() * cluster_size {
+            return Err("Taille de fichier invalide");
+        }
+        
+        let mut data = Vec::with_capacity(file_size);
+        for cluster in chain {
+            if data.len() >= file_size {
+                break;
+            }
+            let cluster_data = self.read_cluster(cluster)?;
+            let wanted = (file_size - data.len()).min(cluster_data.len());
+            data.extend_from_slice(&cluster_data[..wanted]);
+        }
+        
+        Ok(data)
+    }
+
+    // Contrôle rapide de santé : chaque fichier est lu deux fois, cluster par cluster, et les deux lectures
+    // doivent être identiques (et couvrir la taille annoncée). Le CRC noté à la dernière écriture par cet outil
+    // doit aussi correspondre (voir update_file_crcs). Renvoie la liste des fichiers en échec
+    pub fn validate_all(&self) -> Result<(), Vec<String>> {
+        let failures: Vec<String> = self
+            .files_iter()
+            .filter(|file| !self.file_is_valid(file).unwrap_or(false))
+            .map(|file| file.name)
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+
+    // Une validation de validate_all : deux lectures indépendantes (chaîne comprise) comparées cluster par cluster
+    fn file_is_valid(&self, file: &FileInfo) -> Result<bool, &'static str> {
+        let (first, second) = (self.read_chain(file)?, self.read_chain(file)?);
+        let mut crc = !0;
+        for index in 0..file.size.div_ceil(self.cluster_size()) {
+            let piece = self.cluster_piece(file, &first, index)?;
+            if piece != self.cluster_piece(file, &second, index)? {
+                return Ok(false);
+            }
+            // Un fichier suivi n'est jamais creux : tous ses clusters ont des octets
+            if let Some(bytes) = piece {
+                crc = crc32_update(crc, bytes);
+            }
+        }
+        let stored = self.file_crcs.iter().find(|(name, _)| *name == file.name).map(|&(_, crc)| crc);
+        Ok(stored.is_none_or(|stored| stored == !crc))
+    }
+
+    // Vérifie l'intégrité du système de fichiers
+    pub fn check_filesystem(&self) -> Result<(), &'static str> {
+        println!("\n--- Verification du systeme de fichiers ---");
+        
+        // Vérifier la signature du boot sector
+        let signature = self.boot_sector.signature;
+        if signature != 0xAA55 {
+            return Err("Signature du boot sector invalide");
+        }
+        println!("{ok} Signature du boot sector valide (0x{:04X})", signature, ok = glyph(Glyph::Ok));
+        
+        // Zone réservée : boot sector, FSInfo et backup doivent porter leurs signatures sur le disque
+        self.check_reserved_region()?;
+        let (fs_info, backup) = (self.boot_sector.fs_info, self.boot_sector.backup_boot_sector);
+        println!("{ok} Zone reservee intacte (boot: 0, FSInfo: {}, backup: {})", fs_info, backup, ok = glyph(Glyph::Ok));
+        
+        // Vérifier que les clusters système sont bien marqués
+        // FAT[0] reprend le media descriptor, FAT[1] et la racine portent n'importe quelle valeur de fin
+        // de chaîne (0x0FFFFFF8 à 0x0FFFFFFF) : mkfs.fat écrit 0x0FFFFFFF là où new() écrit 0x0FFFFFF8
+        let media = self.boot_sector.media;
+        let root_cluster = self.boot_sector.root_cluster;
+        let fat0 = self.read_fat_entry(0)?;
+        let fat1 = self.read_fat_entry(1)?;
+        let root = self.read_fat_entry(root_cluster)?;
+        
+        // La racine peut avoir été agrandie : son entrée pointe alors vers le cluster suivant
+        let root_ok = root >= CLUSTER_END || (2..self.total_clusters + 2).contains(&root);
+        if fat0 == 0x0FFFFF00 | media as u32 && fat1 >= CLUSTER_END && root_ok {
+            println!("{ok} Clusters systeme correctement marques", ok = glyph(Glyph::Ok));
+        } else {
+            return Err("Clusters systeme incorrects");
+        }
+        
+        // La FAT miroir doit être identique à la principale
+        if let Some((cluster, primary, mirror)) = self.fat_divergence() {
+            println!("{err} FAT[{}] : 0x{:08X} (principale) != 0x{:08X} (miroir)", cluster, primary, mirror, err = glyph(Glyph::Err));
+        }
+        self.verify_fats_consistent()?;
+        println!("{ok} Les {} copies de la FAT sont identiques", self.boot_sector.num_fats, ok = glyph(Glyph::Ok));
+        
+        // Le compteur en cache de FSInfo doit correspondre à un comptage complet
+        // 0xFFFFFFFF ("inconnu") est valide selon la spec : il n'y a alors rien à comparer
+        let cached = self.fs_info()?.free_count;
+        let scanned = self.count_free_clusters()?;
+        if cached == FSINFO_UNKNOWN {
+            println!("{info}  FSInfo ne donne pas de compteur ({} clusters libres d'apres la FAT)", scanned, info = glyph(Glyph::Info));
+        } else if cached != scanned {
+            println!("{err} FSInfo annonce {} clusters libres, la FAT en compte {}", cached, scanned, err = glyph(Glyph::Err));
+            return Err("Compteur FSInfo incoherent");
+        } else {
+            println!("{ok} FSInfo a jour ({} clusters libres)", cached, ok = glyph(Glyph::Ok));
+        }
+        
+        // Un fichier non vide ne peut pas commencer aux clusters réservés 0 et 1 (pas de données à cet endroit)
+        // (un fichier creux jamais écrit n'a pas encore de chaîne)
+        if let Some(file) = self.files_iter().find(|file| file.size > 0 && file.cluster < 2 && !(file.sparse && file.cluster == 0)) {
+            println!("{err} '{}' ({} octets) commence au cluster reserve {}", file.name, file.size, file.cluster, err = glyph(Glyph::Err));
+            return Err("Fichier pointant vers un cluster reserve (0 ou 1)");
+        }
+        println!("{ok} Aucun fichier ne pointe vers les clusters reserves", ok = glyph(Glyph::Ok));
+        
+        // Chaque cluster doit appartenir à une seule chaîne (racine, répertoire ou fichier)
+        if let Some(cluster) = self.first_shared_cluster() {
+            println!("{err} Cluster {} reference par plusieurs chaines (cross-link)", cluster, err = glyph(Glyph::Err));
+            return Err("Cluster partage entre plusieurs chaines");
+        }
+        println!("{ok} Aucun cluster partage entre deux chaines", ok = glyph(Glyph::Ok));
+        
+        // Compter les clusters de données utilisés (sans les clusters système 0, 1 et 2)
+        let used_clusters = self.data_clusters_used()?;
+        println!("{ok} Clusters de donnees utilises: {} / {}", used_clusters, self.total_clusters - 1, ok = glyph(Glyph::Ok));
+        
+        Ok(())
+    }
+
+    // Nombre de clusters de données réellement alloués à des fichiers
+    // Ne compte ni les clusters réservés (0, 1), ni la racine (2), ni les clusters marqués défectueux
+    pub fn data_clusters_used(&self) -> Result<u32, &'static str> {
+        let mut used = 0;
+        for cluster in 3..self.total_clusters + 2 {
+            let entry = self.read_fat_entry(cluster)?;
+            if entry != CLUSTER_FREE && entry != CLUSTER_BAD {
+                used += 1;
+            }
+        }
+        Ok(used)
+    }
+
+    // Met le disque dans un état bien fragmenté, pour montrer ce que fait defragment
+    // `count` fichiers FRAG0.BIN, FRAG1.BIN... de FRAGMENT_CLUSTERS_PER_FILE clusters reçoivent à tour de rôle
+    // (A B C A B C...) des clusters libres régulièrement espacés sur tout le disque, puis un fichier sur deux est
+    // supprimé : les restants sont fragmentés et le libre haché d'un bout à l'autre. Les fichiers restent petits,
+    // le volume reste utilisable avec le heap de 64 Ko
+    // Renvoie la fragmentation de l'espace libre obtenue. Une seule étape dans le journal : les créations
+    // et suppressions internes n'y figurent pas
+    pub fn create_fragmented_state(&mut self, count: usize) -> Result<f32, &'static str> {
+        self.check_writable("fragment", || count.to_string())?;
+        if count < 2 {
+            return Err("Il faut au moins 2 fichiers");
+        }
+        let names: Vec<String> = (0..count).map(|index| format!("FRAG{}.BIN", index)).collect();
+        let mut shorts = Vec::with_capacity(count);
+        for name in &names {
+            if self.find_entry_offset(name).is_some() {
+                return Err("Fichier deja existant");
+            }
+            shorts.push(normalize_83(name)?);
+        }
+
+        // Place dans la racine d'abord : elle peut prendre des clusters
+        let root_cluster = self.boot_sector.root_cluster;
+        self.ensure_directory_capacity(root_cluster, count)?;
+        let cluster_size = self.cluster_size();
+        let free_clusters = self.count_free_clusters()? as usize;
+        let rounds = FRAGMENT_CLUSTERS_PER_FILE.min(free_clusters / count);
+        if rounds == 0 {
+            return Err("Pas assez de clusters libres");
+        }
+
+        // Clusters libres distribués dans l'ordre, chacun au fichier suivant : (premier, dernier) de chaque chaîne
+        let mut chains = vec![(0u32, 0u32); count];
+        let mut fill = vec![0u8; cluster_size];
+        let mut assigned = 0;
+        let mut free_position = 0;
+        for cluster in 3..self.total_clusters + 2 {
+            if assigned == rounds * count {
+                break;
+            }
+            if self.read_fat_entry(cluster)? != CLUSTER_FREE {
+                continue;
+            }
+            // Le n-ième cluster distribué est le (n * libres / distribués)-ième cluster libre
+            free_position += 1;
+            if free_position - 1 != assigned * free_clusters / (rounds * count) {
+                continue;
+            }
+            let index = assigned % count;
+            self.write_fat_entry(cluster, CLUSTER_END)?;
+            match chains[index] {
+                (0, _) => chains[index].0 = cluster,
+                (_, last) => self.write_fat_entry(last, cluster)?,
+            }
+            chains[index].1 = cluster;
+            fill.fill(b'A' + (index % 26) as u8);
+            self.write_cluster(cluster, &fill)?;
+            assigned += 1;
+        }
+
+        let size = (rounds * cluster_size).min(u32::MAX as usize) as u32;
+        for (&short, &(first, _)) in shorts.iter().zip(&chains) {
+            let entry = DirectoryEntry::new_file(short, first, size).stamped(self.now());
+            self.add_dir_entry(root_cluster, entry)?;
+        }
+        for (name, &(first, _)) in names.iter().zip(&chains).skip(1).step_by(2) {
+            let chain = self.file_chain(first)?;
+            self.release_clusters(&chain)?;
+            self.mark_entry_deleted(name)?;
+        }
+
+        self.record(Operation::Fragment { files: count });
+        self.free_space_fragmentation()
+    }
+
+    // Fragmentation de l'espace libre : 0.0 = tout le libre est d'un seul bloc, proche de 1.0 = libre éparpillé
+    // Calcul : 1 - (plus longue suite de clusters libres / nombre total de clusters libres)
+    pub fn free_space_fragmentation(&self) -> Result<f32, &'static str> {
+        let mut free_clusters = 0u32;
+        let mut largest_run = 0u32;
+        let mut current_run = 0u32;
+
+        for cluster in 3..self.total_clusters + 2 {
+            if self.read_fat_entry(cluster)? == CLUSTER_FREE {
+                free_clusters += 1;
+                current_run += 1;
+                largest_run = largest_run.max(current_run);
+            } else {
+                current_run = 0;
+            }
+        }
+
+        if free_clusters == 0 {
+            return Ok(0.0);
+        }
+        Ok(1.0 - largest_run as f32 / free_clusters as f32)
+    }
+
+    // Plan de défragmentation : les fichiers de la racine, dans l'ordre du répertoire, sont rangés d'un seul
+    // tenant le plus bas possible (première suite assez longue). Les clusters qui n'appartiennent à aucun
+    // fichier de la racine (la racine elle-même, répertoires, clusters défectueux, orphelins) ne bougent
+    // pas : un fichier trop long pour le trou avant l'un d'eux est placé après, le trou reste pour les suivants
+    // Un fichier vide (cluster 0, sans chaîne) n'a rien à déplacer
+    fn defrag_plan(&self) -> Result<Vec<PlannedFile>, &'static str> {
+        let mut plan = Vec::new();
+        let end = self.total_clusters + 2;
+        // 1 = cluster qui ne bouge pas (tout ce qui est alloué, sauf les fichiers de la racine)
+        let mut fixed = self.cluster_map();
+        for cluster in 2..end {
+            fixed.set(cluster, (self.read_fat_entry(cluster)? != CLUSTER_FREE) as u8);
+        }
+        // Même parcours que files_iter, en gardant l'emplacement de chaque entrée
+        let files = self
+            .named_entries(self.boot_sector.root_cluster)
+            .filter(|(_, entry, _)| entry.attributes & FILE_ATTRIBUTE_DIRECTORY == 0)
+            .filter(|(_, entry, _)| entry.first_cluster() != 0 && !entry.is_sparse());
+        for (entry_offset, entry, _) in files {
+            let current = self.file_chain(entry.first_cluster())?;
+            for &cluster in &current {
+                fixed.set(cluster, 0);
+            }
+            plan.push(PlannedFile { entry_offset, current, target: Vec::new() });
+        }
+
+        // `fixed` sert ensuite de carte des clusters déjà attribués
+        for file in plan.iter_mut() {
+            let len = file.current.len() as u32;
+            let mut start = 2;
+            while start + len <= end {
+                match (start..start + len).find(|&cluster| fixed.get(cluster) == 1) {
+                    Some(taken) => start = taken + 1,
+                    None => break,
+                }
+            }
+            if start + len > end {
+                return Err("Pas assez d'espace contigu pour defragmenter");
+            }
+            file.target = (start..start + len).collect();
+            for cluster in start..start + len {
+                fixed.set(cluster, 1);
+            }
+        }
+        self.recycle_cluster_map(fixed);
+        Ok(plan)
+    }
+
+    // Défragmente la racine selon defrag_plan : chaque fichier devient contigu, le plus bas possible
+    // Renvoie le nombre de clusters déplacés (le même que cluster_moves dans defrag_preview)
+    // Les données sont déplacées par échanges de clusters en suivant les cycles de la permutation : une
+    // destination occupée par un autre fichier lui est d'abord rendue, rien n'est écrasé en cours de route
+    pub fn defragment(&mut self) -> Result<usize, &'static str> {
+        self.check_writable("defrag", String::new)?;
+        // Un cluster partagé aurait deux destinations
+        if self.first_shared_cluster().is_some() {
+            return Err("Clusters partages entre plusieurs chaines : lancer repair avant de defragmenter");
+        }
+        let plan = self.defrag_plan()?;
+
+        // Déplacements (source, destination) triés par source ; un cluster absent de la liste ne bouge pas
+        // Liste creuse plutôt qu'une table par cluster : seuls les clusters des fichiers qui bougent y figurent
+        let mut moves: Vec<(u32, u32)> = Vec::with_capacity(planned_moves(&plan));
+        for file in &plan {
+            moves.extend(file.current.iter().zip(&file.target).map(|(&from, &to)| (from, to)).filter(|(from, to)| from != to));
+        }
+        moves.sort_unstable();
+        let moved = moves.len();
+
+        let cluster_size = self.cluster_size();
+        for index in 0..moves.len() {
+            let cluster = moves[index].0;
+            // Après l'échange, `cluster` contient ce qui était à `to` : on continue avec la destination de ce contenu
+            // Aucune si `to` n'est pas une source (cluster libre) ou l'a déjà été (son contenu est parti, il ne
+            // reste qu'un contenu sans importance) : (x, x) dans la liste = cluster traité
+            while moves[index].1 != cluster {
+                let to = moves[index].1;
+                let (low, high) = (self.cluster_to_offset(cluster.min(to)), self.cluster_to_offset(cluster.max(to)));
+                let (head, tail) = self.storage.split_at_mut(high);
+                head[low..low + cluster_size].swap_with_slice(&mut tail[..cluster_size]);
+                moves[index].1 = match moves.binary_search_by_key(&to, |&(from, _)| from) {
+                    Ok(next) if moves[next].1 != to => core::mem::replace(&mut moves[next].1, to),
+                    _ => cluster,
+                };
+            }
+        }
+
+        // Nouvelles chaînes : on libère d'abord les anciennes (elles peuvent chevaucher les nouvelles)
+        for file in &plan {
+            self.release_clusters(&file.current)?;
+        }
+        for file in &plan {
+            for pair in file.target.windows(2) {
+                self.write_fat_entry(pair[0], pair[1])?;
+            }
+            self.write_fat_entry(file.target[file.target.len() - 1], CLUSTER_END)?;
+
+            self.set_entry_cluster(file.entry_offset, file.target[0]);
+        }
+        self.record(Operation::Defrag);
+        Ok(moved)
+    }
+
+    // Aperçu d'une défragmentation : fragmentation actuelle, fichiers concernés, déplacements et gain
+    pub fn defrag_preview(&self) -> Result<DefragPreview, &'static str> {
+        let plan = self.defrag_plan()?;
+        let fragmented_files = plan
+            .iter()
+            .filter(|file| file.current.windows(2).any(|pair| pair[1] != pair[0] + 1))
+            .count();
+        let cluster_moves = planned_moves(&plan);
+
+        // Après défragmentation : clusters fixes inchangés, clusters des fichiers à leur destination
+        // 1 = utilisé ; les clusters réservés 0 et 1 le sont toujours
+        let end = self.total_clusters + 2;
+        let mut used = self.cluster_map();
+        for cluster in 0..end {
+            used.set(cluster, (cluster < 2 || self.read_fat_entry(cluster)? != CLUSTER_FREE) as u8);
+        }
+        for file in &plan {
+            for &cluster in &file.current {
+                used.set(cluster, 0);
+            }
+        }
+        for file in &plan {
+            for &cluster in &file.target {
+                used.set(cluster, 1);
+            }
+        }
+        let mut largest_free_run = 0;
+        let mut current_run = 0;
+        for cluster in 0..end {
+            current_run = if used.get(cluster) == 1 { 0 } else { current_run + 1 };
+            largest_free_run = largest_free_run.max(current_run);
+        }
+        self.recycle_cluster_map(used);
+
+        Ok(DefragPreview {
+            fragmentation: self.free_space_fragmentation()?,
+            fragmented_files,
+            cluster_moves,
+            largest_free_run,
+        })
+    }
+
+    // Affiche un résumé du système
+    pub fn summary(&self) {
+        println!("\n=== Résumé du système FAT32 ===");
+        let bytes_per_sector = self.boot_sector.bytes_per_sector;
+        let sectors_per_cluster = self.boot_sector.sectors_per_cluster;
+        let cluster_size = bytes_per_sector as u32 * sectors_per_cluster as u32;
+        
+        println!("Taille cluster: {} octets", cluster_size);
+        println!("Capacite totale: {} octets ({} MB)", 
+                 self.storage.len(), self.storage.len() / (1024 * 1024));
+        
+        if let Ok(free_space) = self.get_free_space() {
+            let used_space = self.storage.len() as u32 - free_space;
+            println!("Espace utilise: {} octets ({} KB)", used_space, used_space / 1024);
+            println!("Espace libre: {} octets ({} KB)", free_space, free_space / 1024);
+        }
+    }
+
+    // Calcule l'espace libre
+    // Le nombre de clusters libres vient du cache FSInfo, en O(1), une fois ce cache confirmé : écrit par un
+    // comptage (formatage, montage limité...) ou égal au premier scan après le montage
+    // Scan complet tant qu'il n'est pas confirmé, ou s'il est absent, inconnu ou impossible (plus de clusters
+    // libres que le volume n'en contient, 0xFFFFFFFF compris)
+    pub fn get_free_space(&self) -> Result<u32, &'static str> {
+        let cached = self.fs_info().ok().map(|fs_info| fs_info.free_count).filter(|&count| count <= self.total_clusters);
+        let free_clusters = match cached {
+            Some(cached) if self.free_count_verified.get() => {
+                // En debug, un comptage complet de temps en temps vérifie que le compteur suit bien la FAT
+                let queries = self.free_space_queries.get().wrapping_add(1);
+                self.free_space_queries.set(queries);
+                if cfg!(debug_assertions) && queries.is_multiple_of(FREE_COUNT_RECHECK_INTERVAL) {
+                    debug_assert_eq!(Ok(cached), self.count_free_clusters(), "Compteur FSInfo desynchronise de la FAT");
+                }
+                cached
+            },
+            _ => {
+                let scanned = self.count_free_clusters()?;
+                self.free_count_verified.set(cached == Some(scanned));
+                scanned
+            },
+        };
+        
+        Ok(free_clusters * self.cluster_size() as u32)
+    }
+
+    // Nombre d'entrées FAT lues depuis le montage : montre le coût des parcours complets de la FAT
+    pub fn fat_reads(&self) -> u64 {
+        self.fat_reads.get()
+    }
+
+    // Octets occupés par la structure du volume plutôt que par des fichiers :
+    // secteurs réservés (boot sector, FSInfo...), toutes les copies de la FAT et les clusters du répertoire racine
+    pub fn overhead_bytes(&self) -> u32 {
+        let root_clusters = self.file_chain(self.boot_sector.root_cluster).map_or(1, |chain| chain.len() as u32);
+        self.data_start_sector * 512 + root_clusters * self.cluster_size() as u32
+    }
+
+    // Compte les clusters libres en parcourant toute la FAT (O(nombre de clusters))
+    pub fn count_free_clusters(&self) -> Result<u32, &'static str> {
+        let mut free_clusters = 0;
+        
+        for cluster in 2..self.total_clusters + 2 {
+            if self.read_fat_entry(cluster)? == CLUSTER_FREE {
+                free_clusters += 1;
+            }
+        }
+        
+        Ok(free_clusters)
+    }
+}
+
+// Fonction inverse de list_files_binary : relit les enregistrements d'un listing binaire
+pub fn parse_binary_listing(data: &[u8]) -> Result<Vec<FileInfo>, &'static str> {
+    let mut files = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        if pos + 2 > data.len() {
+            return Err("Listing binaire tronque");
+        }
+        let name_len = u16::from_le_bytes([data[pos], data[pos + 1]]) as usize;
+        pos += 2;
+
+        // nom + taille (4) + cluster (4)
+        if pos + name_len + 8 > data.len() {
+            return Err("Listing binaire tronque");
+        }
+        let name = std::str::from_utf8(&data[pos..pos + name_len])
+            .map_err(|_| "Nom invalide dans le listing binaire")?
+            .to_string();
+        pos += name_len;
+
+        let size = u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+        let cluster = u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]]);
+        pos += 8;
+
+        files.push(FileInfo { name, cluster, size, modified: None, attributes: 0, sparse: false });
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Horloge fixe : les entrées écrites pendant un test ont toujours la même date
+    fn fixed_clock() -> DosDateTime {
+        DosDateTime { year: 2024, month: 1, day: 2, hour: 3, minute: 4, second: 6 }
+    }
+
+    // Disque de test : buffer de `size` octets alloué pour toute la durée du test
+    fn storage(size: usize) -> &'static mut [u8] {
+        Box::leak(vec![0u8; size].into_boxed_slice())
+    }
+
+    // Volume formaté de 1MB avec des clusters de 4KB (188 clusters, racine comprise)
+    fn new_fs() -> Fat32FileSystem {
+        let mut fs = Fat32FileSystem::new(storage(1024 * 1024)).unwrap();
+        fs.clock = fixed_clock;
+        fs
+    }
+
+    #[test]
+    fn compact_keeps_ranges_end_to_end_and_frees_clusters() {
+        let mut fs = new_fs();
+        let cluster_size = fs.cluster_size();
+        let data: Vec<u8> = (0..3 * cluster_size).map(|i| (i % 251) as u8).collect();
+        fs.create_file_named("A.TXT", &data).unwrap();
+        let free_before = fs.count_free_clusters().unwrap();
+
+        fs.compact_file("A.TXT", &[(10, 100), (2 * cluster_size, 50)]).unwrap();
+
+        let mut expected = data[10..110].to_vec();
+        expected.extend_from_slice(&data[2 * cluster_size..2 * cluster_size + 50]);
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), expected);
+        let file = fs.find_file("A.TXT").unwrap();
+        assert_eq!(fs.file_chain(file.cluster).unwrap().len(), 1);
+        assert_eq!(fs.count_free_clusters().unwrap(), free_before + 2);
+    }
+
+    #[test]
+    fn compact_rejects_overlapping_ranges() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", &[7u8; 4096]).unwrap();
+
+        assert!(fs.compact_file("A.TXT", &[(0, 4096), (0, 4096)]).is_err());
+        assert!(fs.compact_file("A.TXT", &[(100, 10), (50, 10)]).is_err());
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), vec![7u8; 4096]);
+        assert!(fs.check_filesystem().is_ok());
+    }
+
+    #[test]
+    fn set_cluster_size_refused_leaves_volume_unchanged() {
+        // Volume de 512KB monté tel quel : trop petit pour être reformaté
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"contenu").unwrap();
+        let storage = core::mem::take(&mut fs.storage);
+        let mut fs = Fat32FileSystem::open(storage.split_at_mut(512 * 1024).0).unwrap();
+        let checksum = fs.image_checksum();
+
+        assert_eq!(fs.set_cluster_size(1), Err("Storage trop petit pour FAT32"));
+        assert!(fs.set_cluster_size(3).is_err());
+        assert_eq!(fs.storage.len(), 512 * 1024);
+        assert_eq!(fs.image_checksum(), checksum);
+        assert_eq!(fs.boot_sector.sectors_per_cluster, DEFAULT_SECTORS_PER_CLUSTER);
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), b"contenu");
+    }
+
+    #[test]
+    fn set_cluster_size_keeps_cluster_limit() {
+        let mut fs = Fat32FileSystem::open_limited(storage(1024 * 1024), 20).unwrap();
+        fs.set_cluster_size(4).unwrap();
+        assert_eq!(fs.total_clusters, 21);
+        assert_eq!(fs.count_free_clusters().unwrap(), 20);
+        assert!(fs.check_filesystem().is_ok());
+    }
+
+    #[test]
+    fn set_cluster_size_keeps_the_mount_state() {
+        let mut fs = new_fs();
+        fs.set_read_only(true);
+        assert!(fs.create_file_named("A.TXT", b"a").is_err());
+        fs.set_read_only(false);
+        fs.start_op_log();
+        fs.set_cluster_size(4).unwrap();
+
+        assert!(!fs.is_read_only());
+        assert_eq!(fs.audit_log().len(), 1);
+        assert!(fs.op_log().is_some_and(|log| log.is_empty()));
+        // Reformater un volume en lecture seule reste refusé, et audité
+        fs.set_read_only(true);
+        assert!(fs.set_cluster_size(8).is_err());
+        assert!(fs.is_read_only());
+        assert_eq!(fs.audit_log().len(), 2);
+    }
+
+    // Chemin d'un fichier temporaire propre à ce test (et à ce processus)
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("fat32_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn exported_image_mounts_with_same_files() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"premier").unwrap();
+        fs.create_file_named("un nom assez long.txt", &[9u8; 5000]).unwrap();
+        let path = temp_path("export.img");
+        fs.export_image(&path).unwrap();
+
+        let mounted = Fat32FileSystem::from_image(storage(2 * 1024 * 1024), &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let listed = |fs: &Fat32FileSystem| -> Vec<(String, usize)> {
+            fs.list_files(true).into_iter().map(|f| (f.name, f.size)).collect()
+        };
+        assert_eq!(listed(&mounted), listed(&fs));
+        assert_eq!(mounted.read_file_by_name("un nom assez long.txt").unwrap(), vec![9u8; 5000]);
+        assert!(mounted.check_filesystem().is_ok());
+    }
+
+    #[test]
+    fn check_accepts_any_end_of_chain_marker() {
+        // mkfs.fat marque FAT[1] et la racine avec 0x0FFFFFFF
+        let mut fs = new_fs();
+        fs.write_fat_entry(1, 0x0FFFFFFF).unwrap();
+        fs.write_fat_entry(2, 0x0FFFFFFF).unwrap();
+        assert!(fs.check_filesystem().is_ok());
+    }
+
+    // Écrit directement le compteur de clusters libres de FSInfo (offset 488 du secteur)
+    fn set_cached_free_count(fs: &mut Fat32FileSystem, free_count: u32) {
+        let offset = fs.boot_sector.fs_info as usize * 512 + 488;
+        fs.storage[offset..offset + 4].copy_from_slice(&free_count.to_le_bytes());
+    }
+
+    #[test]
+    fn cached_free_count_matches_full_scan() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", &[1u8; 10_000]).unwrap();
+        fs.create_file_named("B.TXT", b"b").unwrap();
+        fs.append_to_file("B.TXT", &[2u8; 9000]).unwrap();
+        fs.reserve_clusters(3).unwrap();
+        fs.truncate_file("A.TXT", 10).unwrap();
+        fs.delete_file_by_name("B.TXT").unwrap();
+        fs.allocate_contiguous(4).unwrap();
+
+        let scanned = fs.count_free_clusters().unwrap();
+        let cached = fs.fs_info().unwrap().free_count;
+        assert_eq!(cached, scanned);
+        assert_eq!(fs.get_free_space().unwrap(), scanned * fs.cluster_size() as u32);
+    }
+
+    #[test]
+    fn fsinfo_next_free_advances_and_survives_a_remount() {
+        let mut fs = new_fs();
+        assert_eq!(fs.fsinfo_next_free(), None);
+
+        fs.create_file_named("A.TXT", &[1u8; 9000]).unwrap();
+        let last = *fs.file_chain(fs.find_file("A.TXT").unwrap().cluster).unwrap().last().unwrap();
+        assert_eq!(fs.fsinfo_next_free(), Some(last + 1));
+        fs.create_file_named("B.TXT", b"b").unwrap();
+        let b = fs.find_file("B.TXT").unwrap().cluster;
+        assert_eq!(b, last + 1);
+        assert_eq!(fs.fsinfo_next_free(), Some(b + 1));
+
+        // L'indice est dans FSInfo, sur le disque : un nouveau montage le relit
+        let fs = Fat32FileSystem::open(fs.unmount().unwrap()).unwrap();
+        assert_eq!(fs.fsinfo_next_free(), Some(b + 1));
+        assert_eq!(fs.find_free_cluster(), Ok(b + 1));
+    }
+
+    #[test]
+    fn unknown_or_impossible_free_count_falls_back_to_scan() {
+        let mut fs = new_fs();
+        let scanned = fs.count_free_clusters().unwrap();
+
+        set_cached_free_count(&mut fs, FSINFO_UNKNOWN);
+        assert!(fs.check_filesystem().is_ok());
+        assert_eq!(fs.get_free_space().unwrap(), scanned * fs.cluster_size() as u32);
+
+        let impossible = fs.total_clusters + 10;
+        set_cached_free_count(&mut fs, impossible);
+        assert_eq!(fs.get_free_space().unwrap(), scanned * fs.cluster_size() as u32);
+    }
+
+    // Transforme un fichier existant en fichier vide "à la FAT" : cluster 0, taille 0, chaîne libérée
+    fn make_empty_without_chain(fs: &mut Fat32FileSystem, name: &str) {
+        let file = fs.find_file(name).unwrap();
+        let chain = fs.file_chain(file.cluster).unwrap();
+        fs.release_clusters(&chain).unwrap();
+        let offset = fs.find_entry_offset(name).unwrap();
+        fs.storage[offset + 20..offset + 22].fill(0);
+        fs.storage[offset + 26..offset + 32].fill(0);
+    }
+
+    #[test]
+    fn defragment_makes_every_file_contiguous() {
+        let mut fs = new_fs();
+        let cluster_size = fs.cluster_size();
+        fs.create_file_named("A.TXT", b"a").unwrap();
+        fs.create_file_named("B.TXT", &[2u8; 5000]).unwrap();
+        fs.create_file_named("C.TXT", b"c").unwrap();
+        fs.append_to_file("A.TXT", &vec![1u8; 2 * cluster_size]).unwrap();
+        fs.delete_file_by_name("B.TXT").unwrap();
+        fs.create_file_named("D.TXT", &[4u8; 12_000]).unwrap();
+        fs.create_file_named("VIDE.TXT", b"").unwrap();
+        make_empty_without_chain(&mut fs, "VIDE.TXT");
+        let before: Vec<Vec<u8>> = ["A.TXT", "C.TXT", "D.TXT"].iter().map(|n| fs.read_file_by_name(n).unwrap()).collect();
+        assert!(fs.file_chain(fs.find_file("A.TXT").unwrap().cluster).unwrap().windows(2).any(|w| w[1] != w[0] + 1));
+
+        assert!(fs.defragment().unwrap() > 0);
+
+        for (name, data) in ["A.TXT", "C.TXT", "D.TXT"].iter().zip(&before) {
+            let chain = fs.file_chain(fs.find_file(name).unwrap().cluster).unwrap();
+            assert!(chain.windows(2).all(|w| w[1] == w[0] + 1), "{} fragmente : {:?}", name, chain);
+            assert_eq!(&fs.read_file_by_name(name).unwrap(), data);
+        }
+        assert_eq!(fs.find_file("VIDE.TXT").unwrap().cluster, 0);
+        assert!(fs.check_filesystem().is_ok());
+    }
+
+    #[test]
+    fn create_files_rolls_back_data_entries_and_directory_growth() {
+        // Clusters de 512 octets : 16 entrées par cluster de la racine
+        let mut fs = Fat32FileSystem::new_with_cluster_size(storage(1024 * 1024), 1).unwrap();
+        for i in 0..15 {
+            fs.create_file_named(&format!("F{}.TXT", i), b"x").unwrap();
+        }
+        let root = fs.boot_sector.root_cluster;
+        let free_before = fs.count_free_clusters().unwrap();
+
+        let batch = vec![
+            ("X1.TXT".to_string(), vec![1u8; 100]),
+            ("X2.TXT".to_string(), vec![2u8; 100]),
+            ("X3.TXT".to_string(), vec![3u8; 1024 * 1024]),
+        ];
+        assert!(fs.create_files(batch).is_err());
+
+        for name in ["X1.TXT", "X2.TXT", "X3.TXT"] {
+            assert!(fs.find_file(name).is_none());
+        }
+        assert_eq!(fs.chain_len(root).unwrap(), 1);
+        assert_eq!(fs.count_free_clusters().unwrap(), free_before);
+        assert_eq!(fs.list_files(true).len(), 15);
+        assert!(fs.check_filesystem().is_ok());
+    }
+
+    #[test]
+    fn backup_boot_sector_matches_until_primary_is_corrupted() {
+        let fs = new_fs();
+        assert!(fs.compare_boot_sectors());
+        assert_eq!(fs.read_backup_boot_sector().unwrap(), &fs.boot_sector_bytes()[..]);
+
+        fs.storage[3] ^= 0xFF; // Premier octet du nom OEM
+        assert!(!fs.compare_boot_sectors());
+    }
+
+    #[test]
+    fn check_reserved_region_spots_broken_signatures_on_disk() {
+        let fs = new_fs();
+        assert_eq!(fs.check_reserved_region(), Ok(()));
+        let fs_info = fs.boot_sector.fs_info as usize * 512;
+        let backup = fs.boot_sector.backup_boot_sector as usize * 512;
+
+        // Signature de tête de FSInfo ("RRaA")
+        fs.storage[fs_info] ^= 0xFF;
+        assert!(fs.check_reserved_region().is_err());
+        fs.storage[fs_info] ^= 0xFF;
+        assert_eq!(fs.check_reserved_region(), Ok(()));
+
+        // 0x55AA en fin de backup du boot sector
+        fs.storage[backup + 510] = 0;
+        assert_eq!(fs.check_reserved_region(), Err("Signature du backup du boot sector invalide"));
+    }
+
+    #[test]
+    fn create_file_at_places_the_chain_where_asked() {
+        let mut fs = new_fs();
+        fs.create_file_at("A.TXT", &[5u8; 5000], 10).unwrap();
+
+        assert_eq!(fs.find_file("A.TXT").unwrap().cluster, 10);
+        assert_eq!(fs.file_chain(10).unwrap(), [10, 11]);
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), vec![5u8; 5000]);
+        // Clusters déjà pris : refusé
+        assert!(fs.create_file_at("B.TXT", b"b", 11).is_err());
+    }
+
+    #[test]
+    fn binary_listing_round_trips() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"a").unwrap();
+        fs.create_file_named("un nom long.data", &[1u8; 9000]).unwrap();
+        fs.create_file_named("VIDE", b"").unwrap();
+
+        let records = |files: Vec<FileInfo>| -> Vec<(String, usize, u32)> {
+            files.into_iter().map(|f| (f.name, f.size, f.cluster)).collect()
+        };
+        let parsed = parse_binary_listing(&fs.list_files_binary()).unwrap();
+        assert_eq!(records(parsed), records(fs.list_files(false)));
+        assert!(parse_binary_listing(&fs.list_files_binary()[..5]).is_err());
+    }
+
+    #[test]
+    fn reserve_takes_the_lowest_free_clusters() {
+        let mut fs = new_fs();
+        let free_before = fs.count_free_clusters().unwrap();
+
+        let reserved = fs.reserve_clusters(3).unwrap();
+        assert_eq!(reserved, [3, 4, 5]);
+        assert_eq!(fs.count_free_clusters().unwrap(), free_before - 3);
+        for cluster in reserved {
+            assert_eq!(fs.read_fat_entry(cluster).unwrap(), CLUSTER_END);
+        }
+    }
+
+    #[test]
+    fn fat_fill_pattern_still_reads_as_free() {
+        let mut fs = new_fs();
+        fs.format_fat(0xA).unwrap();
+
+        // Octet de poids fort de l'entrée 10 dans la première FAT
+        let offset = fs.fat_offset(0) + 10 * 4 + 3;
+        assert_eq!(fs.storage[offset] >> 4, 0xA);
+        assert_eq!(fs.read_fat_entry(10).unwrap(), CLUSTER_FREE);
+        assert_eq!(fs.count_free_clusters().unwrap(), fs.total_clusters - 1);
+        assert_eq!(fs.allocate_cluster().unwrap(), 3);
+    }
+
+    #[test]
+    fn fat_write_keeps_the_reserved_nibble() {
+        let mut fs = new_fs();
+        let cluster = fs.allocate_cluster().unwrap();
+        let offset = fs.fat_offset(0) + cluster as usize * 4 + 3;
+        fs.storage[offset] |= 0x50;
+
+        fs.write_fat_entry(cluster, 0x0000_1234).unwrap();
+        assert_eq!(fs.storage[offset] >> 4, 0x5);
+        assert_eq!(fs.read_fat_entry(cluster).unwrap(), 0x0000_1234);
+    }
+
+    #[test]
+    fn data_clusters_used_ignores_reserved_clusters() {
+        let mut fs = new_fs();
+        assert_eq!(fs.data_clusters_used().unwrap(), 0);
+        fs.create_file_named("A.TXT", b"un cluster").unwrap();
+        assert_eq!(fs.data_clusters_used().unwrap(), 1);
+    }
+
+    #[test]
+    fn partial_cluster_write_preserves_surroundings() {
+        let mut fs = new_fs();
+        let cluster_size = fs.cluster_size();
+        let cluster = fs.allocate_cluster().unwrap();
+        fs.write_cluster(cluster, &vec![0xAB; cluster_size]).unwrap();
+
+        fs.write_cluster_at(cluster, 100, b"milieu").unwrap();
+        let data = fs.read_cluster(cluster).unwrap();
+        assert!(data[..100].iter().all(|&b| b == 0xAB));
+        assert_eq!(&data[100..106], b"milieu");
+        assert!(data[106..].iter().all(|&b| b == 0xAB));
+        assert!(fs.write_cluster_at(cluster, cluster_size - 2, b"trop").is_err());
+    }
+
+    #[test]
+    fn raw_boot_sector_bytes_carry_signature_and_type() {
+        let bytes = new_fs().boot_sector_bytes();
+        assert_eq!(bytes[510..512], [0x55, 0xAA]);
+        assert_eq!(&bytes[82..90], b"FAT32   ");
+    }
+
+    #[test]
+    fn multi_cluster_file_reads_back_byte_for_byte() {
+        let mut fs = new_fs();
+        let data: Vec<u8> = (0..10 * 1024).map(|i| (i * 7 % 256) as u8).collect();
+        let first = fs.create_file_named("GROS.BIN", &data).unwrap();
+
+        assert_eq!(fs.file_chain(first).unwrap().len(), 3);
+        assert_eq!(fs.read_file(first, data.len()).unwrap(), data);
+        assert_eq!(fs.read_file_by_name("GROS.BIN").unwrap(), data);
+    }
+
+    #[test]
+    fn directory_entries_are_persisted_in_the_root() {
+        let mut fs = new_fs();
+        let first = fs.create_file_named("HELLO.TXT", b"Hello").unwrap();
+        fs.create_file_named("B.TXT", b"b").unwrap();
+        fs.create_file_named("C.TXT", b"c").unwrap();
+
+        // Premier emplacement de la racine : l'entrée 8.3 telle qu'écrite sur le disque
+        let root = fs.cluster_to_offset(fs.boot_sector.root_cluster);
+        let entry = DirectoryEntry::from_bytes(&fs.storage[root..root + 32]);
+        assert_eq!(&entry.name, b"HELLO   TXT");
+        assert_eq!(entry.first_cluster(), first);
+        assert_eq!({ entry.file_size }, 5);
+        assert_eq!(fs.find_file("hello.txt").unwrap().cluster, first);
+        assert_eq!(fs.read_file_by_name("HELLO.TXT").unwrap(), b"Hello");
+
+        // 0xE5 : entrée supprimée, les suivantes restent visibles ; 0x00 : fin du répertoire
+        fs.delete_file_by_name("B.TXT").unwrap();
+        assert_eq!(fs.storage[root + 32], DIR_ENTRY_DELETED);
+        assert_eq!(fs.storage[root + 3 * 32], DIR_ENTRY_END);
+        let names: Vec<String> = fs.list_files(true).into_iter().map(|f| f.name).collect();
+        assert_eq!(names, ["HELLO.TXT", "C.TXT"]);
+        assert!(fs.find_file("B.TXT").is_none());
+    }
+
+    #[test]
+    fn embedded_zero_bytes_round_trip() {
+        let mut fs = new_fs();
+        let data = b"avant\0\0\0apres\0".to_vec();
+        fs.create_file_named("ZEROS.BIN", &data).unwrap();
+
+        assert_eq!(fs.find_file("ZEROS.BIN").unwrap().size, data.len());
+        assert_eq!(fs.read_file_by_name("ZEROS.BIN").unwrap(), data);
+    }
+
+    #[test]
+    fn both_fat_copies_stay_in_sync() {
+        let mut fs = new_fs();
+        let fat_bytes = fs.boot_sector.fat_size_32 as usize * 512;
+        let same = |fs: &Fat32FileSystem| {
+            let (primary, mirror) = (fs.fat_offset(0), fs.fat_offset(1));
+            fs.storage[primary..primary + fat_bytes] == fs.storage[mirror..mirror + fat_bytes]
+        };
+
+        fs.create_file_named("A.TXT", &[1u8; 9000]).unwrap();
+        fs.append_to_file("A.TXT", &[2u8; 5000]).unwrap();
+        fs.truncate_file("A.TXT", 100).unwrap();
+        fs.create_file_named("B.TXT", b"b").unwrap();
+        fs.delete_file_by_name("B.TXT").unwrap();
+        assert!(same(&fs));
+        assert!(fs.verify_fats_consistent().is_ok());
+
+        // Une divergence est détectée et localisée
+        let mirror = fs.fat_offset(1) + 50 * 4;
+        fs.storage[mirror] = 0x07;
+        assert_eq!(fs.fat_divergence(), Some((50, 0, 7)));
+        assert!(fs.verify_fats_consistent().is_err());
+    }
+
+    #[test]
+    fn delete_frees_the_whole_chain() {
+        let mut fs = new_fs();
+        let free_before = fs.get_free_space().unwrap();
+        let first = fs.create_file_named("GROS.BIN", &[3u8; 20_000]).unwrap();
+        let chain = fs.file_chain(first).unwrap();
+        assert_eq!(chain.len(), 5);
+
+        fs.delete_file_by_name("GROS.BIN").unwrap();
+        assert_eq!(fs.get_free_space().unwrap(), free_before);
+        assert!(chain.iter().all(|&c| fs.read_fat_entry(c).unwrap() == CLUSTER_FREE));
+    }
+
+    #[test]
+    fn append_to_small_and_multi_cluster_files() {
+        let mut fs = new_fs();
+        fs.create_file_named("PETIT.TXT", b"debut").unwrap();
+        fs.append_to_file("PETIT.TXT", b" et fin").unwrap();
+        assert_eq!(fs.read_file_by_name("PETIT.TXT").unwrap(), b"debut et fin");
+        assert_eq!(fs.file_chain(fs.find_file("PETIT.TXT").unwrap().cluster).unwrap().len(), 1);
+
+        let mut expected = vec![1u8; 6000];
+        fs.create_file_named("GROS.BIN", &expected).unwrap();
+        fs.append_to_file("GROS.BIN", &[2u8; 5000]).unwrap();
+        expected.extend_from_slice(&[2u8; 5000]);
+        assert_eq!(fs.read_file_by_name("GROS.BIN").unwrap(), expected);
+        assert_eq!(fs.file_chain(fs.find_file("GROS.BIN").unwrap().cluster).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn truncate_frees_tail_clusters() {
+        let mut fs = new_fs();
+        let cluster_size = fs.cluster_size();
+        let data: Vec<u8> = (0..3 * cluster_size).map(|i| i as u8).collect();
+        fs.create_file_named("A.BIN", &data).unwrap();
+        let free_before = fs.count_free_clusters().unwrap();
+
+        fs.truncate_file("A.BIN", cluster_size / 2).unwrap();
+        assert_eq!(fs.count_free_clusters().unwrap(), free_before + 2);
+        assert_eq!(fs.read_file_by_name("A.BIN").unwrap(), &data[..cluster_size / 2]);
+        assert!(fs.truncate_file("A.BIN", cluster_size).is_err());
+    }
+
+    #[test]
+    fn crosslinked_cluster_is_reported_with_both_names() {
+        let mut fs = new_fs();
+        let a = fs.create_file_named("A.TXT", b"a").unwrap();
+        let b = fs.create_file_named("B.TXT", &[2u8; 5000]).unwrap();
+        let shared = fs.file_chain(b).unwrap()[1];
+        // A.TXT se prolonge dans le deuxième cluster de B.TXT
+        fs.write_fat_entry(a, shared).unwrap();
+
+        let crosslinks = fs.find_crosslinks();
+        assert_eq!(crosslinks.len(), 1);
+        let (cluster, mut names) = crosslinks.into_iter().next().unwrap();
+        names.sort();
+        assert_eq!((cluster, names), (shared, vec!["A.TXT".to_string(), "B.TXT".to_string()]));
+        assert!(fs.check_filesystem().is_err());
+    }
+
+    #[test]
+    fn short_names_are_normalized_or_rejected() {
+        assert_eq!(&normalize_83("a.txt").unwrap(), b"A       TXT");
+        assert_eq!(&normalize_83("README").unwrap(), b"README     ");
+        assert!(normalize_83("toolongname.ext").is_err());
+        assert!(normalize_83("NO.EXT.TRA").is_err());
+        assert!(normalize_83("A+B.TXT").is_err());
+        assert!(normalize_83(".TXT").is_err());
+    }
+
+    #[test]
+    fn long_file_name_round_trips() {
+        let mut fs = new_fs();
+        let name = "a_very_long_filename_example.txt";
+        fs.create_file_named(name, b"contenu").unwrap();
+
+        let files = fs.list_files(true);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, name);
+        assert_eq!(fs.read_file_by_name(name).unwrap(), b"contenu");
+        // Le nom court de substitution reste utilisable
+        assert_eq!(fs.read_file_by_name("A_VERY~1.TXT").unwrap(), b"contenu");
+    }
+
+    #[test]
+    fn lfn_checksum_matches_the_spec() {
+        assert_eq!(lfn_checksum(b"HELLO   TXT"), 0xF1);
+        assert_eq!(lfn_checksum(b"A_VERY~1TXT"), 0x42);
+    }
+
+    #[test]
+    fn lfn_entries_with_a_wrong_checksum_are_ignored() {
+        let mut fs = new_fs();
+        fs.create_file_named("un nom long.txt", b"x").unwrap();
+        // Le nom court change sans que les entrées LFN suivent : elles sont orphelines
+        let offset = fs.find_entry_offset("un nom long.txt").unwrap();
+        fs.storage[offset + 10] = b'X';
+
+        let files = fs.list_files(true);
+        assert_eq!(files.len(), 1);
+        assert_ne!(files[0].name, "un nom long.txt");
+        assert!(files[0].name.ends_with(".TXX"));
+    }
+
+    #[test]
+    fn dos_timestamps_round_trip() {
+        let dates = [
+            DosDateTime { year: 1980, month: 1, day: 1, hour: 0, minute: 0, second: 0 },
+            DosDateTime { year: 2000, month: 2, day: 29, hour: 12, minute: 30, second: 58 },
+            DosDateTime { year: 2024, month: 12, day: 31, hour: 23, minute: 59, second: 58 },
+            DosDateTime { year: 2107, month: 6, day: 15, hour: 7, minute: 5, second: 2 },
+        ];
+        for date in dates {
+            let (d, t) = date.encode();
+            assert_eq!(DosDateTime::decode(d, t), date);
+        }
+
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"a").unwrap();
+        assert_eq!(fs.find_file("A.TXT").unwrap().modified, Some(fixed_clock()));
+    }
+
+    #[test]
+    fn orphaned_chain_is_recovered_as_a_file() {
+        let mut fs = new_fs();
+        let data: Vec<u8> = (0..6000).map(|i| (i % 200 + 1) as u8).collect();
+        fs.create_file_named("PERDU.BIN", &data).unwrap();
+        // L'entrée disparaît mais la chaîne de 2 clusters reste allouée
+        let offset = fs.find_entry_offset("PERDU.BIN").unwrap();
+        fs.storage[offset] = DIR_ENTRY_DELETED;
+
+        assert_eq!(fs.recover_orphans().unwrap(), ["RECOVER_0.DAT"]);
+        assert_eq!(fs.read_file_by_name("RECOVER_0.DAT").unwrap(), data);
+        assert!(fs.recover_orphans().unwrap().is_empty());
+    }
+
+    #[test]
+    fn swapped_names_read_each_others_content() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"contenu de A").unwrap();
+        fs.create_file_named("un nom long.txt", b"contenu du long").unwrap();
+
+        fs.swap_names("A.TXT", "un nom long.txt").unwrap();
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), b"contenu du long");
+        assert_eq!(fs.read_file_by_name("un nom long.txt").unwrap(), b"contenu de A");
+        assert!(fs.swap_names("A.TXT", "ABSENT.TXT").is_err());
+    }
+
+    #[test]
+    fn overhead_plus_data_capacity_covers_the_storage() {
+        for fs in [new_fs(), Fat32FileSystem::new_with_cluster_size(storage(3 * 1024 * 1024 + 1000), 4).unwrap()] {
+            let cluster_size = fs.cluster_size();
+            // Capacité des données : tous les clusters sauf ceux de la racine, déjà comptés dans l'overhead
+            let data_capacity = (fs.total_clusters as usize - 1) * cluster_size;
+            let covered = fs.overhead_bytes() as usize + data_capacity;
+            assert!(covered <= fs.storage.len() && fs.storage.len() - covered < cluster_size);
+        }
+    }
+
+    #[test]
+    fn contiguous_allocation_needs_a_long_enough_run() {
+        // 10 clusters de données, occupés un sur deux : 5 libres mais jamais 2 à la suite
+        let mut fs = Fat32FileSystem::open_limited(storage(1024 * 1024), 10).unwrap();
+        for i in 0..10 {
+            fs.create_file_named(&format!("F{}.TXT", i), b"x").unwrap();
+        }
+        for i in (0..10).step_by(2) {
+            fs.delete_file_by_name(&format!("F{}.TXT", i)).unwrap();
+        }
+        assert_eq!(fs.count_free_clusters().unwrap(), 5);
+        assert!(fs.allocate_contiguous(2).is_err());
+        assert_eq!(fs.count_free_clusters().unwrap(), 5);
+
+        let mut fs = new_fs();
+        let first = fs.allocate_contiguous(5).unwrap();
+        assert_eq!(fs.file_chain(first).unwrap(), (first..first + 5).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn full_root_directory_grows_on_create() {
+        // Clusters de 512 octets : la racine est pleine après 16 entrées
+        let mut fs = Fat32FileSystem::new_with_cluster_size(storage(1024 * 1024), 1).unwrap();
+        let root = fs.boot_sector.root_cluster;
+        for i in 0..16 {
+            fs.create_file_named(&format!("F{}.TXT", i), b"x").unwrap();
+        }
+        assert_eq!(fs.chain_len(root).unwrap(), 1);
+
+        fs.create_file_named("DE_PLUS.TXT", b"y").unwrap();
+        assert_eq!(fs.chain_len(root).unwrap(), 2);
+        assert_eq!(fs.list_files(true).len(), 17);
+        assert_eq!(fs.read_file_by_name("DE_PLUS.TXT").unwrap(), b"y");
+    }
+
+
+    #[test]
+    fn preallocated_file_reads_zeros_until_written() {
+        let mut fs = new_fs();
+        let free_before = fs.count_free_clusters().unwrap();
+        fs.preallocate("RESERVE.BIN", 12 * 1024).unwrap();
+        assert_eq!(fs.find_file("RESERVE.BIN").unwrap().size, 12 * 1024);
+        assert_eq!(free_before - fs.count_free_clusters().unwrap(), 3);
+        assert!(fs.read_file_by_name("RESERVE.BIN").unwrap().iter().all(|&b| b == 0));
+
+        fs.write_file_range("RESERVE.BIN", 8000, b"au milieu").unwrap();
+        let data = fs.read_file_by_name("RESERVE.BIN").unwrap();
+        assert_eq!(data.len(), 12 * 1024);
+        assert_eq!(&data[8000..8009], b"au milieu");
+        assert!(data[..8000].iter().chain(&data[8009..]).all(|&b| b == 0));
+
+        // La taille ne bouge pas : écrire au-delà de la fin est refusé
+        assert!(fs.write_file_range("RESERVE.BIN", 12 * 1024 - 2, b"abc").is_err());
+    }
+
+
+    #[test]
+    fn directory_slot_usage_tracks_creates_and_deletes() {
+        let mut fs = new_fs();
+        let root = fs.boot_sector.root_cluster;
+        let slots = fs.cluster_size() / 32;
+        let (used, free, deleted) = fs.directory_slot_usage(root);
+        assert_eq!((free, deleted), (slots - used, 0));
+
+        for i in 0..4 {
+            fs.create_file_named(&format!("F{}.TXT", i), b"x").unwrap();
+        }
+        fs.delete_file_by_name("F1.TXT").unwrap();
+        assert_eq!(fs.directory_slot_usage(root), (used + 3, free - 4, 1));
+
+        // Un nouveau fichier réutilise l'emplacement supprimé
+        fs.create_file_named("G.TXT", b"y").unwrap();
+        assert_eq!(fs.directory_slot_usage(root), (used + 4, free - 4, 0));
+    }
+
+
+    #[test]
+    fn merge_from_applies_the_conflict_policy() {
+        let mut other = new_fs();
+        other.create_file_named("A.TXT", b"nouveau").unwrap();
+        other.create_file_named("B.TXT", b"b").unwrap();
+        let target = || {
+            let mut fs = new_fs();
+            fs.create_file_named("A.TXT", b"ancien").unwrap();
+            fs
+        };
+
+        let mut fs = target();
+        let report = fs.merge_from(&other, ConflictPolicy::Skip).unwrap();
+        assert_eq!((report.copied, report.skipped), (vec!["B.TXT".to_string()], vec!["A.TXT".to_string()]));
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), b"ancien");
+        assert_eq!(fs.read_file_by_name("B.TXT").unwrap(), b"b");
+
+        let mut fs = target();
+        let report = fs.merge_from(&other, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(report.copied, ["A.TXT", "B.TXT"]);
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), b"nouveau");
+
+        let mut fs = target();
+        let report = fs.merge_from(&other, ConflictPolicy::Rename).unwrap();
+        assert_eq!(report.renamed, [("A.TXT".to_string(), "A_1.TXT".to_string())]);
+        assert_eq!(report.copied, ["B.TXT"]);
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), b"ancien");
+        assert_eq!(fs.read_file_by_name("A_1.TXT").unwrap(), b"nouveau");
+    }
+
+    #[test]
+    fn merge_from_a_too_large_source_changes_nothing() {
+        let mut other = new_fs();
+        for i in 0..5 {
+            other.create_file_named(&format!("F{}.TXT", i), &[b'x'; 5000]).unwrap();
+        }
+        // 5 clusters libres, il en faudrait 10
+        let mut fs = Fat32FileSystem::open_limited(storage(1024 * 1024), 6).unwrap();
+        let checksum = fs.image_checksum();
+        assert!(fs.merge_from(&other, ConflictPolicy::Skip).is_err());
+        assert_eq!(fs.image_checksum(), checksum);
+    }
+
+
+    #[test]
+    fn logical_size_ignores_trailing_zeros() {
+        let mut fs = new_fs();
+        // 100 zéros de fin, dont une partie dans un cluster qui n'en contient que
+        let mut data = vec![b'x'; 4090];
+        data[10] = 0;
+        data.extend_from_slice(&[0; 100]);
+        fs.create_file_named("PADDED.BIN", &data).unwrap();
+        assert_eq!(fs.logical_size("PADDED.BIN").unwrap(), data.len() - 100);
+
+        fs.create_file_named("ZEROS.BIN", &[0; 9000]).unwrap();
+        assert_eq!(fs.logical_size("ZEROS.BIN").unwrap(), 0);
+    }
+
+
+    #[test]
+    fn files_starting_at_a_reserved_cluster_are_flagged() {
+        let mut fs = new_fs();
+        fs.create_file_named("BAD.TXT", b"donnees").unwrap();
+        fs.create_file_named("EMPTY.TXT", b"").unwrap();
+        make_empty_without_chain(&mut fs, "EMPTY.TXT");
+        assert_eq!(fs.read_file_by_name("EMPTY.TXT").unwrap(), b"");
+        fs.check_filesystem().unwrap();
+
+        // Premier cluster forcé à 1 (octets 26-27, poids faible ; 20-21 déjà à 0)
+        let offset = fs.find_entry_offset("BAD.TXT").unwrap();
+        fs.storage[offset + 26..offset + 28].copy_from_slice(&1u16.to_le_bytes());
+        assert!(fs.read_file_by_name("BAD.TXT").unwrap_err().contains("cluster reserve"));
+        assert!(fs.check_filesystem().unwrap_err().contains("cluster reserve"));
+    }
+
+
+    #[test]
+    fn set_file_clusters_grows_and_shrinks_the_chain() {
+        let mut fs = new_fs();
+        let cluster_size = fs.cluster_size();
+        fs.create_file_named("DATA.BIN", b"debut").unwrap();
+        let free = fs.count_free_clusters().unwrap();
+
+        fs.set_file_clusters("DATA.BIN", 3).unwrap();
+        assert_eq!(fs.count_free_clusters().unwrap(), free - 2);
+        assert_eq!(fs.get_free_space().unwrap(), (free - 2) * cluster_size as u32);
+        let data = fs.read_file_by_name("DATA.BIN").unwrap();
+        assert_eq!(data.len(), 3 * cluster_size);
+        assert!(data.starts_with(b"debut") && data[5..].iter().all(|&b| b == 0));
+
+        fs.set_file_clusters("DATA.BIN", 1).unwrap();
+        assert_eq!(fs.count_free_clusters().unwrap(), free);
+        assert_eq!(fs.find_file("DATA.BIN").unwrap().size, cluster_size);
+
+        // 0 : plus aucune chaîne, le fichier reste utilisable
+        fs.set_file_clusters("DATA.BIN", 0).unwrap();
+        assert_eq!(fs.count_free_clusters().unwrap(), free + 1);
+        assert_eq!((fs.find_file("DATA.BIN").unwrap().cluster, fs.read_file_by_name("DATA.BIN").unwrap()), (0, vec![]));
+        fs.append_to_file("DATA.BIN", b"retour").unwrap();
+        assert_eq!(fs.read_file_by_name("DATA.BIN").unwrap(), b"retour");
+        assert_eq!(fs.count_free_clusters().unwrap(), free);
+    }
+
+
+    #[test]
+    fn free_space_fast_path_reads_no_fat_entry() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", &[1u8; 10_000]).unwrap();
+        fs.delete_file_by_name("A.TXT").unwrap();
+        fs.create_file_named("B.TXT", b"b").unwrap();
+        let expected = fs.count_free_clusters().unwrap() * fs.cluster_size() as u32;
+
+        let reads = fs.fat_reads();
+        assert_eq!(fs.get_free_space().unwrap(), expected);
+        assert_eq!(fs.fat_reads(), reads);
+
+        // Après un remontage, le premier appel confirme le compteur par un scan, les suivants n'en font plus
+        let fs = Fat32FileSystem::open(core::mem::take(&mut fs.storage)).unwrap();
+        assert_eq!(fs.get_free_space().unwrap(), expected);
+        let reads = fs.fat_reads();
+        assert!(reads >= fs.total_clusters as u64);
+        assert_eq!(fs.get_free_space().unwrap(), expected);
+        assert_eq!(fs.fat_reads(), reads);
+    }
+
+
+    #[test]
+    fn fragmented_state_is_undone_by_defragment() {
+        let mut fs = new_fs();
+        fs.start_op_log();
+        let fragmentation = fs.create_fragmented_state(4).unwrap();
+        assert!(fragmentation > 0.8, "fragmentation {}", fragmentation);
+        assert_eq!(fs.list_files(true).len(), 2);
+        let chain = fs.file_chain(fs.find_file("FRAG0.BIN").unwrap().cluster).unwrap();
+        assert_eq!(chain.len(), FRAGMENT_CLUSTERS_PER_FILE);
+        assert!(chain.windows(2).all(|w| w[1] > w[0] + 4));
+        assert!(fs.read_file_by_name("FRAG2.BIN").unwrap().iter().all(|&b| b == b'C'));
+        fs.check_filesystem().unwrap();
+        assert_eq!(fs.op_log().unwrap().len(), 1);
+
+        fs.defragment().unwrap();
+        assert!(fs.free_space_fragmentation().unwrap() < 0.05);
+        assert!(fs.read_file_by_name("FRAG2.BIN").unwrap().iter().all(|&b| b == b'C'));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapped_image_writes_go_to_the_host_file() {
+        let path = temp_path("mapped.img");
+        new_fs().export_image(&path).unwrap();
+
+        let mut fs = Fat32FileSystem::open_mmap(&path).unwrap();
+        fs.create_file_named("MAPPED.TXT", b"ecrit dans le fichier").unwrap();
+        fs.flush_mapping().unwrap();
+        drop(fs);
+
+        // Relu par un chemin indépendant de la projection : une copie du fichier dans un buffer
+        let fs = Fat32FileSystem::from_image(storage(1024 * 1024), &path).unwrap();
+        assert_eq!(fs.read_file_by_name("MAPPED.TXT").unwrap(), b"ecrit dans le fichier");
+        let fs = Fat32FileSystem::open_mmap(&path).unwrap();
+        assert_eq!(fs.read_file_by_name("MAPPED.TXT").unwrap(), b"ecrit dans le fichier");
+
+        // Une taille qui n'est pas un nombre entier de secteurs est refusée
+        std::fs::write(&path, [0u8; 1000]).unwrap();
+        assert!(Fat32FileSystem::open_mmap(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+
+    #[test]
+    fn bundle_round_trip_keeps_metadata() {
+        let mut fs = new_fs();
+        fs.create_file_named("un nom long.txt", b"contenu du bundle").unwrap();
+        fs.set_attributes("un nom long.txt", FILE_ATTRIBUTE_READ_ONLY | FILE_ATTRIBUTE_HIDDEN).unwrap();
+        let path = temp_path("file.bundle");
+        fs.export_bundle("un nom long.txt", path.to_str().unwrap()).unwrap();
+        let original = fs.find_file("un nom long.txt").unwrap();
+        let offset = fs.find_entry_offset("un nom long.txt").unwrap();
+        let times = fs.storage[offset + 13..offset + 26].to_vec();
+
+        // Autre horloge : les dates viennent du bundle, pas de l'heure de l'import
+        let mut other = new_fs();
+        other.clock = || DosDateTime { year: 2030, month: 1, day: 1, hour: 0, minute: 0, second: 0 };
+        assert_eq!(other.import_bundle(path.to_str().unwrap()).unwrap(), "un nom long.txt");
+        let imported = other.find_file("un nom long.txt").unwrap();
+        assert_eq!((imported.attributes, imported.modified), (original.attributes, original.modified));
+        let offset = other.find_entry_offset("un nom long.txt").unwrap();
+        assert_eq!(other.storage[offset + 13..offset + 20], times[..7]);
+        assert_eq!(other.storage[offset + 22..offset + 26], times[9..]);
+        assert_eq!(other.read_file_by_name("un nom long.txt").unwrap(), b"contenu du bundle");
+
+        // Un bundle tronqué est refusé
+        std::fs::write(&path, &std::fs::read(&path).unwrap()[..20]).unwrap();
+        assert!(new_fs().import_bundle(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+
+    #[test]
+    fn sparse_file_only_allocates_written_clusters() {
+        let mut fs = new_fs();
+        let cluster_size = fs.cluster_size();
+        let free = fs.count_free_clusters().unwrap();
+        fs.create_sparse("CREUX.BIN", 1024 * 1024).unwrap();
+        assert_eq!(fs.count_free_clusters().unwrap(), free);
+        assert_eq!(fs.find_file("CREUX.BIN").unwrap().cluster, 0);
+
+        // 256 clusters logiques : l'index tient dans un cluster, plus un seul cluster de données
+        fs.write_file_range("CREUX.BIN", 500 * 1024, &vec![b'z'; cluster_size]).unwrap();
+        assert_eq!(free - fs.count_free_clusters().unwrap(), 1 + 1);
+        let data = fs.read_file_by_name("CREUX.BIN").unwrap();
+        assert_eq!(data.len(), 1024 * 1024);
+        assert!(data[500 * 1024..500 * 1024 + cluster_size].iter().all(|&b| b == b'z'));
+        assert!(data[..500 * 1024].iter().chain(&data[500 * 1024 + cluster_size..]).all(|&b| b == 0));
+
+        // Réécrire la même zone ne prend rien de plus ; check, repair et defrag laissent le fichier intact
+        fs.write_file_range("CREUX.BIN", 500 * 1024 + 10, b"encore").unwrap();
+        assert_eq!(free - fs.count_free_clusters().unwrap(), 2);
+        fs.check_filesystem().unwrap();
+        fs.repair_filesystem().unwrap();
+        fs.defragment().unwrap();
+        assert_eq!(fs.find_file("CREUX.BIN").unwrap().size, 1024 * 1024);
+        assert_eq!(&fs.read_file_by_name("CREUX.BIN").unwrap()[500 * 1024 + 10..500 * 1024 + 16], b"encore");
+        assert!(fs.append_to_file("CREUX.BIN", b"x").is_err());
+    }
+
+    #[cfg(feature = "data-crc")]
+    #[test]
+    fn data_crc_detects_changes_made_between_unmount_and_open() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"contenu").unwrap();
+        assert_eq!(fs.data_crc_status(), None);
+
+        // Cycle propre : le CRC rangé au démontage correspond
+        let fs = Fat32FileSystem::open(fs.unmount().unwrap()).unwrap();
+        assert_eq!(fs.data_crc_status(), Some(true));
+        let data_offset = fs.data_start_sector as usize * 512;
+
+        // Un octet de données modifié hors de l'outil entre le démontage et le montage
+        let storage = fs.unmount().unwrap();
+        storage[data_offset + 5 * 4096] ^= 0xFF;
+        assert_eq!(Fat32FileSystem::open(storage).unwrap().data_crc_status(), Some(false));
+    }
+
+    #[test]
+    fn read_only_mode_audits_refused_modifications() {
+        let mut fs = new_fs();
+        fs.clock = fixed_clock;
+        fs.create_file_named("A.TXT", b"contenu").unwrap();
+        let before = fs.storage.to_vec();
+
+        fs.set_read_only(true);
+        assert!(fs.write_file("A.TXT", b"autre").is_err());
+        assert!(fs.rename_file("A.TXT", "B.TXT").is_err());
+        assert!(fs.create_files(vec![("C.TXT".to_string(), b"c".to_vec())]).is_err());
+        assert!(fs.delete_file_by_name("A.TXT").is_err());
+        assert!(fs.defragment().is_err());
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), b"contenu");
+        assert!(fs.storage[..] == before[..]);
+
+        let at = fixed_clock();
+        let audit: Vec<(&str, &str)> = fs.audit_log().iter().map(|entry| (entry.operation, entry.arguments.as_str())).collect();
+        assert_eq!(audit, [
+            ("write", "A.TXT (5 octets)"),
+            ("rename", "A.TXT B.TXT"),
+            ("create", "C.TXT (1 octets)"),
+            ("delete", "A.TXT"),
+            ("defrag", ""),
+        ]);
+        assert!(fs.audit_log().iter().all(|entry| entry.at == at));
+
+        // Hors lecture seule, plus rien n'est refusé ni audité
+        fs.set_read_only(false);
+        fs.delete_file_by_name("A.TXT").unwrap();
+        assert_eq!(fs.audit_log().len(), 5);
+    }
+
+
+    #[test]
+    fn read_file_range_only_reads_the_requested_clusters() {
+        let mut fs = new_fs();
+        // Bien plus grand que le disque : seule la plage lue est construite
+        fs.create_sparse("BIG.BIN", 50_000_000).unwrap();
+        fs.write_file_range("BIG.BIN", 25_000_000, b"hello").unwrap();
+        assert_eq!(fs.read_file_range("BIG.BIN", 24_999_998, 9).unwrap(), b"\0\0hello\0\0");
+        assert_eq!(fs.read_file_range("BIG.BIN", 49_999_998, 10).unwrap(), [0, 0]);
+        assert_eq!(fs.read_file_range("BIG.BIN", 60_000_000, 10).unwrap(), []);
+
+        // Fichier ordinaire : la plage peut chevaucher deux clusters
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        fs.create_file_named("A.BIN", &data).unwrap();
+        assert_eq!(fs.read_file_range("A.BIN", 4090, 12).unwrap(), data[4090..4102]);
+        assert_eq!(fs.read_file_range("A.BIN", 0, usize::MAX).unwrap(), data);
+    }
+
+
+    #[test]
+    fn validate_all_reports_exactly_the_corrupted_file() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"premier").unwrap();
+        fs.create_file_named("B.TXT", &[b'b'; 6000]).unwrap();
+        fs.create_file_named("C.TXT", b"troisieme").unwrap();
+        fs.write_file("A.TXT", b"premier, reecrit").unwrap();
+        assert_eq!(fs.validate_all(), Ok(()));
+
+        // Un octet du second cluster de B.TXT modifié directement dans le storage
+        let cluster = fs.nth_cluster_of("B.TXT", 1).unwrap();
+        let offset = fs.cluster_offset(cluster).unwrap() + 10;
+        fs.storage[offset] ^= 0xFF;
+        assert_eq!(fs.validate_all(), Err(vec!["B.TXT".to_string()]));
+
+        // Réécrit par l'outil, il redevient valide
+        fs.write_file("B.TXT", b"propre").unwrap();
+        assert_eq!(fs.validate_all(), Ok(()));
+    }
+
+
+    #[test]
+    fn all_chains_lists_disjoint_chains_and_flags_problems() {
+        let mut fs = new_fs();
+        let a = fs.create_file_named("A.TXT", &[1u8; 5000]).unwrap();
+        let b = fs.create_file_named("B.TXT", &[2u8; 9000]).unwrap();
+        fs.create_file_named("C.TXT", b"c").unwrap();
+
+        let chains = fs.all_chains();
+        let (chain_a, chain_b) = (chains[0].clusters.clone().unwrap(), chains[1].clusters.clone().unwrap());
+        assert_eq!((chain_a.len(), chain_b.len()), (2, 3));
+        assert!(chain_a.iter().all(|cluster| !chain_b.contains(cluster)));
+        assert!(chains.iter().all(|chain| chain.shared.is_empty()));
+
+        // A.TXT rejoint la fin de B.TXT : les deux fichiers signalent les clusters partagés
+        let shared = chain_b[1..].to_vec();
+        fs.write_fat_entry(chain_a[1], shared[0]).unwrap();
+        let chains = fs.all_chains();
+        assert_eq!((&chains[0].shared, &chains[1].shared), (&shared, &shared));
+
+        // Une chaîne cassée garde son erreur au lieu d'être vide
+        fs.write_fat_entry(a, CLUSTER_FREE).unwrap();
+        assert!(fs.all_chains()[0].clusters.is_err());
+        assert_eq!(fs.all_chains()[1].clusters, Ok(fs.file_chain(b).unwrap()));
+    }
+
+
+    #[test]
+    fn replayed_op_log_rebuilds_an_identical_image() {
+        let mut fs = new_fs();
+        fs.start_op_log();
+        fs.create_file_named("A.TXT", b"premier").unwrap();
+        fs.create_file_named("un nom long.txt", &[7u8; 6000]).unwrap();
+        fs.append_to_file("A.TXT", b", suite").unwrap();
+        fs.rename_file("A.TXT", "B.TXT").unwrap();
+        fs.create_directory("DOCS").unwrap();
+        fs.truncate_file("un nom long.txt", 4100).unwrap();
+        fs.delete_file_by_name("B.TXT").unwrap();
+        let checksum = fs.image_checksum();
+
+        // Le journal passe par son format texte, puis est rejoué sur un autre disque avec une autre horloge
+        let text = op_log_to_text(fs.op_log().unwrap());
+        fn other_clock() -> DosDateTime {
+            DosDateTime { year: 2030, month: 6, day: 7, hour: 8, minute: 9, second: 10 }
+        }
+        let mut other = new_fs();
+        other.clock = other_clock;
+        other.create_file_named("AUTRE.TXT", b"efface par le replay").unwrap();
+        other.load_op_log(parse_op_log(&text).unwrap());
+        assert_eq!(other.replay(0), Ok(7));
+        assert_eq!(other.image_checksum(), checksum);
+        assert_eq!(op_log_to_text(other.op_log().unwrap()), text);
+
+        // Une étape qui échoue laisse le journal d'origine, rejouable de nouveau
+        let mut broken = parse_op_log(&text).unwrap();
+        broken.insert(3, LoggedOperation { at: fixed_clock(), operation: Operation::Delete { name: "ABSENT.TXT".to_string() } });
+        let broken_text = op_log_to_text(&broken);
+        other.load_op_log(broken);
+        assert!(other.replay(0).is_err());
+        assert_eq!(op_log_to_text(other.op_log().unwrap()), broken_text);
+        assert!(other.replay(0).is_err());
+    }
+
+
+    #[test]
+    fn merge_from_copies_multi_cluster_and_sparse_files_and_logs_them() {
+        let mut other = new_fs();
+        let data: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+        other.create_file_named("DATA.BIN", &data).unwrap();
+        other.create_sparse("CREUX.BIN", 20_000).unwrap();
+        other.write_file_range("CREUX.BIN", 13_000, b"milieu").unwrap();
+
+        let mut fs = new_fs();
+        fs.start_op_log();
+        fs.create_file_named("DATA.BIN", b"ancien").unwrap();
+        fs.merge_from(&other, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(fs.read_file_by_name("DATA.BIN").unwrap(), data);
+        let sparse = fs.read_file_by_name("CREUX.BIN").unwrap();
+        assert_eq!(sparse.len(), 20_000);
+        assert_eq!(&sparse[13_000..13_006], b"milieu");
+        assert!(sparse[..13_000].iter().chain(&sparse[13_006..]).all(|&byte| byte == 0));
+        assert_eq!(fs.validate_all(), Ok(()));
+
+        // Le journal décrit la fusion : le rejouer redonne la même image
+        let mut replayed = new_fs();
+        replayed.load_op_log(fs.op_log().unwrap().to_vec());
+        replayed.replay(0).unwrap();
+        assert_eq!(replayed.image_checksum(), fs.image_checksum());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn list_files_regex_matches_displayed_names() {
+        let mut fs = new_fs();
+        for name in ["HELLO.TXT", "HEAD.BIN", "HE.TXT", "AHEM.TXT", "hello world.txt", "HEX.TXT"] {
+            fs.create_file_named(name, b"x").unwrap();
+        }
+        fs.set_attributes("HEX.TXT", FILE_ATTRIBUTE_HIDDEN).unwrap();
+
+        let names = |pattern| -> Vec<String> {
+            fs.list_files_regex(pattern).unwrap().into_iter().map(|file| file.name).collect()
+        };
+        // Le nom long en minuscules et le fichier caché ne sortent pas
+        assert_eq!(names(r"^HE.*\.TXT$"), ["HELLO.TXT", "HE.TXT"]);
+        assert_eq!(names(r"(?i)^he.*\.txt$"), ["HELLO.TXT", "HE.TXT", "hello world.txt"]);
+        assert!(fs.list_files_regex("(").is_err());
+    }
+
+
+    #[test]
+    fn restore_metadata_brings_back_a_deleted_file() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", b"reste").unwrap();
+        let data = vec![b'z'; 9000];
+        fs.create_file_named("B.BIN", &data).unwrap();
+        let entry = fs.find_file("B.BIN").unwrap();
+        let chain = fs.file_chain(entry.cluster).unwrap();
+        let snapshot = fs.snapshot_metadata();
+
+        fs.delete_file_by_name("B.BIN").unwrap();
+        assert!(fs.find_file("B.BIN").is_none());
+        assert!(chain.iter().all(|&cluster| fs.read_fat_entry(cluster) == Ok(CLUSTER_FREE)));
+
+        fs.restore_metadata(&snapshot).unwrap();
+        let restored = fs.find_file("B.BIN").unwrap();
+        assert_eq!((restored.cluster, restored.size), (entry.cluster, entry.size));
+        assert_eq!(fs.file_chain(restored.cluster).unwrap(), chain);
+        assert_eq!(fs.read_file_by_name("B.BIN").unwrap(), data);
+        assert_eq!(fs.read_file_by_name("A.TXT").unwrap(), b"reste");
+        assert!(fs.check_filesystem().is_ok());
+    }
+
+
+    #[test]
+    fn secure_delete_leaves_the_last_pattern_on_a_freed_cluster() {
+        let mut fs = new_fs();
+        fs.create_file_named("SECRET.TXT", &[b's'; 100]).unwrap();
+        let cluster = fs.find_file("SECRET.TXT").unwrap().cluster;
+        fs.secure_delete("SECRET.TXT", 3).unwrap();
+
+        // Troisième passe (pass = 2) : xorshift32 sur tout le cluster
+        let mut seed = fs.boot_sector.volume_id ^ 3u32.wrapping_mul(0x9E3779B9);
+        let expected: Vec<u8> = (0..fs.cluster_size())
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as u8
+            })
+            .collect();
+        let offset = fs.cluster_to_offset(cluster);
+        assert!(fs.storage[offset..offset + fs.cluster_size()] == expected[..]);
+        assert_eq!(fs.read_fat_entry(cluster), Ok(CLUSTER_FREE));
+        assert!(fs.find_file("SECRET.TXT").is_none());
+    }
+
+
+    #[test]
+    fn create_directory_writes_dot_entries_and_the_directory_attribute() {
+        let mut fs = new_fs();
+        let sub = fs.create_directory("/SUB").unwrap();
+        let inner = fs.create_directory("/SUB/INNER").unwrap();
+        let entry_cluster = |entry: &[u8]| {
+            u32::from(u16::from_le_bytes([entry[20], entry[21]])) << 16 | u32::from(u16::from_le_bytes([entry[26], entry[27]]))
+        };
+
+        // Dans la racine : l'entrée SUB porte l'attribut répertoire et pointe sur son cluster
+        let root = fs.read_directory_raw("/").unwrap();
+        let entry = root.chunks(32).find(|entry| entry[..11] == *b"SUB        ").unwrap();
+        assert_eq!(entry[11], FILE_ATTRIBUTE_DIRECTORY);
+        assert_eq!(entry_cluster(entry), sub);
+
+        // "." désigne le répertoire lui-même, ".." son parent (0 pour la racine)
+        for (path, own, parent) in [("/SUB", sub, 0), ("/SUB/INNER", inner, sub)] {
+            let raw = fs.read_directory_raw(path).unwrap();
+            assert_eq!(raw[..11], *b".          ");
+            assert_eq!(raw[32..43], *b"..         ");
+            assert_eq!((raw[11], raw[32 + 11]), (FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_DIRECTORY));
+            assert_eq!((entry_cluster(&raw[..32]), entry_cluster(&raw[32..64])), (own, parent));
+        }
+    }
+
+
+    #[test]
+    fn read_directory_raw_shows_a_new_file_at_an_entry_boundary() {
+        let mut fs = new_fs();
+        fs.create_file_named("NOTES.TXT", b"notes").unwrap();
+        let raw = fs.read_directory_raw("/").unwrap();
+        assert_eq!(raw.len(), fs.cluster_size());
+
+        let offset = raw.windows(11).position(|name| name == b"NOTES   TXT").unwrap();
+        assert_eq!(offset % 32, 0);
+        assert_eq!(raw[offset + 11] & FILE_ATTRIBUTE_DIRECTORY, 0);
+        assert_eq!(u32::from_le_bytes(raw[offset + 28..offset + 32].try_into().unwrap()), 5);
+    }
+
+
+    #[test]
+    fn defrag_preview_predicts_the_moves_made_by_defragment() {
+        let mut fs = new_fs();
+        fs.create_fragmented_state(5).unwrap();
+        let checksum = fs.image_checksum();
+        let preview = fs.defrag_preview().unwrap();
+        // L'aperçu ne touche pas au disque
+        assert_eq!(fs.image_checksum(), checksum);
+        assert!(preview.fragmented_files > 0 && preview.cluster_moves > 0);
+
+        assert_eq!(fs.defragment(), Ok(preview.cluster_moves));
+        let after = fs.defrag_preview().unwrap();
+        assert_eq!((after.fragmented_files, after.cluster_moves), (0, 0));
+    }
+
+
+    #[test]
+    fn read_sector_returns_the_boot_sector_and_rejects_lbas_past_the_end() {
+        let fs = new_fs();
+        let boot = fs.read_sector(0).unwrap();
+        assert_eq!(boot.len(), 512);
+        assert!(boot.ends_with(&[0x55, 0xAA]));
+
+        let total_sectors = fs.boot_sector.total_sectors_32;
+        assert_eq!(fs.read_sector(total_sectors - 1).map(<[u8]>::len), Ok(512));
+        assert!(fs.read_sector(total_sectors).is_err());
+        assert!(fs.read_sector(u32::MAX).is_err());
+    }
+
+
+    #[test]
+    fn create_file_with_names_links_the_long_name_to_the_chosen_short_name() {
+        let mut fs = new_fs();
+        let long_name = "Rapport annuel final.txt";
+        fs.create_file_with_names(long_name, "RAPPORT.TXT", b"bilan").unwrap();
+
+        // Les entrées LFN précèdent l'entrée 8.3 et portent toutes sa somme de contrôle
+        let raw = fs.read_directory_raw("/").unwrap();
+        let entries: Vec<&[u8]> = raw.chunks(32).collect();
+        let short_index = entries.iter().position(|entry| entry[..11] == *b"RAPPORT TXT").unwrap();
+        let lfn_count = long_name.encode_utf16().count().div_ceil(LFN_CHARS_PER_ENTRY);
+        assert_eq!(short_index, lfn_count);
+        let checksum = lfn_checksum(b"RAPPORT TXT");
+        for entry in &entries[..lfn_count] {
+            assert_eq!((entry[11], entry[13]), (FILE_ATTRIBUTE_LFN, checksum));
+        }
+
+        assert_eq!(fs.read_file_by_name(long_name).unwrap(), b"bilan");
+        assert_eq!(fs.read_file_by_name("RAPPORT.TXT").unwrap(), b"bilan");
+    }
+}
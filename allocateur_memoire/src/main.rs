@@ -13,16 +13,64 @@
 
 mod allocator;          // Notre allocateur bump personnalisé (le cœur du projet)
 mod fat32;              // Le système de fichiers FAT32 qu'on a implémenté
+mod ui;                 // Choix des symboles affichés (emoji ou ASCII pur)
 
-use std::alloc::{alloc, Layout};
+use std::alloc::{alloc, GlobalAlloc, Layout};
+use std::borrow::Cow;
 use std::mem;
 use std::io::{self, Write};    // Pour le terminal interactif (flush du stdout)
-use fat32::{Fat32FileSystem, FileInfo};
+use fat32::{op_log_to_text, parse_binary_listing, parse_op_log, ConflictPolicy, Fat32FileSystem, FileInfo, MetaSnapshot, Operation};
+use fat32::{CLUSTER_FREE, FSINFO_UNKNOWN};
+use fat32::{FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_READ_ONLY, FILE_ATTRIBUTE_SYSTEM, FILE_ATTRIBUTES_EDITABLE};
+use ui::{glyph, Glyph};
 
-// Storage simulé pour notre "disque dur" FAT32 (10MB)
+// Taille de notre "disque dur" FAT32 (10MB)
+const DISK_SIZE: usize = 10 * 1024 * 1024;
+
+// Zones de garde autour du disque : un motif connu juste avant et juste après le storage
+// Si le code FAT32 déborde (mauvais copy_from_slice, offset faux...), le motif est écrasé
+const GUARD_SIZE: usize = 64;
+const GUARD_PATTERN: u8 = 0xA5;
+
+// #[repr(C)] garantit que les champs sont placés dans cet ordre, collés les uns aux autres
+#[repr(C)]
+struct GuardedDisk {
+    guard_before: [u8; GUARD_SIZE],
+    storage: [u8; DISK_SIZE],
+    guard_after: [u8; GUARD_SIZE],
+}
+
+// Storage simulé pour notre "disque dur" FAT32
 // Dans un vrai OS, ça serait un vrai disque dur ou une partition
-// Ici on simule avec un gros tableau statique en mémoire
-static mut DISK_STORAGE: [u8; 10 * 1024 * 1024] = [0; 10 * 1024 * 1024];
+// Ici on simule avec un gros tableau statique en mémoire, encadré par les zones de garde
+static mut DISK: GuardedDisk = GuardedDisk {
+    guard_before: [GUARD_PATTERN; GUARD_SIZE],
+    storage: [0; DISK_SIZE],
+    guard_after: [GUARD_PATTERN; GUARD_SIZE],
+};
+
+// Second disque, où la commande merge charge l'image à fusionner (sans passer par le heap bump)
+static mut MERGE_DISK: [u8; DISK_SIZE] = [0; DISK_SIZE];
+
+// Vérifie que les zones de garde autour du disque sont intactes
+// On ne lit que les gardes (jamais le storage, emprunté par le FS), donc pas de conflit d'accès
+fn check_guards() -> Result<(), &'static str> {
+    let (before, after) = unsafe {
+        (&*core::ptr::addr_of!(DISK.guard_before), &*core::ptr::addr_of!(DISK.guard_after))
+    };
+    check_guard_zones(before, after)
+}
+
+// Le contrôle lui-même, séparé du disque statique pour pouvoir l'exercer sur un petit buffer
+fn check_guard_zones(before: &[u8], after: &[u8]) -> Result<(), &'static str> {
+    if before.iter().any(|&b| b != GUARD_PATTERN) {
+        return Err("Zone de garde AVANT le disque ecrasee (underflow)");
+    }
+    if after.iter().any(|&b| b != GUARD_PATTERN) {
+        return Err("Zone de garde APRES le disque ecrasee (overflow)");
+    }
+    Ok(())
+}
 
 // Test complet de notre allocateur bump personnalisé
 // Reproduit les tests de base + test de débordement volontaire
@@ -39,7 +87,7 @@ fn test_allocator() {
 
     // Test 3 : allocation d'une string slice (la string elle-même est dans le binaire)
     let c = Box::new("hello rust");
-    println!("c = {}, address = {:p}, size = {} octets", c, c.as_ptr(), mem::size_of_val(&*c));
+    println!("c = {}, address = {:p}, size = {} octets", c, c.as_ptr(), mem::size_of_val(*c));
     
     // Afficher l'état de notre allocateur
     println!("Mémoire utilisée : {} / {} octets", 
@@ -47,14 +95,14 @@ fn test_allocator() {
              allocator::ALLOCATOR.heap_size());
 
     // Test 4 : tentative d'allocation qui doit échouer (plus de mémoire dispo)
-    // Notre heap fait 64KB, on essaie d'allouer 64KB d'un coup -> doit échouer
-    let layout = Layout::from_size_align(64 * 1024, 8).unwrap();
+    // On essaie d'allouer la taille du heap entier d'un coup (64KB par défaut) -> doit échouer
+    let layout = Layout::from_size_align(allocator::HEAP_SIZE, 8).unwrap();
     unsafe {
         let ptr = alloc(layout);
         if ptr.is_null() {
-            println!("✅ Allocation échouée comme prévu (plus assez de mémoire)");
+            println!("{ok} Allocation échouée comme prévu (plus assez de mémoire)", ok = glyph(Glyph::Ok));
         } else {
-            println!("❌ ATTENTION : allocation réussie alors qu'elle ne devrait pas");
+            println!("{err} ATTENTION : allocation réussie alors qu'elle ne devrait pas", err = glyph(Glyph::Err));
             // Note : si ça arrive, c'est qu'on a mal calculé la taille ou qu'il y a un bug
         }
     }
@@ -62,17 +110,369 @@ fn test_allocator() {
     println!("Mémoire après tentative : {} / {} octets", 
              allocator::ALLOCATOR.allocated_bytes(), 
              allocator::ALLOCATOR.heap_size());
+
+    // Test 5 : un second allocateur de 4 Ko, indépendant de l'allocateur global
+    let small = SMALL_ALLOCATOR.heap_size();
+    let layout = Layout::from_size_align(small, 8).unwrap();
+    unsafe {
+        let fits = !SMALL_ALLOCATOR.alloc(layout).is_null();
+        let overflow = SMALL_ALLOCATOR.alloc(Layout::from_size_align(1, 1).unwrap()).is_null();
+        if fits && overflow {
+            println!("{ok} Petit allocateur: {} octets alloues puis plein, heap global intact ({} / {})", 
+                     small, allocator::ALLOCATOR.allocated_bytes(), allocator::ALLOCATOR.heap_size(), ok = glyph(Glyph::Ok));
+        } else {
+            println!("{err} ATTENTION : le petit allocateur ne respecte pas sa taille", err = glyph(Glyph::Err));
+        }
+    }
+}
+
+// Allocateur de test de 4 Ko, à côté de l'allocateur global de 64 Ko
+static SMALL_ALLOCATOR: allocator::BumpAllocator<4096> = allocator::BumpAllocator::new();
+
+// Affiche un buffer en hexadécimal, 16 octets par ligne (format proche de hexdump -C)
+// Rien n'est alloué : le heap ne libère jamais, un dump de secteur ne doit pas le consommer
+fn hexdump(data: &[u8]) {
+    for (line, chunk) in data.chunks(16).enumerate() {
+        print!("  {:04X}: ", line * 16);
+        let mut ascii = [b' '; 16];
+        for (index, &b) in chunk.iter().enumerate() {
+            print!("{:02X} ", b);
+            ascii[index] = if b.is_ascii_graphic() || b == b' ' { b } else { b'.' };
+        }
+        for _ in chunk.len()..16 {
+            print!("   ");
+        }
+        println!(" |{}|", core::str::from_utf8(&ascii[..chunk.len()]).unwrap_or(""));
+    }
+}
+
+// En-tête du listing façon DOS "dir" : label et numéro de série du volume
+fn listing_header(label: &str, volume_id: u32) -> String {
+    format!(" Volume {} - numero de serie {:04X}-{:04X}", label, volume_id >> 16, volume_id & 0xFFFF)
+}
+
+// Pied du listing façon DOS "dir" : nombre de fichiers et octets libres
+fn listing_footer(file_count: usize, free_bytes: u32) -> String {
+    format!("   {} fichier(s)   {} octets libres", file_count, free_bytes)
+}
+
+// Destination de la commande read : affichage dans le terminal ou écriture sur l'hôte
+#[derive(Debug, PartialEq)]
+enum ReadTarget<'a> {
+    Terminal,
+    HostFile(&'a str),
+}
+
+// Encodage utilisé pour afficher le contenu d'un fichier (read --enc)
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Encoding {
+    Utf8,   // UTF-8, les séquences invalides deviennent U+FFFD
+    Latin1, // ISO-8859-1 : chaque octet = le point de code Unicode de même valeur
+    Ascii,  // ASCII strict : les octets >= 0x80 deviennent '?'
+}
+
+impl Encoding {
+    fn parse(name: &str) -> Option<Encoding> {
+        match name.to_lowercase().as_str() {
+            "utf8" | "utf-8" => Some(Encoding::Utf8),
+            "latin1" | "iso-8859-1" => Some(Encoding::Latin1),
+            "ascii" => Some(Encoding::Ascii),
+            _ => None,
+        }
+    }
+}
+
+// Décode des octets bruts en texte selon l'encodage choisi
+fn decode_bytes(data: &[u8], encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Utf8 => String::from_utf8_lossy(data).into_owned(),
+        Encoding::Latin1 => data.iter().map(|&b| b as char).collect(),
+        Encoding::Ascii => data.iter().map(|&b| if b < 0x80 { b as char } else { '?' }).collect(),
+    }
+}
+
+const READ_USAGE: &str = "Usage: read <nom> [<offset> <longueur>] [> <chemin_hote> | --out <chemin_hote>] [--enc utf8|latin1|ascii]";
+
+// Au-delà de cette taille, read n'affiche qu'un aperçu pour ne pas inonder le terminal
+const READ_PREVIEW_LIMIT: usize = 4096;
+
+// Arguments de la commande read une fois parsés
+struct ReadArgs<'a> {
+    name: &'a str,
+    target: ReadTarget<'a>,
+    encoding: Encoding,
+    range: Option<(usize, usize)>, // (offset, longueur) : n'afficher qu'une partie du fichier
+}
+
+// Parse les arguments de read : "read <nom>", "read <nom> > <chemin>" ou "read <nom> --out <chemin>",
+// avec en option "<offset> <longueur>" juste après le nom et "--enc <encodage>" pour l'affichage
+fn parse_read_args<'a>(parts: &[&'a str]) -> Result<ReadArgs<'a>, &'static str> {
+    if parts.len() < 2 {
+        return Err(READ_USAGE);
+    }
+
+    let mut args = ReadArgs {
+        name: parts[1],
+        target: ReadTarget::Terminal,
+        encoding: Encoding::Utf8,
+        range: None,
+    };
+
+    // Plage optionnelle : deux nombres juste après le nom
+    let mut options = &parts[2..];
+    if let [offset, len, rest @ ..] = options {
+        if let (Ok(offset), Ok(len)) = (offset.parse::<usize>(), len.parse::<usize>()) {
+            args.range = Some((offset, len));
+            options = rest;
+        }
+    }
+
+    // Les options vont toujours par paires : <option> <valeur>
+    for option in options.chunks(2) {
+        match option {
+            [">" | "--out", path] => args.target = ReadTarget::HostFile(path),
+            ["--enc", enc] => args.encoding = Encoding::parse(enc).ok_or("Encodage inconnu (utf8, latin1 ou ascii)")?,
+            _ => return Err(READ_USAGE),
+        }
+    }
+
+    // L'export vers l'hôte copie toujours le fichier entier
+    if args.range.is_some() && args.target != ReadTarget::Terminal {
+        return Err("La plage <offset> <longueur> ne s'utilise qu'a l'affichage");
+    }
+
+    Ok(args)
+}
+
+// Un contenu trop long pour l'affichage n'est lu que sur `limit` octets
+// Renvoie, si on coupe, le message expliquant comment tout afficher
+fn preview_notice(name: &str, size: usize, limit: usize) -> Option<String> {
+    (size > limit).then(|| format!(
+        "... (affichage de {} sur {} octets, utilisez 'read {} 0 {}' pour tout voir)",
+        limit, size, name, size
+    ))
+}
+
+// Fichiers touchés pendant la session du terminal, regroupés par type d'opération (commande changes)
+// Chaque nom n'apparaît qu'une fois par groupe, dans l'ordre de la première opération
+// Comparaison sans la casse, comme FAT : "a.txt" tapé et "A.TXT" relu du disque sont le même fichier
+#[derive(Default)]
+struct SessionChanges {
+    created: Vec<String>,
+    modified: Vec<String>,
+    deleted: Vec<String>,
+}
+
+impl SessionChanges {
+    fn record(group: &mut Vec<String>, name: &str) {
+        if !group.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            group.push(name.to_string());
+        }
+    }
+
+    fn created(&mut self, name: &str) {
+        Self::record(&mut self.created, name);
+    }
+
+    fn modified(&mut self, name: &str) {
+        Self::record(&mut self.modified, name);
+    }
+
+    fn deleted(&mut self, name: &str) {
+        Self::record(&mut self.deleted, name);
+    }
+
+    // Écriture brute dans un cluster (writeat) : on note le fichier qui le possède
+    // (aucun si le cluster est libre ou système)
+    fn cluster_written(&mut self, fs: &Fat32FileSystem, cluster: u32) {
+        let owner = fs.list_files(true).into_iter()
+            .find(|file| fs.file_chain(file.cluster).is_ok_and(|chain| chain.contains(&cluster)));
+        if let Some(file) = owner {
+            self.modified(&file.name);
+        }
+    }
+
+    // Opérations sur tout le volume (repair, defrag, restore...) : on compare les listings avant / après
+    // Un fichier est modifié si son premier cluster, sa taille ou sa date a changé
+    fn record_listing_diff(&mut self, before: &[FileInfo], after: &[FileInfo]) {
+        for old in before {
+            match after.iter().find(|file| file.name == old.name) {
+                None => self.deleted(&old.name),
+                Some(new) if (new.cluster, new.size, new.modified) != (old.cluster, old.size, old.modified) => self.modified(&old.name),
+                Some(_) => {},
+            }
+        }
+        for new in after.iter().filter(|file| !before.iter().any(|old| old.name == file.name)) {
+            self.created(&new.name);
+        }
+    }
+
+    fn print(&self) {
+        if self.created.is_empty() && self.modified.is_empty() && self.deleted.is_empty() {
+            println!("{info}  Aucune modification pendant cette session", info = glyph(Glyph::Info));
+            return;
+        }
+        for (label, names) in [("Crees", &self.created), ("Modifies", &self.modified), ("Supprimes", &self.deleted)] {
+            if names.is_empty() {
+                continue;
+            }
+            println!("{folder} {} ({}):", label, names.len(), folder = glyph(Glyph::Folder));
+            for name in names {
+                println!("  {doc} {}", name, doc = glyph(Glyph::File));
+            }
+        }
+    }
+}
+
+// Nombre d'échantillons gardés par fragwatch (le heap bump ne libère rien, on borne la taille)
+const FRAGWATCH_MAX_SAMPLES: usize = 40;
+
+// Transforme une suite de ratios (0.0 à 1.0) en mini-graphique d'une ligne (caractères ASCII si `ascii`)
+fn render_sparkline(samples: &[f32], ascii: bool) -> String {
+    const UNICODE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    const ASCII_LEVELS: [char; 8] = ['_', '.', '-', ':', '=', '+', '*', '#'];
+    let levels = if ascii { &ASCII_LEVELS } else { &UNICODE_LEVELS };
+
+    samples
+        .iter()
+        .map(|&ratio| {
+            let index = (ratio.clamp(0.0, 1.0) * (levels.len() - 1) as f32).round() as usize;
+            levels[index]
+        })
+        .collect()
+}
+
+// Remplace le premier mot de la ligne par sa commande s'il s'agit d'un alias ("ll -x" -> "ls -a -x")
+// Une seule expansion (pas d'alias d'alias, donc pas de boucle) ; sans alias, aucune allocation
+fn expand_alias<'a>(aliases: &[(String, String)], line: &'a str) -> Cow<'a, str> {
+    let (first, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    match aliases.iter().find(|(name, _)| name.eq_ignore_ascii_case(first)) {
+        Some((_, command)) if rest.is_empty() => Cow::Owned(command.clone()),
+        Some((_, command)) => Cow::Owned(format!("{} {}", command, rest)),
+        None => Cow::Borrowed(line),
+    }
+}
+
+// Suite d'allocations (taille, alignement) que produit la démo sur l'état actuel du disque :
+// un listing (Vec<FileInfo> qui double de capacité + un String par nom) puis la lecture de chaque fichier
+// Sert à memprofile pour estimer si HEAP_SIZE suffit
+fn demo_workload(fs: &Fat32FileSystem) -> Vec<(usize, usize)> {
+    let info_size = mem::size_of::<fat32::FileInfo>();
+    let info_align = mem::align_of::<fat32::FileInfo>();
+    let files = fs.list_files(true);
+    let mut workload = Vec::with_capacity(files.len() * 2 + 8);
+
+    // Listing : Vec démarre à 4 éléments puis double (et le bump ne récupère jamais l'ancien buffer)
+    let mut capacity = 0;
+    for (count, file) in files.iter().enumerate() {
+        workload.push((file.name.len(), 1));
+        if count == capacity {
+            capacity = if capacity == 0 { 4 } else { capacity * 2 };
+            workload.push((capacity * info_size, info_align));
+        }
+    }
+
+    // Lecture : read_file copie le contenu dans un Vec<u8>
+    for file in files.iter() {
+        workload.push((file.size, 1));
+    }
+
+    workload
+}
+
+// Listing filtré par ls --regex, disponible seulement avec la feature "regex"
+#[cfg(feature = "regex")]
+fn list_files_matching(fs: &Fat32FileSystem, pattern: &str) -> Result<Vec<fat32::FileInfo>, &'static str> {
+    fs.list_files_regex(pattern)
+}
+
+#[cfg(not(feature = "regex"))]
+fn list_files_matching(_fs: &Fat32FileSystem, _pattern: &str) -> Result<Vec<fat32::FileInfo>, &'static str> {
+    Err("Filtre indisponible : recompiler avec --features regex")
+}
+
+// Montage d'une image projetée en mémoire (--mmap), disponible seulement avec la feature "mmap"
+#[cfg(feature = "mmap")]
+fn open_mapped_image(path: &str) -> Result<Fat32FileSystem, &'static str> {
+    Fat32FileSystem::open_mmap(std::path::Path::new(path))
+}
+
+#[cfg(not(feature = "mmap"))]
+fn open_mapped_image(_path: &str) -> Result<Fat32FileSystem, &'static str> {
+    Err("Montage par projection indisponible : recompiler avec --features mmap")
+}
+
+// Avertit si la zone de données a changé hors de cet outil depuis le dernier démontage (CRC rangé dans FSInfo)
+#[cfg(feature = "data-crc")]
+fn report_data_crc(fs: &Fat32FileSystem) {
+    match fs.data_crc_status() {
+        Some(true) => println!("   - Zone de donnees intacte depuis le dernier demontage (CRC32)"),
+        Some(false) => println!("{warn}  Zone de donnees modifiee hors de cet outil depuis le dernier demontage (CRC32 different)", warn = glyph(Glyph::Warn)),
+        None => {}
+    }
+}
+
+#[cfg(not(feature = "data-crc"))]
+fn report_data_crc(_fs: &Fat32FileSystem) {}
+
+// Barre proportionnelle "[####----] 50%" : `width` caractères entre crochets, arrondis au plus proche
+fn render_usage_bar(used: usize, total: usize, width: usize) -> String {
+    let ratio = if total == 0 { 0.0 } else { used.min(total) as f64 / total as f64 };
+    let filled = (ratio * width as f64).round() as usize;
+    format!("[{}{}] {:.0}%", "#".repeat(filled), "-".repeat(width - filled), ratio * 100.0)
+}
+
+// Seuil par défaut de l'alerte mémoire basse : prévient avant qu'un list_files ne fasse crasher le programme
+const LOW_MEMORY_THRESHOLD: usize = 8 * 1024;
+
+// Affiche l'alerte levée par l'allocateur quand il reste peu de mémoire
+// Appelé entre deux commandes, jamais depuis alloc() : l'affichage lui-même alloue
+fn report_low_memory() {
+    if allocator::ALLOCATOR.take_low_memory_alert() {
+        eprintln!("{warn}  Memoire basse : plus que {} octets disponibles dans le heap",
+                  allocator::ALLOCATOR.remaining(), warn = glyph(Glyph::Warn));
+    }
 }
 
 // Terminal interactif pour tester notre système FAT32 en live
 // Inspiré des shells Unix mais simplifié pour notre cas d'usage
 // Commandes disponibles : ls, create, read, delete, info, space, check, demo, quit
 fn terminal_interactif(fs: &mut Fat32FileSystem) {
-    println!("\n🚀 === TERMINAL FAT32 INTERACTIF ===");
+    println!("\n{rocket} === TERMINAL FAT32 INTERACTIF ===", rocket = glyph(Glyph::Rocket));
     println!("Tapez 'help' pour voir les commandes disponibles");
     
+    // Fichiers créés / modifiés / supprimés depuis le lancement du terminal
+    let mut changes = SessionChanges::default();
+    
+    // Dernier snapshot des métadonnées (commande snapshot), pour annuler avec restore
+    let mut meta_snapshot: Option<MetaSnapshot> = None;
+    
+    // Échantillons de fragmentation de l'espace libre (None = fragwatch inactif)
+    let mut fragwatch: Option<Vec<f32>> = None;
+    let mut command_executed = false;
+    
+    // Alias définis avec la commande alias : (nom, commande)
+    let mut aliases: Vec<(String, String)> = Vec::new();
+    
     // Boucle principale du terminal (REPL = Read-Eval-Print-Loop)
     loop {
+        report_low_memory();
+        
+        // fragwatch : après chaque commande, on mesure la fragmentation et on affiche la tendance
+        if let (Some(samples), true) = (fragwatch.as_mut(), command_executed) {
+            match fs.free_space_fragmentation() {
+                Ok(ratio) => {
+                    if samples.len() == FRAGWATCH_MAX_SAMPLES {
+                        samples.remove(0);
+                    }
+                    samples.push(ratio);
+                    println!("  [fragwatch] {:.3} {}", ratio, render_sparkline(samples, ui::ascii_mode()));
+                },
+                Err(e) => println!("{err} fragwatch: {}", e, err = glyph(Glyph::Err)),
+            }
+        }
+        command_executed = false;
+        
         // Afficher le prompt (comme bash$ ou cmd>)
         print!("FAT32> ");
         io::stdout().flush().unwrap();  // Forcer l'affichage immédiat
@@ -81,45 +481,164 @@ fn terminal_interactif(fs: &mut Fat32FileSystem) {
         let mut input = String::new();
         match io::stdin().read_line(&mut input) {
             Ok(_) => {
-                let input = input.trim();  // Supprimer les \n et espaces
+                let input = expand_alias(&aliases, input.trim());  // Supprimer les \n et espaces, puis remplacer un alias
                 let parts: Vec<&str> = input.split_whitespace().collect();  // Parser les arguments
                 
                 if parts.is_empty() {
                     continue;  // Ligne vide, recommencer
                 }
+                command_executed = true;
                 
                 // Dispatch vers la bonne commande (pattern matching ftw!)
                 match parts[0].to_lowercase().as_str() {
                     "help" | "h" => {
-                        println!("📖 Commandes disponibles:");
-                        println!("  ls                    - Lister les fichiers");
-                        println!("  create <nom> <contenu> - Creer un fichier");
+                        println!("{read} Commandes disponibles:", read = glyph(Glyph::Read));
+                        println!("  ls [-a]               - Lister les fichiers (-a : fichiers caches compris)");
+                        println!("  ls --regex <motif>    - Lister les fichiers dont le nom correspond (feature regex)");
+                        println!("  attrib <nom> [+r|-r|+h|-h|+s|-s|+a|-a ...] - Afficher ou modifier les attributs");
+                        println!("  lsbin <chemin>        - Ecrire le listing binaire sur l'hote");
+                        println!("  lsbin --read <chemin> - Relire un listing binaire de l'hote");
+                        println!("  create <nom> <contenu> - Creer un fichier (nom long accepte, sans espace)");
+                        println!("  createmany <nom>=<contenu> ... - Creer plusieurs fichiers (tout ou rien)");
+                        println!("  createat <cluster> <nom> <contenu> - Creer un fichier contigu a partir d'un cluster precis");
+                        println!("  createlfn <nom_long> <NOM8.3> <contenu> - Creer un fichier avec un alias 8.3 choisi");
                         println!("  read <nom>            - Lire un fichier");
+                        println!("  read <nom> > <chemin> - Copier un fichier vers l'hote (ou --out <chemin>)");
+                        println!("  read <nom> --enc <e>  - Lire avec un encodage (utf8, latin1, ascii)");
+                        println!("  read <nom> <off> <n>  - Lire n octets a partir de l'offset off");
+                        println!("  write <nom> <contenu> - Remplacer le contenu d'un fichier (le cree s'il n'existe pas)");
+                        println!("  append <nom> <texte>  - Ajouter du texte a la fin d'un fichier");
+                        println!("  prealloc <nom> <taille> - Reserver un fichier rempli de zeros");
+                        println!("  sparse <nom> <taille> - Creer un fichier creux (clusters alloues a l'ecriture, non standard)");
+                        println!("  writerange <nom> <offset> <texte> - Ecrire dans un fichier sans changer sa taille");
+                        println!("  rename <ancien> <nouveau> - Renommer (garde l'extension si omise)");
+                        println!("  swap <nom1> <nom2>    - Echanger les noms de deux fichiers");
+                        println!("  compact <nom> <offset>:<longueur> ... - Ne garder que ces plages (croissantes, disjointes)");
+                        println!("  truncate <nom> <taille> - Raccourcir un fichier");
+                        println!("  setclusters <nom> <n> - Donner exactement n clusters a un fichier (0 = aucun)");
                         println!("  delete <nom>          - Supprimer un fichier");
+                        println!("  mkdir <chemin>        - Creer un repertoire (ex: mkdir DOCS, mkdir /DOCS/2024)");
+                        println!("  dirdump [chemin]      - Dump brut des entrees de 32 octets d'un repertoire (defaut: /)");
+                        println!("  dirstat [chemin]      - Emplacements utilises, libres et supprimes d'un repertoire");
+                        println!("  shred <nom> [passes]  - Supprimer en ecrasant les donnees (3 passes par defaut)");
                         println!("  info                  - Informations systeme");
+                        println!("  geometry              - Offsets de la FAT et de la zone de donnees");
+                        println!("  fsinfo                - Compteur de clusters libres et indice next_free de FSInfo");
+                        println!("  setclustersize <n>    - Reformater avec n secteurs par cluster (efface tout)");
                         println!("  space                 - Espace disque");
+                        println!("  fragwatch [off]       - Suivre la fragmentation de l'espace libre apres chaque commande");
                         println!("  check                 - Verifier le systeme");
+                        println!("  repair                - Corriger chaines croisees, chaines cassees et clusters perdus");
+                        println!("  recover               - Rattacher les chaines orphelines (RECOVER_N.DAT)");
+                        println!("  validate              - Relire tous les fichiers, verifier leur stabilite et leur CRC");
+                        println!("  guards                - Verifier les zones de garde autour du disque");
+                        println!("  chains                - Afficher la chaine FAT de chaque fichier");
+                        println!("  export <fichier.img>  - Ecrire l'image complete du disque sur l'hote");
+                        println!("  bundle <nom> <chemin> - Sauver un fichier et ses metadonnees (attributs, dates) sur l'hote");
+                        println!("  unbundle <chemin>     - Recreer un fichier sauve par bundle");
+                        println!("  merge <fichier.img> [skip|overwrite|rename] - Copier les fichiers d'une autre image (skip par defaut)");
+                        println!("  defrag                - Rendre chaque fichier contigu (le plus bas possible)");
+                        println!("  defrag --preview      - Estimer le gain d'une defragmentation (sans rien deplacer)");
+                        println!("  fragment <n>          - Remplir le disque de n fichiers entrelaces, puis en supprimer un sur deux");
+                        println!("  crosslinks            - Lister les clusters partages entre plusieurs fichiers");
+                        println!("  nth <nom> <n>         - Numero du n-ieme cluster d'un fichier");
+                        println!("  logical <nom>         - Taille d'un fichier sans ses octets nuls de fin");
+                        println!("  reserve <n>           - Reserver n clusters libres (sans les chainer)");
+                        println!("  reserve <n> --contiguous - Allouer n clusters consecutifs, chaines entre eux");
+                        println!("  writeat <cluster> <offset> <texte> - Ecrire dans un cluster sans toucher au reste");
+                        println!("  fatfill <0-F>         - Reformater la FAT avec un motif dans les bits reserves (efface tout)");
+                        println!("  bootsector            - Afficher le boot sector brut et verifier sa signature");
+                        println!("  sector <lba>          - Afficher un secteur brut (0 = boot, 1 = FSInfo, 6 = backup...)");
+                        println!("  bootbackup            - Afficher et comparer le backup du boot sector");
+                        println!("  mem [reset]           - Utilisation du heap et pic (reset = repartir du niveau actuel)");
+                        println!("  memwarn <octets>      - Regler le seuil d'alerte memoire basse");
+                        println!("  memprofile            - Simuler les allocations de la demo sur le bump allocator");
+                        println!("  changes               - Fichiers crees/modifies/supprimes pendant la session");
+                        println!("  oplog [on|off]        - Journal de toutes les modifications du disque");
+                        println!("  oplog save|load <chemin> - Ecrire ou relire le journal sur l'hote");
+                        println!("  replay [etape]        - Reformater et rejouer le journal (depuis l'etape donnee)");
+                        println!("  snapshot              - Sauvegarder la FAT et la racine (metadonnees seulement)");
+                        println!("  restore               - Revenir au dernier snapshot (creations/suppressions annulees)");
                         println!("  demo                  - Lancer demo automatique");
+                        println!("  readonly [on|off]     - Refuser toute modification du disque (--read-only au lancement)");
+                        println!("  audit                 - Modifications refusees en lecture seule (operation, arguments, heure)");
+                        println!("  color on|off          - Activer/desactiver les emojis (off = ASCII pur)");
+                        println!("  alias [<nom> \"<commande>\"] - Lister ou definir un alias (ex: alias ll \"ls -a\")");
+                        println!("  unalias <nom>         - Supprimer un alias");
                         println!("  quit | exit           - Quitter");
                     },
                     
                     // Commande ls : lister les fichiers (comme Unix ls)
                     "ls" | "list" => {
-                        let files = fs.list_files();
+                        // ls -a : fichiers cachés compris ; ls --regex <motif> : seulement les noms qui correspondent
+                        let show_hidden = parts.get(1) == Some(&"-a");
+                        let files = match parts.as_slice() {
+                            [_, "--regex", pattern] => match list_files_matching(fs, pattern) {
+                                Ok(files) => files,
+                                Err(e) => {
+                                    println!("{err} Erreur: {}", e, err = glyph(Glyph::Err));
+                                    continue;
+                                }
+                            },
+                            _ => fs.list_files(show_hidden),
+                        };
+                        println!("{}", listing_header(&fs.volume_label(), fs.volume_id()));
+                        let file_count = files.len();
                         if files.is_empty() {
-                            println!("📁 Aucun fichier trouve");
+                            println!("{folder} Aucun fichier trouve", folder = glyph(Glyph::Folder));
                         } else {
-                            println!("📁 Fichiers:");
+                            println!("{folder} Fichiers:", folder = glyph(Glyph::Folder));
                             for file in files {
-                                println!("  📄 {} (cluster {}, {} octets)", file.name, file.cluster, file.size);
+                                let sparse = if file.sparse { ", creux" } else { "" };
+                                match file.modified {
+                                    Some(modified) => println!("  {doc} {} (cluster {}, {} octets{}, modifie le {})",
+                                                               file.name, file.cluster, file.size, sparse, modified, doc = glyph(Glyph::File)),
+                                    None => println!("  {doc} {} (cluster {}, {} octets{})", file.name, file.cluster, file.size, sparse, doc = glyph(Glyph::File)),
+                                }
                             }
                         }
+                        // Sous-répertoires après les fichiers, repérés par le "/" final
+                        for dir in fs.list_directories().into_iter().filter(|dir| show_hidden || dir.attributes & FILE_ATTRIBUTE_HIDDEN == 0) {
+                            println!("  {folder} {}/ (cluster {})", dir.name, dir.cluster, folder = glyph(Glyph::Folder));
+                        }
+                        match fs.get_free_space() {
+                            Ok(free_space) => println!("{}", listing_footer(file_count, free_space)),
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande lsbin : listing binaire pour d'autres outils (écriture ou relecture)
+                    "lsbin" => {
+                        match parts.as_slice() {
+                            [_, path] => {
+                                let listing = fs.list_files_binary();
+                                match std::fs::write(path, &listing) {
+                                    Ok(_) => println!("{ok} Listing binaire ecrit dans '{}' ({} octets)", path, listing.len(), ok = glyph(Glyph::Ok)),
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                }
+                            },
+                            [_, "--read", path] => {
+                                match std::fs::read(path) {
+                                    Ok(data) => match parse_binary_listing(&data) {
+                                        Ok(files) => {
+                                            println!("{folder} {} enregistrement(s):", files.len(), folder = glyph(Glyph::Folder));
+                                            for file in files {
+                                                println!("  {doc} {} (cluster {}, {} octets)", file.name, file.cluster, file.size, doc = glyph(Glyph::File));
+                                            }
+                                        },
+                                        Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                    },
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                }
+                            },
+                            _ => println!("{err} Usage: lsbin <chemin> | lsbin --read <chemin>", err = glyph(Glyph::Err)),
+                        }
                     },
                     
                     // Commande create : créer un nouveau fichier ON FAIT PAS TOUCH ICI 🤡 
                     "create" => {
                         if parts.len() < 3 {
-                            println!("❌ Usage: create <nom> <contenu>");
+                            println!("{err} Usage: create <nom> <contenu>", err = glyph(Glyph::Err));
                             continue;
                         }
                         
@@ -127,39 +646,467 @@ fn terminal_interactif(fs: &mut Fat32FileSystem) {
                         let content = parts[2..].join(" ");  // Rejoindre tous les mots après le nom
                         
                         match fs.create_file_named(filename, content.as_bytes()) {
-                            Ok(cluster) => println!("✅ Fichier '{}' cree dans le cluster {} ({} octets)", 
-                                                   filename, cluster, content.len()),
-                            Err(e) => println!("❌ Erreur: {}", e),
+                            Ok(cluster) => {
+                                changes.created(filename);
+                                println!("{ok} Fichier '{}' cree dans le cluster {} ({} octets)", 
+                                         filename, cluster, content.len(), ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande createmany : création par lot, annulée entièrement si un fichier échoue
+                    "createmany" => {
+                        let batch: Option<Vec<(String, Vec<u8>)>> = parts[1..]
+                            .iter()
+                            .map(|arg| arg.split_once('=').map(|(name, content)| (name.to_string(), content.as_bytes().to_vec())))
+                            .collect();
+                        let batch = match batch {
+                            Some(batch) if !batch.is_empty() => batch,
+                            _ => {
+                                println!("{err} Usage: createmany <nom>=<contenu> [<nom>=<contenu>...]", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        
+                        let names: Vec<String> = batch.iter().map(|(name, _)| name.clone()).collect();
+                        match fs.create_files(batch) {
+                            Ok(_) => {
+                                for name in &names {
+                                    changes.created(name);
+                                }
+                                println!("{ok} {} fichier(s) crees", names.len(), ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {} (aucun fichier du lot n'a ete cree)", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande createat : créer un fichier à un cluster choisi (placement déterministe)
+                    "createat" => {
+                        if parts.len() < 4 {
+                            println!("{err} Usage: createat <cluster> <nom> <contenu>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        let cluster: u32 = match parts[1].parse() {
+                            Ok(c) => c,
+                            Err(_) => {
+                                println!("{err} Cluster invalide: '{}'", parts[1], err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        let filename = parts[2];
+                        let content = parts[3..].join(" ");
+                        
+                        match fs.create_file_at(filename, content.as_bytes(), cluster) {
+                            Ok(_) => {
+                                changes.created(filename);
+                                let size = fs.find_file(filename).map_or(0, |file| file.size);
+                                println!("{ok} Fichier '{}' cree a partir du cluster {} ({} octets)", 
+                                         filename, cluster, size, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande createlfn : nom long et alias 8.3 choisis tous les deux
+                    "createlfn" => {
+                        if parts.len() < 4 {
+                            println!("{err} Usage: createlfn <nom_long> <NOM8.3> <contenu>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        let (long_name, short_name) = (parts[1], parts[2]);
+                        let content = parts[3..].join(" ");
+                        
+                        match fs.create_file_with_names(long_name, short_name, content.as_bytes()) {
+                            Ok(cluster) => {
+                                changes.created(long_name);
+                                println!("{ok} Fichier '{}' (alias {}) cree dans le cluster {} ({} octets)",
+                                         long_name, short_name.to_ascii_uppercase(), cluster, content.len(), ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
                         }
                     },
                     
                     // Commande read : afficher le contenu d'un fichier (comme Unix cat) ON FAIT PAS DE CAT NON PLUS ICI ! 🤡 
                     "read" => {
-                        if parts.len() != 2 {
-                            println!("❌ Usage: read <nom>");
+                        let args = match parse_read_args(&parts) {
+                            Ok(args) => args,
+                            Err(usage) => {
+                                println!("{err} {}", usage, err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        let name = args.name;
+                        
+                        match args.target {
+                            ReadTarget::Terminal => {
+                                // Seule la plage affichée est lue : un grand fichier (creux par exemple) n'est jamais chargé en entier
+                                let size = fs.find_file(name).map_or(0, |file| file.size);
+                                let (offset, len, notice) = match args.range {
+                                    Some((offset, len)) => (offset, len, None),
+                                    None => (0, READ_PREVIEW_LIMIT, preview_notice(name, size, READ_PREVIEW_LIMIT)),
+                                };
+                                match fs.read_file_range(name, offset, len) {
+                                    Ok(data) => {
+                                        let content = decode_bytes(&data, args.encoding);
+                                        println!("{read} Contenu de '{}':", name, read = glyph(Glyph::Read));
+                                        println!("\"{}\"", content);
+                                        if let Some(notice) = notice {
+                                            println!("{}", notice);
+                                        }
+                                    },
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                }
+                            },
+                            // Redirection : on écrit les octets bruts sur l'hôte au lieu de les afficher
+                            ReadTarget::HostFile(path) => match fs.export_host_file(name, path) {
+                                Ok(size) => println!("{ok} '{}' exporte vers '{}' ({} octets)", name, path, size, ok = glyph(Glyph::Ok)),
+                                Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                            },
+                        }
+                    },
+                    
+                    // Commande write : écraser un fichier en réutilisant ses clusters (comme Unix echo >)
+                    "write" => {
+                        if parts.len() < 3 {
+                            println!("{err} Usage: write <nom> <contenu>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        let existed = fs.find_file(parts[1]).is_some();
+                        let content = parts[2..].join(" ");
+                        match fs.write_file(parts[1], content.as_bytes()) {
+                            Ok(cluster) => {
+                                if existed {
+                                    changes.modified(parts[1]);
+                                } else {
+                                    changes.created(parts[1]);
+                                }
+                                println!("{ok} '{}' ecrit a partir du cluster {} ({} octets)", 
+                                         parts[1], cluster, content.len(), ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande append : agrandir un fichier (comme Unix echo >>)
+                    "append" => {
+                        if parts.len() < 3 {
+                            println!("{err} Usage: append <nom> <texte>", err = glyph(Glyph::Err));
                             continue;
                         }
                         
-                        match fs.read_file_by_name(parts[1]) {
-                            Ok(data) => {
-                                let content = std::str::from_utf8(&data).unwrap_or("Donnees binaires");
-                                println!("📖 Contenu de '{}':", parts[1]);
-                                println!("\"{}\"", content);
+                        let text = parts[2..].join(" ");
+                        match fs.append_to_file(parts[1], text.as_bytes()) {
+                            Ok(_) => {
+                                changes.modified(parts[1]);
+                                println!("{ok} {} octets ajoutes a '{}'", text.len(), parts[1], ok = glyph(Glyph::Ok));
                             },
-                            Err(e) => println!("❌ Erreur: {}", e),
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
                         }
                     },
                     
+                    // Commande prealloc : réserver la place d'un fichier avant d'avoir ses données (comme fallocate)
+                    "prealloc" => {
+                        let size = match (parts.len(), parts.get(2).map(|v| v.parse::<usize>())) {
+                            (3, Some(Ok(size))) => size,
+                            _ => {
+                                println!("{err} Usage: prealloc <nom> <taille>", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+
+                        match fs.preallocate(parts[1], size) {
+                            Ok(_) => {
+                                changes.created(parts[1]);
+                                println!("{ok} '{}' reserve ({} octets)", parts[1], size, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande sparse : fichier creux, seules les zones écrites par writerange prennent de la place
+                    "sparse" => {
+                        let size = match (parts.len(), parts.get(2).map(|v| v.parse::<usize>())) {
+                            (3, Some(Ok(size))) => size,
+                            _ => {
+                                println!("{err} Usage: sparse <nom> <taille>", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+
+                        match fs.create_sparse(parts[1], size) {
+                            Ok(_) => {
+                                changes.created(parts[1]);
+                                println!("{ok} Fichier creux '{}' cree ({} octets, aucun cluster alloue)", parts[1], size, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande writerange : écrire à un offset, à l'intérieur de la taille actuelle du fichier
+                    "writerange" => {
+                        let offset = match parts.get(2).map(|v| v.parse::<usize>()) {
+                            Some(Ok(offset)) if parts.len() >= 4 => offset,
+                            _ => {
+                                println!("{err} Usage: writerange <nom> <offset> <texte>", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+
+                        let text = parts[3..].join(" ");
+                        match fs.write_file_range(parts[1], offset, text.as_bytes()) {
+                            Ok(_) => {
+                                changes.modified(parts[1]);
+                                println!("{ok} {} octets ecrits dans '{}' a l'offset {}", text.len(), parts[1], offset, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande attrib : sans option affiche les attributs, sinon les modifie (comme sous DOS)
+                    "attrib" => {
+                        if parts.len() < 2 {
+                            println!("{err} Usage: attrib <nom> [+r|-r|+h|-h|+s|-s|+a|-a ...]", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        let mut attributes = match fs.get_attributes(parts[1]) {
+                            Ok(attributes) => attributes,
+                            Err(e) => {
+                                println!("{err} Erreur: {}", e, err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        
+                        let mut valid = true;
+                        for flag in &parts[2..] {
+                            let bit = match flag.get(1..).map(|f| f.to_ascii_lowercase()).as_deref() {
+                                Some("r") => FILE_ATTRIBUTE_READ_ONLY,
+                                Some("h") => FILE_ATTRIBUTE_HIDDEN,
+                                Some("s") => FILE_ATTRIBUTE_SYSTEM,
+                                Some("a") => FILE_ATTRIBUTE_ARCHIVE,
+                                _ => 0,
+                            };
+                            match (flag.as_bytes().first(), bit) {
+                                (Some(b'+'), bit) if bit != 0 => attributes |= bit,
+                                (Some(b'-'), bit) if bit != 0 => attributes &= !bit,
+                                _ => valid = false,
+                            }
+                        }
+                        if !valid {
+                            println!("{err} Usage: attrib <nom> [+r|-r|+h|-h|+s|-s|+a|-a ...]", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        if parts.len() > 2 {
+                            // Les bits répertoire/label ne sont pas modifiables : on ne renvoie que les autres
+                            if let Err(e) = fs.set_attributes(parts[1], attributes & FILE_ATTRIBUTES_EDITABLE) {
+                                println!("{err} Erreur: {}", e, err = glyph(Glyph::Err));
+                                continue;
+                            }
+                            changes.modified(parts[1]);
+                        }
+                        let letters: String = [
+                            (FILE_ATTRIBUTE_ARCHIVE, 'A'),
+                            (FILE_ATTRIBUTE_SYSTEM, 'S'),
+                            (FILE_ATTRIBUTE_HIDDEN, 'H'),
+                            (FILE_ATTRIBUTE_READ_ONLY, 'R'),
+                            (FILE_ATTRIBUTE_DIRECTORY, 'D'),
+                        ]
+                        .iter()
+                        .map(|&(bit, letter)| if attributes & bit != 0 { letter } else { '-' })
+                        .collect();
+                        println!("  {}  {} (0x{:02X})", letters, parts[1], attributes);
+                    },
+                    
+                    // Commande rename : l'extension d'origine est conservée si le nouveau nom n'en donne pas
+                    "rename" => {
+                        if parts.len() != 3 {
+                            println!("{err} Usage: rename <ancien> <nouveau>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        match fs.rename_file(parts[1], parts[2]) {
+                            Ok(new_name) => {
+                                changes.deleted(parts[1]);
+                                changes.created(&new_name);
+                                println!("{ok} '{}' renomme en '{}'", parts[1], new_name, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande swap : chaque nom désigne ensuite le contenu de l'autre fichier
+                    "swap" => {
+                        let [_, a, b] = parts.as_slice() else {
+                            println!("{err} Usage: swap <nom1> <nom2>", err = glyph(Glyph::Err));
+                            continue;
+                        };
+                        match fs.swap_names(a, b) {
+                            Ok(_) => {
+                                changes.modified(a);
+                                changes.modified(b);
+                                println!("{ok} Noms de '{}' et '{}' echanges", a, b, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande compact : réécrit un fichier avec seulement les plages demandées
+                    "compact" => {
+                        let ranges: Option<Vec<(usize, usize)>> = parts.get(2..).unwrap_or(&[])
+                            .iter()
+                            .map(|arg| {
+                                let (offset, len) = arg.split_once(':')?;
+                                Some((offset.parse().ok()?, len.parse().ok()?))
+                            })
+                            .collect();
+                        let ranges = match ranges {
+                            Some(ranges) if !ranges.is_empty() => ranges,
+                            _ => {
+                                println!("{err} Usage: compact <nom> <offset>:<longueur> [<offset>:<longueur>...]", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        
+                        match fs.compact_file(parts[1], &ranges) {
+                            Ok(_) => {
+                                changes.modified(parts[1]);
+                                println!("{ok} '{}' compacte ({} plage(s) gardee(s))", parts[1], ranges.len(), ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande truncate : raccourcir un fichier (comme Unix truncate -s)
+                    "truncate" => {
+                        let size = match (parts.len(), parts.get(2).map(|v| v.parse::<usize>())) {
+                            (3, Some(Ok(size))) => size,
+                            _ => {
+                                println!("{err} Usage: truncate <nom> <taille>", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        
+                        match fs.truncate_file(parts[1], size) {
+                            Ok(_) => {
+                                changes.modified(parts[1]);
+                                println!("{ok} '{}' tronque a {} octets", parts[1], size, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande setclusters : fixe la longueur de la chaîne d'un fichier, en clusters
+                    "setclusters" => {
+                        let count = match (parts.len(), parts.get(2).map(|v| v.parse::<u32>())) {
+                            (3, Some(Ok(count))) => count,
+                            _ => {
+                                println!("{err} Usage: setclusters <nom> <n>", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+
+                        match fs.set_file_clusters(parts[1], count) {
+                            Ok(_) => {
+                                changes.modified(parts[1]);
+                                let size = fs.find_file(parts[1]).map_or(0, |file| file.size);
+                                println!("{ok} '{}' occupe {} cluster(s), taille {} octets", parts[1], count, size, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
                     // Commande delete : supprimer un fichier (comme Unix rm)
                     "delete" | "del" | "rm" => {
                         if parts.len() != 2 {
-                            println!("❌ Usage: delete <nom>");
+                            println!("{err} Usage: delete <nom>", err = glyph(Glyph::Err));
                             continue;
                         }
                         
                         match fs.delete_file_by_name(parts[1]) {
-                            Ok(_) => println!("✅ Fichier '{}' supprime", parts[1]),
-                            Err(e) => println!("❌ Erreur: {}", e),
+                            Ok(_) => {
+                                changes.deleted(parts[1]);
+                                println!("{ok} Fichier '{}' supprime", parts[1], ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande mkdir : créer un sous-répertoire (comme Unix mkdir)
+                    "mkdir" => {
+                        if parts.len() != 2 {
+                            println!("{err} Usage: mkdir <chemin>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        match fs.create_directory(parts[1]) {
+                            Ok(cluster) => {
+                                changes.created(parts[1]);
+                                println!("{ok} Repertoire '{}' cree dans le cluster {}", parts[1], cluster, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande dirdump : entrées brutes d'un répertoire, une par bloc de 32 octets
+                    "dirdump" => {
+                        let path = parts.get(1).copied().unwrap_or("/");
+                        match fs.read_directory_raw(path) {
+                            Ok(raw) => {
+                                println!("{folder} Repertoire '{}' ({} octets):", path, raw.len(), folder = glyph(Glyph::Folder));
+                                for (index, entry) in raw.chunks(32).enumerate() {
+                                    println!("  --- entree {} (offset 0x{:04X}) ---", index, index * 32);
+                                    hexdump(entry);
+                                    // 0x00 en premier octet : fin du répertoire, la suite est vide
+                                    if entry[0] == 0x00 {
+                                        println!("  --- fin du repertoire ---");
+                                        break;
+                                    }
+                                }
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande dirstat : occupation des emplacements d'un répertoire
+                    "dirstat" => {
+                        let path = parts.get(1).copied().unwrap_or("/");
+                        match fs.resolve_directory(path) {
+                            Ok(cluster) => {
+                                let (used, free, deleted) = fs.directory_slot_usage(cluster);
+                                println!("{folder} Repertoire '{}' (cluster {}):", path, cluster, folder = glyph(Glyph::Folder));
+                                println!("  Utilises:   {}", used);
+                                println!("  Libres:     {}", free);
+                                println!("  Supprimes:  {}", deleted);
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande shred : suppression sécurisée en plusieurs passes (comme Unix shred)
+                    "shred" => {
+                        if parts.len() < 2 || parts.len() > 3 {
+                            println!("{err} Usage: shred <nom> [passes]", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        let passes = match parts.get(2).map(|p| p.parse::<u32>()) {
+                            None => 3,
+                            Some(Ok(p)) => p,
+                            Some(Err(_)) => {
+                                println!("{err} Nombre de passes invalide: '{}'", parts[2], err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        
+                        match fs.secure_delete(parts[1], passes) {
+                            Ok(_) => {
+                                changes.deleted(parts[1]);
+                                println!("{ok} Fichier '{}' ecrase ({} passes) et supprime", parts[1], passes, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
                         }
                     },
                     
@@ -167,43 +1114,800 @@ fn terminal_interactif(fs: &mut Fat32FileSystem) {
                         fs.info();
                     },
                     
+                    // Commande geometry : rend visible le calcul de cluster_to_offset
+                    "geometry" => {
+                        let bytes_per_sector = fs.boot_sector.bytes_per_sector as u32;
+                        let last_cluster = fs.total_clusters + 1;
+                        println!("{ruler} Geometrie du volume:", ruler = glyph(Glyph::Ruler));
+                        println!("  fat_start_sector:  {} (octet {})", fs.fat_start_sector, fs.fat_start_sector * bytes_per_sector);
+                        println!("  data_start_sector: {} (octet {})", fs.data_start_sector, fs.data_start_sector * bytes_per_sector);
+                        match (fs.cluster_offset(2), fs.cluster_offset(last_cluster)) {
+                            (Ok(first), Ok(last)) => {
+                                println!("  cluster 2 (premier cluster de donnees): octet {}", first);
+                                println!("  cluster {} (dernier cluster valide): octet {}", last_cluster, last);
+                            },
+                            (Err(e), _) | (_, Err(e)) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande fsinfo : les deux valeurs en cache de FSInfo, comparées à la FAT
+                    "fsinfo" => {
+                        let free_count = match fs.fs_info() {
+                            Ok(fs_info) => fs_info.free_count,
+                            Err(e) => {
+                                println!("{err} Erreur: {}", e, err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        let sector = fs.boot_sector.fs_info;
+                        let fat_reads = fs.fat_reads();
+                        println!("{disk} FSInfo (secteur {}):", sector, disk = glyph(Glyph::Disk));
+                        match (free_count, fs.count_free_clusters()) {
+                            (FSINFO_UNKNOWN, _) => println!("  Clusters libres : inconnu (0xFFFFFFFF)"),
+                            (cached, Ok(scanned)) if cached == scanned => println!("  Clusters libres : {} (valide)", cached),
+                            (cached, Ok(scanned)) => println!("  Clusters libres : {} (perime, la FAT en compte {})", cached, scanned),
+                            (cached, Err(e)) => println!("  Clusters libres : {} (verification impossible: {})", cached, e),
+                        }
+                        match fs.fsinfo_next_free() {
+                            None => println!("  Prochain libre  : inconnu (0xFFFFFFFF)"),
+                            Some(next) if next < 2 || next >= fs.total_clusters + 2 => {
+                                println!("  Prochain libre  : {} (hors du volume, ignore)", next)
+                            },
+                            Some(next) => match fs.read_fat_entry(next) {
+                                Ok(CLUSTER_FREE) => println!("  Prochain libre  : {} (valide, cluster libre)", next),
+                                _ => println!("  Prochain libre  : {} (indice perime, cluster occupe)", next),
+                            },
+                        }
+                        println!("  Lectures FAT    : {} depuis le montage (hors verification ci-dessus)", fat_reads);
+                    },
+                    
+                    // Commande setclustersize : reformate le volume avec une autre taille de cluster (après confirmation)
+                    "setclustersize" => {
+                        match parts.get(1).map(|v| v.parse::<u8>()) {
+                            Some(Ok(sectors)) if sectors == fs.boot_sector.sectors_per_cluster => {
+                                println!("{ok} Le volume utilise deja {} secteur(s) par cluster", sectors, ok = glyph(Glyph::Ok));
+                            },
+                            Some(Ok(sectors)) => if let Err(e) = fat32::check_cluster_size(sectors) {
+                                println!("{err} Erreur: {}", e, err = glyph(Glyph::Err));
+                            } else {
+                                // Reformater efface tout : on demande confirmation avant
+                                print!("{warn}  Le volume va etre reformate, tous les fichiers seront perdus. Continuer ? (o/N) ", warn = glyph(Glyph::Warn));
+                                io::stdout().flush().unwrap();
+                                let mut answer = String::new();
+                                io::stdin().read_line(&mut answer).unwrap();
+                                if !answer.trim().eq_ignore_ascii_case("o") {
+                                    println!("{info}  Annule, volume inchange", info = glyph(Glyph::Info));
+                                } else {
+                                    let before = fs.list_files(true);
+                                    match fs.set_cluster_size(sectors) {
+                                        Ok(_) => {
+                                            changes.record_listing_diff(&before, &fs.list_files(true));
+                                            println!("{ok} Volume reformate : {} secteur(s) par cluster ({} octets), {} clusters de donnees",
+                                                     sectors, fs.cluster_size(), fs.total_clusters - 1, ok = glyph(Glyph::Ok));
+                                        },
+                                        Err(e) => {
+                                            let current = fs.boot_sector.sectors_per_cluster;
+                                            println!("{err} Erreur: {}", e, err = glyph(Glyph::Err));
+                                            println!("   Taille actuelle: {} secteur(s) par cluster, inchangee", current);
+                                        },
+                                    }
+                                }
+                            },
+                            _ => println!("{err} Usage: setclustersize <secteurs_par_cluster>", err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
                     "space" => {
                         match fs.get_free_space() {
                             Ok(free_space) => {
-                                let total_space = fs.storage.len() as u32;
-                                let used_space = total_space - free_space;
-                                println!("💾 Espace disque:");
+                                // Taille réelle du volume : zone système + clusters de données (pas toute la mémoire de stockage)
+                                let cluster_size = fs.cluster_size() as u32;
+                                let total_space = fs.data_start_sector * 512 + fs.total_clusters * cluster_size;
+                                let used_space = total_space.saturating_sub(free_space);
+                                let overhead = fs.overhead_bytes();
+                                println!("{disk} Espace disque:", disk = glyph(Glyph::Disk));
                                 println!("  Total: {} octets ({} KB)", total_space, total_space / 1024);
                                 println!("  Utilise: {} octets ({} KB)", used_space, used_space / 1024);
+                                // Clusters alloués hors racine ; les clusters d'extension de la racine sont déjà comptés dans la structure
+                                let root_extra = overhead.saturating_sub(fs.data_start_sector * 512 + cluster_size);
+                                let files = match fs.data_clusters_used() {
+                                    Ok(clusters) => (clusters * cluster_size).saturating_sub(root_extra),
+                                    Err(_) => used_space.saturating_sub(overhead),
+                                };
+                                println!("    dont structure (reserve + FATs + racine): {} octets ({} KB)", overhead, overhead / 1024);
+                                println!("    dont fichiers: {} octets ({} KB)", files, files / 1024);
                                 println!("  Libre: {} octets ({} KB)", free_space, free_space / 1024);
                             },
-                            Err(e) => println!("❌ Erreur: {}", e),
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande fragwatch : active (ou coupe) le suivi de la fragmentation de l'espace libre
+                    "fragwatch" => {
+                        if parts.get(1) == Some(&"off") {
+                            fragwatch = None;
+                            command_executed = false;
+                            println!("{ok} fragwatch desactive", ok = glyph(Glyph::Ok));
+                        } else {
+                            fragwatch = Some(Vec::with_capacity(FRAGWATCH_MAX_SAMPLES));
+                            println!("{ok} fragwatch active (0 = espace libre contigu, 1 = tres fragmente)", ok = glyph(Glyph::Ok));
                         }
                     },
                     
                     "check" => {
                         match fs.check_filesystem() {
-                            Ok(_) => println!("✅ Systeme de fichiers OK"),
-                            Err(e) => println!("❌ Erreur: {}", e),
+                            Ok(_) => println!("{ok} Systeme de fichiers OK", ok = glyph(Glyph::Ok)),
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande repair : corrige la FAT façon fsck, puis affiche le bilan
+                    "repair" => {
+                        let before = fs.list_files(true);
+                        match fs.repair_filesystem() {
+                            Ok(report) => {
+                                changes.record_listing_diff(&before, &fs.list_files(true));
+                                println!("{ok} Reparation terminee:", ok = glyph(Glyph::Ok));
+                                println!("  Chaines croisees recopiees: {}", report.crosslinks);
+                                println!("  Chaines coupees: {}", report.truncated_chains);
+                                println!("  Clusters perdus liberes: {}", report.lost_clusters);
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande recover : rend accessibles les chaînes de clusters qui n'ont plus d'entrée
+                    "recover" => {
+                        match fs.recover_orphans() {
+                            Ok(names) if names.is_empty() => println!("{ok} Aucune chaine orpheline", ok = glyph(Glyph::Ok)),
+                            Ok(names) => {
+                                println!("{ok} {} chaine(s) orpheline(s) recuperee(s):", names.len(), ok = glyph(Glyph::Ok));
+                                for name in names {
+                                    changes.created(&name);
+                                    println!("  {doc} {}", name, doc = glyph(Glyph::File));
+                                }
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    "validate" => {
+                        match fs.validate_all() {
+                            Ok(_) => println!("{ok} Tous les fichiers se relisent a l'identique", ok = glyph(Glyph::Ok)),
+                            Err(failures) => {
+                                println!("{err} {} fichier(s) en echec:", failures.len(), err = glyph(Glyph::Err));
+                                for name in failures {
+                                    println!("  {doc} {}", name, doc = glyph(Glyph::File));
+                                }
+                            },
+                        }
+                    },
+                    
+                    "guards" => {
+                        match check_guards() {
+                            Ok(_) => println!("{ok} Zones de garde intactes ({} octets de chaque cote)", GUARD_SIZE, ok = glyph(Glyph::Ok)),
+                            Err(e) => println!("{err} {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande export : copie brute du disque vers un fichier .img de l'hôte
+                    "export" => {
+                        let [_, path] = parts.as_slice() else {
+                            println!("{err} Usage: export <fichier.img>", err = glyph(Glyph::Err));
+                            continue;
+                        };
+                        match fs.export_image(path) {
+                            Ok(_) => println!("{ok} Image du disque ecrite dans '{}' ({} octets)", path, fs.storage.len(), ok = glyph(Glyph::Ok)),
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande bundle : un fichier avec ses attributs et ses dates, dans un fichier de l'hôte
+                    "bundle" => {
+                        let [_, name, path] = parts.as_slice() else {
+                            println!("{err} Usage: bundle <nom> <chemin>", err = glyph(Glyph::Err));
+                            continue;
+                        };
+                        match fs.export_bundle(name, path) {
+                            Ok(size) => println!("{ok} '{}' sauve dans '{}' ({} octets)", name, path, size, ok = glyph(Glyph::Ok)),
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande unbundle : inverse de bundle
+                    "unbundle" => {
+                        let [_, path] = parts.as_slice() else {
+                            println!("{err} Usage: unbundle <chemin>", err = glyph(Glyph::Err));
+                            continue;
+                        };
+                        match fs.import_bundle(path) {
+                            Ok(name) => {
+                                changes.created(&name);
+                                println!("{ok} '{}' recree depuis '{}'", name, path, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande merge : copie les fichiers d'une image de l'hôte, montée sur le second disque
+                    "merge" => {
+                        let policy = match parts.get(2).copied() {
+                            None | Some("skip") => ConflictPolicy::Skip,
+                            Some("overwrite") => ConflictPolicy::Overwrite,
+                            Some("rename") => ConflictPolicy::Rename,
+                            Some(_) => {
+                                println!("{err} Usage: merge <fichier.img> [skip|overwrite|rename]", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        let Some(path) = parts.get(1).filter(|_| parts.len() <= 3) else {
+                            println!("{err} Usage: merge <fichier.img> [skip|overwrite|rename]", err = glyph(Glyph::Err));
+                            continue;
+                        };
+                        // Le FS de l'image ne vit que le temps de la commande : un seul emprunt du second disque à la fois
+                        let storage = unsafe { &mut *core::ptr::addr_of_mut!(MERGE_DISK) };
+                        let existing: Vec<String> = fs.list_files(true).into_iter().map(|file| file.name).collect();
+                        let result = Fat32FileSystem::from_image(storage, std::path::Path::new(path))
+                            .and_then(|other| fs.merge_from(&other, policy));
+                        match result {
+                            Ok(report) => {
+                                for name in &report.copied {
+                                    if existing.contains(name) {
+                                        changes.modified(name);
+                                    } else {
+                                        changes.created(name);
+                                    }
+                                }
+                                for (from, to) in &report.renamed {
+                                    changes.created(to);
+                                    println!("  {pin} {} copie sous le nom {}", from, to, pin = glyph(Glyph::Pin));
+                                }
+                                for name in &report.skipped {
+                                    println!("  {warn}  {} existe deja, ignore", name, warn = glyph(Glyph::Warn));
+                                }
+                                println!(
+                                    "{ok} Fusion terminee : {} copie(s), {} renomme(s), {} ignore(s)",
+                                    report.copied.len(), report.renamed.len(), report.skipped.len(), ok = glyph(Glyph::Ok)
+                                );
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande fragment : prépare une démo de defrag (fichiers entrelacés, espace libre éparpillé)
+                    "fragment" => {
+                        let count = match (parts.len(), parts.get(1).map(|v| v.parse::<usize>())) {
+                            (2, Some(Ok(count))) => count,
+                            _ => {
+                                println!("{err} Usage: fragment <n>", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+
+                        match fs.create_fragmented_state(count) {
+                            Ok(fragmentation) => {
+                                for index in (0..count).step_by(2) {
+                                    changes.created(&format!("FRAG{}.BIN", index));
+                                }
+                                println!("{ok} {} fichier(s) fragmente(s) crees", count.div_ceil(2), ok = glyph(Glyph::Ok));
+                                println!("  Fragmentation de l'espace libre: {:.0}% (essayer defrag)", fragmentation * 100.0);
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande defrag : rend chaque fichier contigu ; avec --preview, montre seulement ce que ça rapporterait
+                    "defrag" => {
+                        match parts.get(1).copied() {
+                            None => {
+                                let before = fs.list_files(true);
+                                match fs.defragment() {
+                                    Ok(moved) => {
+                                        changes.record_listing_diff(&before, &fs.list_files(true));
+                                        println!("{ok} Defragmentation terminee : {} cluster(s) deplace(s)", moved, ok = glyph(Glyph::Ok));
+                                    },
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                }
+                                continue;
+                            },
+                            Some("--preview") => {},
+                            Some(_) => {
+                                println!("{err} Usage: defrag [--preview]", err = glyph(Glyph::Err));
+                                continue;
+                            },
+                        }
+                        match fs.defrag_preview() {
+                            Ok(preview) => {
+                                println!("{disk} Apercu de la defragmentation:", disk = glyph(Glyph::Disk));
+                                println!("  Fragmentation de l'espace libre: {:.0}%", preview.fragmentation * 100.0);
+                                println!("  Fichiers fragmentes: {}", preview.fragmented_files);
+                                println!("  Clusters a deplacer: {}", preview.cluster_moves);
+                                println!("  Plus grand bloc libre apres: {} clusters ({} KB)",
+                                         preview.largest_free_run, preview.largest_free_run as usize * fs.cluster_size() / 1024);
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande chains : montre comment la FAT relie les clusters de chaque fichier
+                    "chains" => {
+                        let chains = fs.all_chains();
+                        if chains.is_empty() {
+                            println!("{folder} Aucun fichier trouve", folder = glyph(Glyph::Folder));
+                        }
+                        // Affichage cluster par cluster : pas de chaîne de caractères par cluster dans le heap
+                        for chain in &chains {
+                            print!("  {doc} {}: ", chain.name, doc = glyph(Glyph::File));
+                            match &chain.clusters {
+                                Ok(clusters) => {
+                                    for cluster in clusters {
+                                        print!("{} -> ", cluster);
+                                    }
+                                    println!("EOC");
+                                },
+                                Err(e) => println!("{err} {}", e, err = glyph(Glyph::Err)),
+                            }
+                            // Cross-link : un cluster ne doit appartenir qu'à un seul fichier
+                            if !chain.shared.is_empty() {
+                                print!("     {warn}  Cross-link: cluster(s)", warn = glyph(Glyph::Warn));
+                                for cluster in &chain.shared {
+                                    print!(" {}", cluster);
+                                }
+                                println!(" partage(s) avec un autre fichier (voir crosslinks)");
+                            }
+                        }
+                    },
+                    
+                    // Commande crosslinks : uniquement les clusters partagés entre plusieurs fichiers
+                    "crosslinks" => {
+                        let crosslinks = fs.find_crosslinks();
+                        if crosslinks.is_empty() {
+                            println!("{ok} Aucun cluster partage entre fichiers", ok = glyph(Glyph::Ok));
+                        }
+                        for (cluster, names) in crosslinks {
+                            println!("{warn}  Cluster {} partage par {}", cluster, names.join(", "), warn = glyph(Glyph::Warn));
+                        }
+                    },
+                    
+                    // Commande nth : accès direct au n-ième cluster d'un fichier (0 = premier)
+                    "nth" => {
+                        if parts.len() != 3 {
+                            println!("{err} Usage: nth <nom> <n>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        match parts[2].parse::<usize>() {
+                            Ok(n) => match fs.nth_cluster_of(parts[1], n) {
+                                Ok(cluster) => println!("{pin} Cluster {} de '{}': {}", n, parts[1], cluster, pin = glyph(Glyph::Pin)),
+                                Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                            },
+                            Err(_) => println!("{err} Position invalide: '{}'", parts[2], err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande logical : repère les fichiers dont la fin n'est que du bourrage à zéro
+                    "logical" => {
+                        if parts.len() != 2 {
+                            println!("{err} Usage: logical <nom>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+
+                        let result = fs.logical_size(parts[1]).map(|logical| (logical, fs.find_file(parts[1]).map_or(0, |file| file.size)));
+                        match result {
+                            Ok((logical, size)) => println!(
+                                "{info}  '{}': {} octets utiles sur {} ({} octets nuls en fin)",
+                                parts[1], logical, size, size - logical, info = glyph(Glyph::Info)
+                            ),
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+
+                    // Commande reserve : primitive bas niveau, marque n clusters sans les relier
+                    "reserve" => {
+                        // Avec --contiguous : une seule suite de clusters consécutifs, déjà chaînés
+                        if parts.get(2) == Some(&"--contiguous") {
+                            match parts[1].parse::<u32>() {
+                                Ok(count) => match fs.allocate_contiguous(count) {
+                                    Ok(first) => println!("{ok} Clusters {} a {} alloues et chaines", first, first + count - 1, ok = glyph(Glyph::Ok)),
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                },
+                                Err(_) => println!("{err} Usage: reserve <n> --contiguous", err = glyph(Glyph::Err)),
+                            }
+                            continue;
+                        }
+                        match parts.get(1).map(|v| v.parse::<u32>()) {
+                            Some(Ok(count)) => match fs.reserve_clusters(count) {
+                                Ok(clusters) => println!("{ok} Clusters reserves: {:?}", clusters, ok = glyph(Glyph::Ok)),
+                                Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                            },
+                            _ => println!("{err} Usage: reserve <n>", err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande writeat : écriture partielle dans un cluster, le reste est préservé
+                    "writeat" => {
+                        if parts.len() < 4 {
+                            println!("{err} Usage: writeat <cluster> <offset> <texte>", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        
+                        let (cluster, offset) = match (parts[1].parse::<u32>(), parts[2].parse::<usize>()) {
+                            (Ok(c), Ok(o)) => (c, o),
+                            _ => {
+                                println!("{err} Cluster ou offset invalide", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        let text = parts[3..].join(" ");
+                        
+                        match fs.write_cluster_at(cluster, offset, text.as_bytes()) {
+                            Ok(_) => {
+                                changes.cluster_written(fs, cluster);
+                                println!("{ok} {} octets ecrits dans le cluster {} a l'offset {}", 
+                                         text.len(), cluster, offset, ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande fatfill : reformate la FAT avec un motif diagnostique dans le quartet réservé
+                    "fatfill" => {
+                        match parts.get(1).map(|v| u8::from_str_radix(v, 16)) {
+                            Some(Ok(nibble)) => {
+                                let before = fs.list_files(true);
+                                match fs.format_fat(nibble) {
+                                    Ok(_) => {
+                                        changes.record_listing_diff(&before, &fs.list_files(true));
+                                        println!("{ok} FAT reformatee avec le motif 0x{:X} (tous les fichiers sont effaces)", nibble, ok = glyph(Glyph::Ok));
+                                    },
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                }
+                            },
+                            _ => println!("{err} Usage: fatfill <0-F>", err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande bootsector : octets bruts du secteur 0 + contrôles de base
+                    "bootsector" => {
+                        let bytes = fs.boot_sector_bytes();
+                        println!("{disk} Boot sector (secteur 0):", disk = glyph(Glyph::Disk));
+                        hexdump(&bytes);
+                        if bytes[510..512] == [0x55, 0xAA] {
+                            println!("{ok} Signature 0x55AA presente", ok = glyph(Glyph::Ok));
+                        } else {
+                            println!("{err} Signature 0x55AA absente", err = glyph(Glyph::Err));
+                        }
+                        if &bytes[82..90] == b"FAT32   " {
+                            println!("{ok} Type de systeme de fichiers: FAT32", ok = glyph(Glyph::Ok));
+                        } else {
+                            println!("{err} Type de systeme de fichiers inattendu", err = glyph(Glyph::Err));
                         }
                     },
                     
+                    // Commande sector : dump hexadécimal d'un secteur quelconque
+                    "sector" => {
+                        let lba = match parts.get(1).map(|v| v.parse::<u32>()) {
+                            Some(Ok(lba)) => lba,
+                            _ => {
+                                println!("{err} Usage: sector <lba>", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        match fs.read_sector(lba) {
+                            Ok(bytes) => {
+                                println!("{disk} Secteur {} (offset {}):", lba, lba as usize * 512, disk = glyph(Glyph::Disk));
+                                hexdump(bytes);
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande bootbackup : montre la redondance du boot sector
+                    "bootbackup" => {
+                        match fs.read_backup_boot_sector() {
+                            Ok(backup) => {
+                                let sector = fs.boot_sector.backup_boot_sector;
+                                println!("{disk} Backup du boot sector (secteur {}):", sector, disk = glyph(Glyph::Disk));
+                                hexdump(backup);
+                                if fs.compare_boot_sectors() {
+                                    println!("{ok} Le backup est identique au boot sector principal", ok = glyph(Glyph::Ok));
+                                } else {
+                                    println!("{err} Le backup differe du boot sector principal", err = glyph(Glyph::Err));
+                                }
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande mem : état du heap de l'allocateur bump
+                    "mem" => {
+                        if parts.get(1) == Some(&"reset") {
+                            allocator::ALLOCATOR.reset_peak();
+                            println!("{ok} Pic remis au niveau actuel", ok = glyph(Glyph::Ok));
+                        }
+                        println!("{disk} Heap: {} / {} octets utilises, pic {}, restant {}", 
+                                 allocator::ALLOCATOR.allocated_bytes(), allocator::ALLOCATOR.heap_size(),
+                                 allocator::ALLOCATOR.peak_bytes(), allocator::ALLOCATOR.remaining(),
+                                 disk = glyph(Glyph::Disk));
+                        println!("  {}", render_usage_bar(allocator::ALLOCATOR.allocated_bytes(), allocator::ALLOCATOR.heap_size(), 32));
+                        let (start, end) = allocator::ALLOCATOR.heap_range();
+                        println!("  Zone: 0x{:X} - 0x{:X} (debut aligne sur {} octets)", 
+                                 start, end, 1usize << start.trailing_zeros().min(12));
+                        println!("  Granularite minimale d'allocation: {} octet(s)", allocator::ALLOCATOR.min_alloc_granularity());
+                    },
+                    
+                    // Commande memprofile : rejoue la charge de la démo sur un heap simulé
+                    "memprofile" => {
+                        let workload = demo_workload(fs);
+                        let heap_size = allocator::ALLOCATOR.heap_size();
+                        let profile = allocator::BumpAllocator::simulate(&workload, heap_size);
+                        println!("{disk} Profil memoire de la demo ({} allocations)", workload.len(), disk = glyph(Glyph::Disk));
+                        println!("  Pic simule: {} / {} octets", profile.peak, heap_size);
+                        match profile.first_failure {
+                            Some(index) => println!("{err} OOM a l'allocation #{} ({} reussies)", 
+                                                    index, profile.allocations, err = glyph(Glyph::Err)),
+                            None => println!("{ok} Tient dans HEAP_SIZE", ok = glyph(Glyph::Ok)),
+                        }
+                        // Le vrai heap n'est jamais libéré : la démo doit aussi tenir dans ce qui reste
+                        let remaining = allocator::ALLOCATOR.remaining();
+                        if profile.peak <= remaining && profile.first_failure.is_none() {
+                            println!("{ok} Tient aussi dans la memoire restante ({} octets)", remaining, ok = glyph(Glyph::Ok));
+                        } else {
+                            println!("{warn} Depasserait la memoire restante ({} octets)", remaining, warn = glyph(Glyph::Warn));
+                        }
+                    },
+                    
+                    // Commande memwarn : change le seuil d'alerte et la réarme
+                    "memwarn" => {
+                        match parts.get(1).map(|v| v.parse::<usize>()) {
+                            Some(Ok(threshold)) => {
+                                allocator::ALLOCATOR.set_low_memory_threshold(threshold);
+                                println!("{ok} Alerte memoire basse sous {} octets (restant: {})", 
+                                         threshold, allocator::ALLOCATOR.remaining(), ok = glyph(Glyph::Ok));
+                            },
+                            _ => println!("{err} Usage: memwarn <octets>", err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commandes snapshot / restore : annulation rapide, seules la FAT et la racine sont copiées
+                    "snapshot" => {
+                        let snapshot = fs.snapshot_metadata();
+                        println!("{ok} Snapshot des metadonnees pris ({} octets)", snapshot.size_bytes(), ok = glyph(Glyph::Ok));
+                        meta_snapshot = Some(snapshot);
+                    },
+                    
+                    "restore" => {
+                        let Some(snapshot) = meta_snapshot.as_ref() else {
+                            println!("{err} Aucun snapshot (utilisez d'abord 'snapshot')", err = glyph(Glyph::Err));
+                            continue;
+                        };
+                        let before = fs.list_files(true);
+                        match fs.restore_metadata(snapshot) {
+                            Ok(_) => {
+                                changes.record_listing_diff(&before, &fs.list_files(true));
+                                println!("{ok} FAT et repertoire racine restaures", ok = glyph(Glyph::Ok));
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande oplog : journal des opérations, pour reproduire un problème avec replay
+                    "oplog" => {
+                        match parts.as_slice() {
+                            [_, "on"] => {
+                                fs.start_op_log();
+                                if !fs.list_files(true).is_empty() {
+                                    println!("{warn}  Le disque n'est pas vide : un replay ne recreera pas les fichiers deja presents", warn = glyph(Glyph::Warn));
+                                }
+                                println!("{ok} Journal des operations demarre", ok = glyph(Glyph::Ok));
+                            },
+                            [_, "off"] => match fs.stop_op_log() {
+                                Some(log) => println!("{ok} Journal arrete ({} etape(s) oubliee(s))", log.len(), ok = glyph(Glyph::Ok)),
+                                None => println!("{info}  Le journal n'etait pas actif", info = glyph(Glyph::Info)),
+                            },
+                            [_, "save", path] => match fs.op_log() {
+                                Some(log) => match std::fs::write(path, op_log_to_text(log)) {
+                                    Ok(_) => println!("{ok} {} etape(s) ecrite(s) dans '{}'", log.len(), path, ok = glyph(Glyph::Ok)),
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                },
+                                None => println!("{err} Journal inactif (oplog on)", err = glyph(Glyph::Err)),
+                            },
+                            [_, "load", path] => match std::fs::read_to_string(path) {
+                                Ok(text) => match parse_op_log(&text) {
+                                    Ok(log) => {
+                                        println!("{ok} {} etape(s) chargee(s) depuis '{}'", log.len(), path, ok = glyph(Glyph::Ok));
+                                        fs.load_op_log(log);
+                                    },
+                                    Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                                },
+                                Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                            },
+                            [_] => match fs.op_log() {
+                                Some([]) => println!("{info}  Journal vide", info = glyph(Glyph::Info)),
+                                Some(log) => {
+                                    println!("{folder} Journal ({} etape(s)):", log.len(), folder = glyph(Glyph::Folder));
+                                    for (step, logged) in log.iter().enumerate() {
+                                        let description = match &logged.operation {
+                                            Operation::Create { name, data } => format!("create {} ({} octets)", name, data.len()),
+                                            Operation::CreateAt { name, data, cluster } => {
+                                                format!("createat {} {} ({} octets)", cluster, name, data.len())
+                                            },
+                                            Operation::CreateLfn { long_name, short_name, data } => {
+                                                format!("createlfn {} {} ({} octets)", long_name, short_name, data.len())
+                                            },
+                                            Operation::Write { name, data } => format!("write {} ({} octets)", name, data.len()),
+                                            Operation::Append { name, data } => format!("append {} ({} octets)", name, data.len()),
+                                            Operation::Preallocate { name, size } => format!("prealloc {} {}", name, size),
+                                            Operation::CreateSparse { name, size } => format!("sparse {} {}", name, size),
+                                            Operation::WriteRange { name, offset, data } => {
+                                                format!("writerange {} {} ({} octets)", name, offset, data.len())
+                                            },
+                                            Operation::Truncate { name, size } => format!("truncate {} {}", name, size),
+                                            Operation::SetClusters { name, count } => format!("setclusters {} {}", name, count),
+                                            Operation::Compact { name, ranges } => format!("compact {} ({} plage(s))", name, ranges.len()),
+                                            Operation::Delete { name } => format!("delete {}", name),
+                                            Operation::Shred { name, passes } => format!("shred {} {}", name, passes),
+                                            Operation::Rename { from, to } => format!("rename {} {}", from, to),
+                                            Operation::Swap { a, b } => format!("swap {} {}", a, b),
+                                            Operation::Attrib { name, attributes } => format!("attrib {} 0x{:02X}", name, attributes),
+                                            Operation::Mkdir { path } => format!("mkdir {}", path),
+                                            Operation::WriteAt { cluster, offset, data } => {
+                                                format!("writeat {} {} ({} octets)", cluster, offset, data.len())
+                                            },
+                                            Operation::Reserve { count, contiguous: false } => format!("reserve {}", count),
+                                            Operation::Reserve { count, contiguous: true } => format!("reserve {} --contiguous", count),
+                                            Operation::FatFill { pattern } => format!("fatfill {:X}", pattern),
+                                            Operation::Repair => "repair".to_string(),
+                                            Operation::Recover => "recover".to_string(),
+                                            Operation::Defrag => "defrag".to_string(),
+                                            Operation::Fragment { files } => format!("fragment {}", files),
+                                            Operation::ImportBundle { bundle } => format!("unbundle ({} octets)", bundle.len()),
+                                        };
+                                        println!("  {:>3}. {}  {}", step, logged.at, description);
+                                    }
+                                },
+                                None => println!("{info}  Journal inactif (oplog on pour le demarrer)", info = glyph(Glyph::Info)),
+                            },
+                            _ => println!("{err} Usage: oplog [on|off|save <chemin>|load <chemin>]", err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande replay : reformate puis rejoue le journal, et compare l'image obtenue
+                    "replay" => {
+                        let from_step = match parts.get(1).map(|v| v.parse::<usize>()) {
+                            None => 0,
+                            Some(Ok(step)) => step,
+                            Some(Err(_)) => {
+                                println!("{err} Usage: replay [etape_de_depart]", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        };
+                        let before = fs.image_checksum();
+                        let files_before = fs.list_files(true);
+                        match fs.replay(from_step) {
+                            Ok(steps) => {
+                                let after = fs.image_checksum();
+                                changes.record_listing_diff(&files_before, &fs.list_files(true));
+                                println!("{ok} {} etape(s) rejouee(s) sur un disque reformate", steps, ok = glyph(Glyph::Ok));
+                                println!("   Empreinte avant: {:08X}  apres: {:08X}", before, after);
+                                if before == after {
+                                    println!("{ok} Image identique a l'originale", ok = glyph(Glyph::Ok));
+                                } else {
+                                    println!("{warn}  Image differente (etapes sautees, ou operations non journalisees)", warn = glyph(Glyph::Warn));
+                                }
+                            },
+                            Err(e) => println!("{err} Erreur: {}", e, err = glyph(Glyph::Err)),
+                        }
+                    },
+                    
+                    // Commande changes : ce que la session a modifié sur le disque
+                    "changes" => {
+                        changes.print();
+                    },
+                    
                     "demo" => {
                         test_fat32_demo(fs);
                     },
                     
+                    // Commande readonly : sans argument affiche le mode, sinon l'active ou le désactive
+                    "readonly" => {
+                        match parts.get(1).copied() {
+                            Some("on") => fs.set_read_only(true),
+                            Some("off") => fs.set_read_only(false),
+                            None => {}
+                            _ => {
+                                println!("{err} Usage: readonly [on|off]", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        }
+                        let mode = if fs.is_read_only() { "lecture seule" } else { "lecture/ecriture" };
+                        println!("{ok} Volume en {}", mode, ok = glyph(Glyph::Ok));
+                    },
+                    
+                    // Commande audit : modifications refusées en lecture seule
+                    "audit" => {
+                        let audit = fs.audit_log();
+                        if audit.is_empty() {
+                            println!("{info}  Aucune modification refusee", info = glyph(Glyph::Info));
+                            continue;
+                        }
+                        println!("{folder} Modifications refusees ({}):", audit.len(), folder = glyph(Glyph::Folder));
+                        for entry in audit {
+                            println!("  {}  {} {}", entry.at, entry.operation, entry.arguments);
+                        }
+                    },
+                    
+                    // Commande color : bascule entre emoji/Unicode et ASCII pur
+                    "color" => {
+                        match parts.get(1).copied() {
+                            Some("on") => ui::set_ascii_mode(false),
+                            Some("off") => ui::set_ascii_mode(true),
+                            _ => {
+                                println!("{err} Usage: color on|off", err = glyph(Glyph::Err));
+                                continue;
+                            }
+                        }
+                        let mode = if ui::ascii_mode() { "ASCII" } else { "Unicode" };
+                        println!("{ok} Affichage en mode {}", mode, ok = glyph(Glyph::Ok));
+                    },
+                    
+                    // Commande alias : sans argument liste les alias, sinon définit (ou remplace) un alias
+                    "alias" => {
+                        let [_, name, ..] = parts.as_slice() else {
+                            if aliases.is_empty() {
+                                println!("{info}  Aucun alias defini", info = glyph(Glyph::Info));
+                            }
+                            for (name, command) in &aliases {
+                                println!("  {} = \"{}\"", name, command);
+                            }
+                            continue;
+                        };
+                        // La commande est tout le reste de la ligne, guillemets facultatifs
+                        let command = input
+                            .split_once(*name)
+                            .map_or("", |(_, rest)| rest)
+                            .trim()
+                            .trim_matches('"')
+                            .trim();
+                        if command.is_empty() {
+                            println!("{err} Usage: alias <nom> \"<commande>\"", err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        if name.eq_ignore_ascii_case("alias") || name.eq_ignore_ascii_case("unalias") {
+                            println!("{err} '{}' ne peut pas etre redefini", name, err = glyph(Glyph::Err));
+                            continue;
+                        }
+                        aliases.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+                        aliases.push((name.to_string(), command.to_string()));
+                        println!("{ok} Alias '{}' -> \"{}\"", name, command, ok = glyph(Glyph::Ok));
+                    },
+                    
+                    "unalias" => {
+                        let [_, name] = parts.as_slice() else {
+                            println!("{err} Usage: unalias <nom>", err = glyph(Glyph::Err));
+                            continue;
+                        };
+                        let before = aliases.len();
+                        aliases.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+                        if aliases.len() == before {
+                            println!("{err} Alias inconnu: '{}'", name, err = glyph(Glyph::Err));
+                        } else {
+                            println!("{ok} Alias '{}' supprime", name, ok = glyph(Glyph::Ok));
+                        }
+                    },
+                    
                     "quit" | "exit" | "q" => {
-                        println!("👋 Au revoir!");
+                        println!("{bye} Au revoir!", bye = glyph(Glyph::Bye));
                         break;
                     },
                     
                     _ => {
-                        println!("❌ Commande inconnue: '{}'. Tapez 'help' pour l'aide.", parts[0]);
+                        println!("{err} Commande inconnue: '{}'. Tapez 'help' pour l'aide.", parts[0], err = glyph(Glyph::Err));
                     }
                 }
             },
             Err(e) => {
-                println!("❌ Erreur de lecture: {}", e);
+                println!("{err} Erreur de lecture: {}", e, err = glyph(Glyph::Err));
                 break;
             }
         }
@@ -211,7 +1915,7 @@ fn terminal_interactif(fs: &mut Fat32FileSystem) {
 }
 
 fn test_fat32_demo(fs: &mut Fat32FileSystem) {
-    println!("\n🎬 === DEMONSTRATION AUTOMATIQUE ===");
+    println!("\n{demo} === DEMONSTRATION AUTOMATIQUE ===", demo = glyph(Glyph::Demo));
     
     // Test de création de fichiers
     println!("\n--- Creation de fichiers de demonstration ---");
@@ -225,14 +1929,14 @@ fn test_fat32_demo(fs: &mut Fat32FileSystem) {
     for (name, content) in test_files.iter() {
         // Vérifier si le fichier existe déjà
         if fs.find_file(name).is_some() {
-            println!("ℹ️  Fichier {} existe deja", name);
+            println!("{info}  Fichier {} existe deja", name, info = glyph(Glyph::Info));
             continue;
         }
         
         match fs.create_file_named(name, content) {
-            Ok(cluster) => println!("✅ Fichier '{}' cree dans le cluster {} ({} octets)", 
-                                   name, cluster, content.len()),
-            Err(e) => println!("❌ Erreur creation {}: {}", name, e),
+            Ok(cluster) => println!("{ok} Fichier '{}' cree dans le cluster {} ({} octets)", 
+                                   name, cluster, content.len(), ok = glyph(Glyph::Ok)),
+            Err(e) => println!("{err} Erreur creation {}: {}", name, e, err = glyph(Glyph::Err)),
         }
     }
     
@@ -242,49 +1946,133 @@ fn test_fat32_demo(fs: &mut Fat32FileSystem) {
         match fs.read_file_by_name(name) {
             Ok(data) => {
                 let content = std::str::from_utf8(&data).unwrap_or("Donnees binaires");
-                println!("✅ Lecture {}: \"{}\"", name, content);
+                println!("{ok} Lecture {}: \"{}\"", name, content, ok = glyph(Glyph::Ok));
             },
-            Err(e) => println!("❌ Erreur lecture {}: {}", name, e),
+            Err(e) => println!("{err} Erreur lecture {}: {}", name, e, err = glyph(Glyph::Err)),
+        }
+    }
+    
+    // Contenu binaire : la taille vient de l'entrée de répertoire, les octets nuls ne coupent pas le fichier
+    println!("\n--- Test de contenu binaire ---");
+    let binary: &[u8] = b"AB\0\0CD\0EF\0";
+    if fs.find_file("DATA.BIN").is_none() {
+        if let Err(e) = fs.create_file_named("DATA.BIN", binary) {
+            println!("{err} Erreur creation DATA.BIN: {}", e, err = glyph(Glyph::Err));
         }
     }
+    match fs.read_file_by_name("DATA.BIN") {
+        Ok(data) if data == binary => println!("{ok} DATA.BIN relu a l'identique ({} octets dont 4 nuls)", 
+                                                data.len(), ok = glyph(Glyph::Ok)),
+        Ok(data) => println!("{err} DATA.BIN relu avec {} octets au lieu de {}", 
+                             data.len(), binary.len(), err = glyph(Glyph::Err)),
+        Err(e) => println!("{err} Erreur lecture DATA.BIN: {}", e, err = glyph(Glyph::Err)),
+    }
     
     // Vérification
     if let Err(e) = fs.check_filesystem() {
-        println!("❌ Erreur verification: {}", e);
+        println!("{err} Erreur verification: {}", e, err = glyph(Glyph::Err));
     }
     
     fs.summary();
     
-    println!("✅ Demonstration terminee");
+    // Aucune opération ne doit avoir débordé du storage
+    match check_guards() {
+        Ok(_) => println!("{ok} Zones de garde intactes", ok = glyph(Glyph::Ok)),
+        Err(e) => println!("{err} {}", e, err = glyph(Glyph::Err)),
+    }
+    
+    println!("{ok} Demonstration terminee", ok = glyph(Glyph::Ok));
 }
 
 fn main() {
-    println!("🚀 === SYSTEME FAT32 AVEC ALLOCATEUR PERSONNALISE ===");
+    // Option --ascii : pas d'emoji, pour les terminaux limités et les logs
+    if std::env::args().skip(1).any(|arg| arg == "--ascii") {
+        ui::set_ascii_mode(true);
+    }
+    
+    println!("{rocket} === SYSTEME FAT32 AVEC ALLOCATEUR PERSONNALISE ===", rocket = glyph(Glyph::Rocket));
     println!("Projet étudiant : Implémentation d'un filesystem sans libc malloc");
     println!("Architecture : Allocateur bump + FAT32 basique + Terminal interactif");
     
     // Étape 1 : Test de notre allocateur personnalisé
     test_allocator();
     
+    // Alerte avant l'OOM : le heap bump ne libère jamais rien
+    allocator::ALLOCATOR.set_low_memory_threshold(LOW_MEMORY_THRESHOLD);
+    
     // Étape 2 : Initialisation du système de fichiers FAT32
     println!("\n=== Initialisation du système FAT32 ===");
-    let storage = unsafe { &mut DISK_STORAGE };  // Récupération de notre "disque"
-    let mut fs = match Fat32FileSystem::new(storage) {
-        Ok(fs) => {
-            println!("✅ Systeme de fichiers FAT32 cree avec succes!");
+    let storage = unsafe { &mut *core::ptr::addr_of_mut!(DISK.storage) };  // Récupération de notre "disque"
+    let blank = Fat32FileSystem::is_blank(storage);
+    // Option --max-clusters=N : ne voir qu'une partie du disque, pour simuler un petit volume
+    let max_clusters = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--max-clusters=").and_then(|n| n.parse::<u32>().ok()));
+    // Option --cluster-size=N : nombre de secteurs par cluster au formatage (1, 2, 4, ... 64)
+    let cluster_size = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--cluster-size=").and_then(|n| n.parse::<u8>().ok()));
+    // Option --image=fichier.img : monter une image existante au lieu de formater
+    let image = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--image=").map(String::from));
+    // Option --mmap=fichier.img : monter une image en place, les écritures vont directement dans le fichier
+    let mapped = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--mmap=").map(String::from));
+    // Les messages suivent le chemin choisi : une image ou un volume existant est monté, pas formaté
+    let formats = image.is_none() && mapped.is_none() && (blank || max_clusters.is_some());
+    match (&mapped, &image) {
+        (Some(path), _) => println!("{info}  Montage de l'image '{}' projetee en memoire", path, info = glyph(Glyph::Info)),
+        (None, Some(path)) => println!("{info}  Montage de l'image '{}'", path, info = glyph(Glyph::Info)),
+        (None, None) if blank => println!("{info}  Disque vierge detecte, formatage en FAT32", info = glyph(Glyph::Info)),
+        (None, None) if formats => println!("{info}  Formatage en FAT32 (--max-clusters)", info = glyph(Glyph::Info)),
+        (None, None) => println!("{info}  Le disque contient deja des donnees, montage du volume existant", info = glyph(Glyph::Info)),
+    }
+    // Un volume monté garde la géométrie écrite dans son boot sector
+    if cluster_size.is_some() && !formats {
+        println!("{warn}  --cluster-size ignore : le volume existant garde sa taille de cluster", warn = glyph(Glyph::Warn));
+    }
+    let opened = match (mapped, image, max_clusters, cluster_size) {
+        (Some(path), _, _, _) => open_mapped_image(&path),
+        (None, Some(path), _, _) => Fat32FileSystem::from_image(storage, std::path::Path::new(&path)),
+        (None, None, Some(limit), None) => Fat32FileSystem::open_limited(storage, limit),
+        (None, None, Some(limit), Some(sectors)) => Fat32FileSystem::open_limited_with_cluster_size(storage, limit, sectors),
+        (None, None, None, None) if blank => Fat32FileSystem::new(storage),
+        (None, None, None, Some(sectors)) if blank => Fat32FileSystem::new_with_cluster_size(storage, sectors),
+        (None, None, None, _) => Fat32FileSystem::open(storage),
+    };
+    let mut fs = match opened {
+        Ok(fs) if formats => {
+            println!("{ok} Systeme de fichiers FAT32 cree avec succes!", ok = glyph(Glyph::Ok));
             println!("   - Boot sector écrit (signature 0xAA55)");
             println!("   - Table FAT initialisée"); 
             println!("   - {} clusters disponibles", fs.total_clusters);
             fs
         },
-        Err(e) => {
-            println!("❌ Erreur lors de la creation du FS: {}", e);
+        Ok(fs) => {
+            println!("{ok} Systeme de fichiers FAT32 monte avec succes!", ok = glyph(Glyph::Ok));
+            println!("   - {} clusters disponibles", fs.total_clusters);
+            report_data_crc(&fs);
+            fs
+        },
+        Err(e) if formats => {
+            println!("{err} Erreur lors de la creation du FS: {}", e, err = glyph(Glyph::Err));
             return;  // Abandon si on peut pas créer le FS
+        },
+        Err(e) => {
+            println!("{err} Erreur lors du montage du FS: {}", e, err = glyph(Glyph::Err));
+            return;
         }
     };
+    // Option --read-only : auditer une image sans risquer de la modifier
+    if std::env::args().skip(1).any(|arg| arg == "--read-only") {
+        fs.set_read_only(true);
+        println!("{info}  Volume en lecture seule : les modifications refusees sont listees par audit", info = glyph(Glyph::Info));
+    }
     
     // Étape 3 : Menu utilisateur (interface humaine)
-    println!("\n📋 === MENU PRINCIPAL ===");
+    println!("\n{menu} === MENU PRINCIPAL ===", menu = glyph(Glyph::Menu));
     println!("Choisissez votre mode d'interaction :");
     println!("[1] Demonstration automatique (fichiers pre-definis)");
     println!("[2] Terminal interactif (vous tapez les commandes)");
@@ -298,11 +2086,11 @@ fn main() {
     // Dispatch selon le choix
     match input.trim() {
         "1" => {
-            println!("\n🎬 Mode démonstration sélectionné");
+            println!("\n{demo} Mode démonstration sélectionné", demo = glyph(Glyph::Demo));
             test_fat32_demo(&mut fs);
         },
         "2" => {
-            println!("\n💻 Mode terminal interactif sélectionné"); 
+            println!("\n{terminal} Mode terminal interactif sélectionné", terminal = glyph(Glyph::Terminal)); 
             terminal_interactif(&mut fs);
         },
         _ => {
@@ -310,9 +2098,185 @@ fn main() {
             test_fat32_demo(&mut fs);
         }
     }
+    // Alerte levée pendant la démo (ou la dernière commande du terminal)
+    report_low_memory();
+    // Démontage : CRC de la zone de données (feature data-crc) et écriture de l'image projetée (--mmap)
+    if let Err(e) = fs.unmount() {
+        println!("{err} Erreur: {}", e, err = glyph(Glyph::Err));
+    }
     
-    println!("\n🎉 === PROGRAMME TERMINE ===");
+    println!("\n{party} === PROGRAMME TERMINE ===", party = glyph(Glyph::Party));
     println!("Merci d'avoir testé notre implémentation FAT32 !");
     // Note : pas besoin de free() grâce à notre allocateur bump 
     // (tout est libéré automatiquement à la fin du programme)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_prints_to_terminal_or_redirects_to_host() {
+        let args = parse_read_args(&["read", "A.TXT"]).unwrap();
+        assert_eq!((args.name, args.target, args.range), ("A.TXT", ReadTarget::Terminal, None));
+
+        let args = parse_read_args(&["read", "A.TXT", ">", "/tmp/a.txt"]).unwrap();
+        assert_eq!(args.target, ReadTarget::HostFile("/tmp/a.txt"));
+        let args = parse_read_args(&["read", "A.TXT", "--out", "/tmp/a.txt"]).unwrap();
+        assert_eq!(args.target, ReadTarget::HostFile("/tmp/a.txt"));
+
+        let args = parse_read_args(&["read", "A.TXT", "10", "20"]).unwrap();
+        assert_eq!((args.target, args.range), (ReadTarget::Terminal, Some((10, 20))));
+    }
+
+    #[test]
+    fn read_rejects_incomplete_or_mixed_arguments() {
+        assert!(parse_read_args(&["read"]).is_err());
+        assert!(parse_read_args(&["read", "A.TXT", ">"]).is_err());
+        assert!(parse_read_args(&["read", "A.TXT", "0", "10", ">", "/tmp/a.txt"]).is_err());
+    }
+
+    #[test]
+    fn decodes_bytes_with_the_chosen_encoding() {
+        assert_eq!(decode_bytes(&[0xE9], Encoding::Latin1), "é");
+        assert_eq!(decode_bytes(&[0xE9], Encoding::Ascii), "?");
+        assert_eq!(decode_bytes(&[0xE9], Encoding::Utf8), "\u{FFFD}");
+        assert_eq!(decode_bytes("é".as_bytes(), Encoding::Utf8), "é");
+        assert_eq!(parse_read_args(&["read", "A.TXT", "--enc", "latin1"]).unwrap().encoding, Encoding::Latin1);
+    }
+
+    #[test]
+    fn long_read_output_is_truncated_with_a_notice() {
+        let notice = preview_notice("A.TXT", 12000, READ_PREVIEW_LIMIT);
+        assert_eq!(notice.as_deref(), Some("... (affichage de 4096 sur 12000 octets, utilisez 'read A.TXT 0 12000' pour tout voir)"));
+        assert_eq!(preview_notice("A.TXT", 100, READ_PREVIEW_LIMIT), None);
+    }
+
+    #[test]
+    fn sparkline_maps_ratios_to_levels() {
+        let samples = [0.0, 0.15, 0.5, 0.9, 1.0, 1.7, -0.3];
+        assert_eq!(render_sparkline(&samples, false), "▁▂▅▇██▁");
+        assert_eq!(render_sparkline(&samples, true), "_.=*##_");
+        assert_eq!(render_sparkline(&[], false), "");
+    }
+
+    #[test]
+    fn usage_bar_is_proportional() {
+        assert_eq!(render_usage_bar(32, 64, 8), "[####----] 50%");
+        assert_eq!(render_usage_bar(0, 64, 8), "[--------] 0%");
+        assert_eq!(render_usage_bar(100, 64, 4), "[####] 100%");
+    }
+
+    #[test]
+    fn alias_expands_before_dispatch() {
+        let aliases = vec![("ll".to_string(), "ls --sort size".to_string())];
+        assert_eq!(expand_alias(&aliases, "ll"), "ls --sort size");
+        assert_eq!(expand_alias(&aliases, "LL -a"), "ls --sort size -a");
+        assert!(matches!(expand_alias(&aliases, "ls"), Cow::Borrowed("ls")));
+        assert!(matches!(expand_alias(&aliases, "llx"), Cow::Borrowed("llx")));
+    }
+
+    // Volume formaté de 1MB, alloué pour toute la durée du test
+    fn new_fs() -> Fat32FileSystem {
+        Fat32FileSystem::new(Box::leak(vec![0u8; 1024 * 1024].into_boxed_slice())).unwrap()
+    }
+
+    #[test]
+    fn session_changes_group_create_delete_and_rename() {
+        let mut fs = new_fs();
+        let mut changes = SessionChanges::default();
+
+        // Mêmes enregistrements que les commandes create, rm et rename du terminal
+        fs.create_file_named("A.TXT", b"a").unwrap();
+        changes.created("A.TXT");
+        fs.create_file_named("B.TXT", b"b").unwrap();
+        changes.created("B.TXT");
+        fs.delete_file_by_name("B.TXT").unwrap();
+        changes.deleted("B.TXT");
+        let new_name = fs.rename_file("A.TXT", "C.TXT").unwrap();
+        changes.deleted("A.TXT");
+        changes.created(&new_name);
+        // Noms comparés sans la casse : pas de doublon
+        changes.created("c.txt");
+
+        assert_eq!(changes.created, ["A.TXT", "B.TXT", "C.TXT"]);
+        assert!(changes.modified.is_empty());
+        assert_eq!(changes.deleted, ["B.TXT", "A.TXT"]);
+    }
+
+    #[test]
+    fn listing_diff_sorts_files_into_groups() {
+        let mut fs = new_fs();
+        fs.create_file_named("GARDE.TXT", b"1").unwrap();
+        fs.create_file_named("MODIF.TXT", b"2").unwrap();
+        fs.create_file_named("PARTI.TXT", b"3").unwrap();
+        let before = fs.list_files(true);
+
+        fs.append_to_file("MODIF.TXT", b"plus long").unwrap();
+        fs.delete_file_by_name("PARTI.TXT").unwrap();
+        fs.create_file_named("NOUVEAU.TXT", b"4").unwrap();
+        let mut changes = SessionChanges::default();
+        changes.record_listing_diff(&before, &fs.list_files(true));
+
+        assert_eq!(changes.created, ["NOUVEAU.TXT"]);
+        assert_eq!(changes.modified, ["MODIF.TXT"]);
+        assert_eq!(changes.deleted, ["PARTI.TXT"]);
+    }
+
+    #[test]
+    fn writeat_and_volume_wide_commands_show_up_in_changes() {
+        let mut fs = new_fs();
+        fs.create_file_named("A.TXT", &[1u8; 5000]).unwrap();
+        fs.create_file_named("B.TXT", b"b").unwrap();
+        let mut changes = SessionChanges::default();
+
+        // writeat dans le deuxième cluster de A.TXT, puis dans un cluster libre (aucun fichier)
+        let cluster = fs.nth_cluster_of("A.TXT", 1).unwrap();
+        fs.write_cluster_at(cluster, 0, b"x").unwrap();
+        changes.cluster_written(&fs, cluster);
+        changes.cluster_written(&fs, fs.find_free_cluster().unwrap());
+        assert_eq!(changes.modified, ["A.TXT"]);
+
+        // defrag : B.TXT descend dans le trou laissé par A.TXT
+        let snapshot = fs.snapshot_metadata();
+        fs.delete_file_by_name("A.TXT").unwrap();
+        changes.deleted("A.TXT");
+        let before = fs.list_files(true);
+        fs.defragment().unwrap();
+        changes.record_listing_diff(&before, &fs.list_files(true));
+        assert_eq!(changes.modified, ["A.TXT", "B.TXT"]);
+
+        // restore : A.TXT revient
+        let before = fs.list_files(true);
+        fs.restore_metadata(&snapshot).unwrap();
+        changes.record_listing_diff(&before, &fs.list_files(true));
+        assert_eq!(changes.created, ["A.TXT"]);
+
+        // setclustersize : le reformatage supprime tout
+        let before = fs.list_files(true);
+        fs.set_cluster_size(4).unwrap();
+        changes.record_listing_diff(&before, &fs.list_files(true));
+        assert_eq!(changes.deleted, ["A.TXT", "B.TXT"]);
+    }
+
+
+    #[test]
+    fn writes_outside_the_disk_trip_the_guard_check() {
+        // Même disposition que GuardedDisk, en petit : garde, storage, garde
+        let mut disk = vec![GUARD_PATTERN; GUARD_SIZE];
+        disk.extend_from_slice(&[0; 512]);
+        disk.extend_from_slice(&[GUARD_PATTERN; GUARD_SIZE]);
+        let check = |disk: &[u8]| check_guard_zones(&disk[..GUARD_SIZE], &disk[GUARD_SIZE + 512..]);
+        assert_eq!(check(&disk), Ok(()));
+
+        // Remplir tout le storage ne touche pas aux gardes
+        disk[GUARD_SIZE..GUARD_SIZE + 512].fill(0xFF);
+        assert_eq!(check(&disk), Ok(()));
+
+        // Un octet écrit juste après la fin du storage
+        disk[GUARD_SIZE + 512] = 0;
+        assert_eq!(check(&disk), Err("Zone de garde APRES le disque ecrasee (overflow)"));
+        disk[GUARD_SIZE + 512] = GUARD_PATTERN;
+        disk[GUARD_SIZE - 1] = 0;
+        assert_eq!(check(&disk), Err("Zone de garde AVANT le disque ecrasee (underflow)"));
+    }
+}
@@ -0,0 +1,80 @@
+// ui.rs - Choix des symboles affichés dans le terminal
+//
+// Les emojis et caractères Unicode s'affichent mal sur certains terminaux et dans les logs.
+// En mode ASCII (option --ascii ou commande "color off"), chaque symbole est remplacé par un
+// équivalent en ASCII pur.
+// Les symboles sont des &'static str : aucune allocation, important avec notre heap bump qui ne libère rien
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+// Mode d'affichage global (false = Unicode/emoji, true = ASCII pur)
+static ASCII_MODE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_ascii_mode(enabled: bool) {
+    ASCII_MODE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn ascii_mode() -> bool {
+    ASCII_MODE.load(Ordering::Relaxed)
+}
+
+// Tous les symboles utilisés dans les messages
+#[derive(Clone, Copy)]
+pub enum Glyph {
+    Ok,       // Opération réussie
+    Err,      // Erreur
+    Warn,     // Avertissement
+    Info,     // Information
+    Folder,   // Liste de fichiers
+    File,     // Un fichier dans une liste
+    Read,     // Contenu lu
+    Disk,     // Espace disque / secteurs
+    Rocket,   // Démarrage
+    Demo,     // Démonstration automatique
+    Party,    // Fin du programme
+    Bye,      // Sortie du terminal
+    Menu,     // Menu principal
+    Terminal, // Mode terminal
+    Ruler,    // Géométrie du volume
+    Pin,      // Position précise
+}
+
+// Renvoie le symbole à afficher selon le mode courant
+pub fn glyph(g: Glyph) -> &'static str {
+    let (unicode, ascii) = match g {
+        Glyph::Ok => ("✅", "[OK]"),
+        Glyph::Err => ("❌", "[ERR]"),
+        Glyph::Warn => ("⚠️", "[!]"),
+        Glyph::Info => ("ℹ️", "[i]"),
+        Glyph::Folder => ("📁", "[DIR]"),
+        Glyph::File => ("📄", "|--"),
+        Glyph::Read => ("📖", "[READ]"),
+        Glyph::Disk => ("💾", "[DISK]"),
+        Glyph::Rocket => ("🚀", ">>"),
+        Glyph::Demo => ("🎬", ">>"),
+        Glyph::Party => ("🎉", "**"),
+        Glyph::Bye => ("👋", "--"),
+        Glyph::Menu => ("📋", "=="),
+        Glyph::Terminal => ("💻", ">>"),
+        Glyph::Ruler => ("📐", "[GEO]"),
+        Glyph::Pin => ("📍", "->"),
+    };
+    if ascii_mode() { ascii } else { unicode }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Seul test à changer le mode global : les deux cas restent dans la même fonction
+    #[test]
+    fn glyphs_follow_the_display_mode() {
+        set_ascii_mode(true);
+        assert_eq!((glyph(Glyph::Ok), glyph(Glyph::Err), glyph(Glyph::File)), ("[OK]", "[ERR]", "|--"));
+        assert!([Glyph::Warn, Glyph::Folder, Glyph::Disk, Glyph::Pin].iter().all(|&g| glyph(g).is_ascii()));
+
+        set_ascii_mode(false);
+        assert_eq!((glyph(Glyph::Ok), glyph(Glyph::Err)), ("✅", "❌"));
+        assert!(!glyph(Glyph::Folder).is_ascii());
+    }
+}